@@ -0,0 +1,617 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use hashbrown::HashMap;
+use rusqlite::{Connection, OpenFlags};
+use serde::Deserialize;
+use zip::{result::ZipError, ZipArchive};
+
+use crate::deck::{Card, Deck, DeckMetadata, Face, MIN_FACE_COUNT};
+
+///Collections in packages exported by older Anki versions, newest first
+const LEGACY_COLLECTIONS: [&str; 2] = ["collection.anki21", "collection.anki2"];
+///Zstd compressed collection of newer Anki versions. Packages with it also contain a
+///placeholder legacy collection, which only holds a note asking to update Anki.
+const COMPRESSED_COLLECTION: &str = "collection.anki21b";
+///Separator of note fields in the notes table
+const FIELD_SEPARATOR: char = '\x1f';
+///Note type kind of cloze notes
+const CLOZE_KIND: u8 = 1;
+
+#[derive(Debug)]
+pub enum AnkiError {
+    IoError(PathBuf, std::io::Error),
+    ZipError(PathBuf, ZipError),
+    SqliteError(PathBuf, rusqlite::Error),
+    SerdeError(PathBuf, serde_json::Error),
+    MissingCollection(PathBuf),
+    CompressedCollection(PathBuf),
+    DeckFileExists(PathBuf),
+}
+
+impl Display for AnkiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(path, err) => f.write_fmt(format_args!(
+                "IoError: {err}, path: {}",
+                path.to_str().unwrap_or("unknown")
+            )),
+            Self::ZipError(path, err) => f.write_fmt(format_args!(
+                "ZipError: {err}, path: {}",
+                path.to_str().unwrap_or("unknown")
+            )),
+            Self::SqliteError(path, err) => f.write_fmt(format_args!(
+                "SqliteError: {err}, path: {}",
+                path.to_str().unwrap_or("unknown")
+            )),
+            Self::SerdeError(path, err) => f.write_fmt(format_args!(
+                "SerdeError: {err}, path: {}",
+                path.to_str().unwrap_or("unknown")
+            )),
+            Self::MissingCollection(path) => f.write_fmt(format_args!(
+                "MissingCollection: Package has no Anki collection, path: {}",
+                path.to_str().unwrap_or("unknown")
+            )),
+            Self::CompressedCollection(path) => f.write_fmt(format_args!(
+                "CompressedCollection: Package was exported by a newer Anki version, re-export it with \"Support older Anki versions\" checked, path: {}",
+                path.to_str().unwrap_or("unknown")
+            )),
+            Self::DeckFileExists(path) => f.write_fmt(format_args!(
+                "DeckFileExists: Deck file already exists, use --force to overwrite it, path: {}",
+                path.to_str().unwrap_or("unknown")
+            )),
+        }
+    }
+}
+
+///Why a note could not be converted to a card
+#[derive(Debug, PartialEq, Eq)]
+pub enum NoteProblem {
+    Cloze,
+    MediaOnly(Vec<String>),
+    NotEnoughFields,
+    DuplicateFront(String),
+}
+
+impl Display for NoteProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cloze => f.write_str("Cloze notes are not supported"),
+            Self::MediaOnly(fields) => f.write_fmt(format_args!(
+                "Too few faces are left without media-only fields: {}",
+                fields.join(", ")
+            )),
+            Self::NotEnoughFields => f.write_fmt(format_args!(
+                "Note has fewer than {MIN_FACE_COUNT} non-empty fields"
+            )),
+            Self::DuplicateFront(front) => f.write_fmt(format_args!(
+                "Front \"{front}\" is the same as an earlier note's"
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SkippedNote {
+    pub id: i64,
+    pub note_type: String,
+    pub problem: NoteProblem,
+}
+
+impl Display for SkippedNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "Skipped {} note {}: {}",
+            self.note_type, self.id, self.problem
+        ))
+    }
+}
+
+///Decks converted from an Anki package, one for each note type, along with every note
+///that couldn't be converted
+#[derive(Debug)]
+pub struct AnkiImport {
+    pub decks: Vec<Deck>,
+    pub skipped: Vec<SkippedNote>,
+}
+
+impl AnkiImport {
+    ///Path of the JSON file the deck is written to within the directory
+    pub fn deck_path(dir: &Path, deck: &Deck) -> PathBuf {
+        dir.join(format!("{}.json", deck.file_stem()))
+    }
+
+    ///Writes every deck to the directory as JSON, failing before writing anything if
+    ///any deck files already exist and aren't to be overwritten
+    pub fn write(&self, dir: &Path, overwrite: bool) -> Result<Vec<PathBuf>, AnkiError> {
+        let paths = self
+            .decks
+            .iter()
+            .map(|deck| Self::deck_path(dir, deck))
+            .collect::<Vec<_>>();
+
+        if let Some(path) = paths.iter().find(|path| !overwrite && path.exists()) {
+            return Err(AnkiError::DeckFileExists(path.clone()));
+        }
+
+        std::fs::create_dir_all(dir).map_err(|err| AnkiError::IoError(dir.to_path_buf(), err))?;
+        for (deck, path) in self.decks.iter().zip(paths.iter()) {
+            std::fs::write(path, deck.to_json())
+                .map_err(|err| AnkiError::IoError(path.clone(), err))?;
+        }
+
+        Ok(paths)
+    }
+}
+
+impl Display for AnkiImport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for skipped in self.skipped.iter() {
+            f.write_fmt(format_args!("{skipped}\n"))?;
+        }
+        f.write_fmt(format_args!(
+            "{} decks, {} cards converted, {} notes skipped\n",
+            self.decks.len(),
+            self.decks
+                .iter()
+                .map(|deck| deck.cards.len())
+                .sum::<usize>(),
+            self.skipped.len()
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct NoteType {
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: u8,
+    flds: Vec<NoteField>,
+}
+
+#[derive(Deserialize)]
+struct NoteField {
+    name: String,
+    ord: usize,
+}
+
+#[derive(Deserialize)]
+struct AnkiDeck {
+    name: String,
+}
+
+struct Note {
+    id: i64,
+    note_type: i64,
+    fields: String,
+    tags: String,
+    deck: Option<i64>,
+}
+
+struct Collection {
+    note_types: HashMap<i64, NoteType>,
+    decks: HashMap<i64, AnkiDeck>,
+    notes: Vec<Note>,
+}
+
+///Converts the notes of an Anki package (.apkg) to decks, one for each note type,
+///whose faces are the note type's fields
+pub fn import_apkg(path: &Path) -> Result<AnkiImport, AnkiError> {
+    let collection = read_collection(path)?;
+
+    let mut deck_ids = collection.notes.iter().filter_map(|note| note.deck);
+    let first_deck = deck_ids.next();
+    let package_name = match first_deck.filter(|first| deck_ids.all(|id| id == *first)) {
+        Some(id) => collection.decks.get(&id).map(|deck| deck.name.clone()),
+        None => None,
+    }
+    .or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+    })
+    .unwrap_or_default();
+
+    let mut note_type_ids: Vec<i64> = vec![];
+    for note in collection.notes.iter() {
+        if !note_type_ids.contains(&note.note_type) {
+            note_type_ids.push(note.note_type);
+        }
+    }
+
+    let mut skipped = vec![];
+    let mut decks = vec![];
+    for note_type_id in note_type_ids.iter() {
+        let notes = collection
+            .notes
+            .iter()
+            .filter(|note| note.note_type == *note_type_id);
+        let Some(note_type) = collection.note_types.get(note_type_id) else {
+            continue;
+        };
+        let skip = |note: &Note, problem| SkippedNote {
+            id: note.id,
+            note_type: note_type.name.clone(),
+            problem,
+        };
+
+        if note_type.kind == CLOZE_KIND || note_type.flds.len() < MIN_FACE_COUNT {
+            let problem = || match note_type.kind {
+                CLOZE_KIND => NoteProblem::Cloze,
+                _ => NoteProblem::NotEnoughFields,
+            };
+            skipped.extend(notes.map(|note| skip(note, problem())));
+            continue;
+        }
+
+        let mut fields = note_type.flds.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|field| field.ord);
+        let faces = fields
+            .iter()
+            .map(|field| field.name.clone())
+            .collect::<Vec<_>>();
+
+        let mut cards: Vec<Card> = vec![];
+        for note in notes {
+            let mut values = note.fields.split(FIELD_SEPARATOR).collect::<Vec<_>>();
+            values.resize(faces.len(), "");
+            let card_faces = values
+                .iter()
+                .map(|value| html_to_face(value))
+                .collect::<Vec<_>>();
+
+            let card = Card::from_faces(card_faces.iter().cloned().map(Option::flatten).collect())
+                .with_tags(note.tags.split_whitespace().map(str::to_owned).collect());
+            if card.iter().flatten().count() < MIN_FACE_COUNT {
+                let media_only = faces
+                    .iter()
+                    .zip(card_faces.iter())
+                    .filter(|(_, face)| matches!(face, Some(None)))
+                    .map(|(face, _)| face.clone())
+                    .collect::<Vec<_>>();
+                let problem = match media_only.is_empty() {
+                    true => NoteProblem::NotEnoughFields,
+                    false => NoteProblem::MediaOnly(media_only),
+                };
+                skipped.push(skip(note, problem));
+            } else if cards.iter().any(|other| other.front() == card.front()) {
+                skipped.push(skip(note, NoteProblem::DuplicateFront(card.front_string())));
+            } else {
+                cards.push(card);
+            }
+        }
+
+        let name = match note_type_ids.len() {
+            1 => package_name.clone(),
+            _ => format!("{package_name} ({})", note_type.name),
+        };
+        decks.push(Deck {
+            name,
+            metadata: DeckMetadata::default(),
+            faces,
+            cards,
+        });
+    }
+
+    Ok(AnkiImport { decks, skipped })
+}
+
+///Reads the package's collection, which SQLite can only open from a file of its own
+fn read_collection(path: &Path) -> Result<Collection, AnkiError> {
+    let file = File::open(path).map_err(|err| AnkiError::IoError(path.to_path_buf(), err))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|err| AnkiError::ZipError(path.to_path_buf(), err))?;
+
+    let names = archive.file_names().collect::<Vec<_>>();
+    let legacy = LEGACY_COLLECTIONS
+        .into_iter()
+        .find(|collection| names.contains(collection));
+    let name = match legacy {
+        _ if names.contains(&COMPRESSED_COLLECTION) && legacy != Some(LEGACY_COLLECTIONS[0]) => {
+            return Err(AnkiError::CompressedCollection(path.to_path_buf()))
+        }
+        Some(name) => name,
+        None => return Err(AnkiError::MissingCollection(path.to_path_buf())),
+    };
+
+    let mut bytes = vec![];
+    archive
+        .by_name(name)
+        .map_err(|err| AnkiError::ZipError(path.to_path_buf(), err))?
+        .read_to_end(&mut bytes)
+        .map_err(|err| AnkiError::IoError(path.to_path_buf(), err))?;
+
+    let temp_path = std::env::temp_dir().join(format!("flashr-{}-{}", std::process::id(), name));
+    std::fs::write(&temp_path, bytes).map_err(|err| AnkiError::IoError(temp_path.clone(), err))?;
+    let collection = query_collection(&temp_path).map_err(|err| match err {
+        AnkiError::SqliteError(_, err) => AnkiError::SqliteError(path.to_path_buf(), err),
+        AnkiError::SerdeError(_, err) => AnkiError::SerdeError(path.to_path_buf(), err),
+        err => err,
+    });
+    //NOTE: Failing to remove the temporary file is harmless, so it's ignored
+    let _ = std::fs::remove_file(&temp_path);
+
+    collection
+}
+
+fn query_collection(path: &Path) -> Result<Collection, AnkiError> {
+    let sqlite_err = |err| AnkiError::SqliteError(path.to_path_buf(), err);
+    let serde_err = |err| AnkiError::SerdeError(path.to_path_buf(), err);
+
+    let connection =
+        Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(sqlite_err)?;
+
+    let (models, decks): (String, String) = connection
+        .query_row("SELECT models, decks FROM col", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .map_err(sqlite_err)?;
+    let note_types = serde_json::from_str::<HashMap<String, NoteType>>(&models)
+        .map_err(serde_err)?
+        .into_iter()
+        .filter_map(|(id, note_type)| Some((id.parse().ok()?, note_type)))
+        .collect();
+    let decks = serde_json::from_str::<HashMap<String, AnkiDeck>>(&decks)
+        .map_err(serde_err)?
+        .into_iter()
+        .filter_map(|(id, deck)| Some((id.parse().ok()?, deck)))
+        .collect();
+
+    let mut statement = connection
+        .prepare(
+            "SELECT notes.id, notes.mid, notes.flds, notes.tags, MIN(cards.did) FROM notes
+            LEFT JOIN cards ON cards.nid = notes.id
+            GROUP BY notes.id ORDER BY notes.id",
+        )
+        .map_err(sqlite_err)?;
+    let notes = statement
+        .query_map([], |row| {
+            Ok(Note {
+                id: row.get(0)?,
+                note_type: row.get(1)?,
+                fields: row.get(2)?,
+                tags: row.get(3)?,
+                deck: row.get(4)?,
+            })
+        })
+        .map_err(sqlite_err)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(sqlite_err)?;
+
+    Ok(Collection {
+        note_types,
+        decks,
+        notes,
+    })
+}
+
+///Converts a field's HTML to a face, with each line as a subface. Returns None for empty
+///fields, and Some(None) for fields that only contain media, like images and sounds.
+fn html_to_face(html: &str) -> Option<Option<Face>> {
+    let mut text = String::with_capacity(html.len());
+    let mut has_media = false;
+    let mut rest = html;
+
+    while let Some(start) = rest.find(['<', '[']) {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.starts_with('<') {
+            true => rest.find('>'),
+            false => rest
+                .starts_with("[sound:")
+                .then(|| rest.find(']'))
+                .flatten(),
+        };
+        let Some(end) = end else {
+            text.push_str(&rest[..1]);
+            rest = &rest[1..];
+            continue;
+        };
+
+        let tag = rest[1..end]
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match tag.as_str() {
+            "br" | "div" | "p" | "li" | "tr" => text.push('\n'),
+            "img" | "audio" | "video" => has_media = true,
+            _ if tag.starts_with("sound:") => has_media = true,
+            _ => {}
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+
+    let lines = decode_entities(&text)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+
+    match lines.len() {
+        0 if has_media => Some(None),
+        0 => None,
+        1 => Some(Some(Face::Single(lines.into_iter().next()?))),
+        _ => Some(Some(Face::Multi(lines))),
+    }
+}
+
+///Decodes the HTML entities Anki's editor writes, leaving unknown entities as they are
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..=end]);
+        let c = entity.and_then(|entity| match entity {
+            "nbsp" => Some(' '),
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        });
+
+        match (entity, c) {
+            (Some(entity), Some(c)) => {
+                decoded.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::deck::{parse_deck, Face};
+
+    use super::{html_to_face, import_apkg, AnkiError, AnkiImport, NoteProblem};
+
+    #[test]
+    fn imports_notes_as_decks() {
+        let import =
+            import_apkg(Path::new("./tests/anki/deck.apkg")).expect("Unable to import package");
+        assert_eq!(import.decks.len(), 2);
+
+        let basic = &import.decks[0];
+        assert_eq!(basic.name, "Japanese (Basic)");
+        assert_eq!(basic.faces, vec!["Front", "Back"]);
+        assert_eq!(
+            basic[0][1],
+            Some(Face::Single("Konnichiwa (polite)".to_owned()))
+        );
+        assert_eq!(
+            basic[1][1],
+            Some(Face::Multi(vec!["Cat".to_owned(), "Mouse".to_owned()]))
+        );
+
+        //NOTE: Fields are ordered by their ord, not their order in the note type
+        let vocab = &import.decks[1];
+        assert_eq!(vocab.faces, vec!["Kanji", "Reading", "Meaning"]);
+        assert_eq!(vocab[1][1], None);
+
+        assert_eq!(basic[0].tags(), ["greeting", "polite"]);
+        assert_eq!(vocab[0].tags(), ["nature"]);
+        assert!(basic[1].tags().is_empty());
+    }
+
+    #[test]
+    fn reports_unconvertible_notes() {
+        let import =
+            import_apkg(Path::new("./tests/anki/deck.apkg")).expect("Unable to import package");
+        let problems = import
+            .skipped
+            .iter()
+            .map(|skipped| (skipped.id, &skipped.problem))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    3,
+                    &NoteProblem::MediaOnly(vec!["Front".to_owned(), "Back".to_owned()])
+                ),
+                (8, &NoteProblem::MediaOnly(vec!["Back".to_owned()])),
+                (6, &NoteProblem::DuplicateFront("火".to_owned())),
+                (7, &NoteProblem::Cloze),
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_valid_decks() {
+        let import =
+            import_apkg(Path::new("./tests/anki/deck.apkg")).expect("Unable to import package");
+        let dir = std::env::temp_dir().join(format!("flashr-anki-test-{}", std::process::id()));
+
+        let paths = import.write(&dir, false).expect("Unable to write decks");
+        for (deck, path) in import.decks.iter().zip(paths.iter()) {
+            assert_eq!(*path, AnkiImport::deck_path(&dir, deck));
+            let json = std::fs::read_to_string(path).expect("Unable to read written deck");
+            let written = parse_deck(path, &json).expect("Written deck is invalid");
+            assert_eq!(written.cards, deck.cards);
+        }
+        let overwrite = import.write(&dir, false);
+
+        std::fs::remove_dir_all(&dir).expect("Unable to remove test directory");
+        assert!(matches!(overwrite, Err(AnkiError::DeckFileExists(_))));
+    }
+
+    #[test]
+    fn fails_on_newer_packages() {
+        assert!(matches!(
+            import_apkg(Path::new("./tests/anki/newer.apkg")),
+            Err(AnkiError::CompressedCollection(_))
+        ));
+    }
+
+    #[test]
+    fn strips_html() {
+        assert_eq!(html_to_face(""), None);
+        assert_eq!(html_to_face("<br>&nbsp;"), None);
+        assert_eq!(
+            html_to_face("<img src=\"a.png\"> [sound:a.mp3]"),
+            Some(None)
+        );
+        assert_eq!(
+            html_to_face("<span style=\"color: red\">1 &lt; 2</span> &#x41;&#66; [1]"),
+            Some(Some(Face::Single("1 < 2 AB [1]".to_owned())))
+        );
+        assert_eq!(
+            html_to_face("<ol><li>One</li><li>Two &amp; three</li></ol>"),
+            Some(Some(Face::Multi(vec![
+                "One".to_owned(),
+                "Two & three".to_owned()
+            ])))
+        );
+    }
+}
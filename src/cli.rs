@@ -17,44 +17,408 @@
  * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use clap::Parser;
+use clap::{builder::RangedU64ValueParser, Args, Parser, Subcommand};
+use glob::Pattern;
 
-use crate::Mode;
+use crate::{export::ExportFormat, tags::TagExpr, FaceDirection, Mode};
+
+pub const DEFAULT_CHOICES: usize = 4;
+pub const MIN_CHOICES: u64 = 2;
+pub const MAX_CHOICES: u64 = 9;
+pub const DEFAULT_COUNTDOWN: u64 = 5;
+pub const MIN_COUNTDOWN: u64 = 1;
+pub const MAX_COUNTDOWN: u64 = 60;
 
 #[derive(Parser, Debug)]
-#[command(name = "flashr", version = env!("CARGO_PKG_VERSION"))]
+#[command(
+    name = "flashr",
+    version = env!("CARGO_PKG_VERSION"),
+    args_conflicts_with_subcommands = true
+)]
 pub struct FlashrCli {
+    #[command(subcommand)]
+    pub command: Option<FlashrCommand>,
     #[arg(short = 'c', long = "count", value_name = "PROBLEM_COUNT", help = "Number of problems to show.", long_help = COUNT_HELP)]
     pub problem_count: Option<usize>,
     #[arg(
         short = 'f',
-        long = "faces",
+        long = "question-faces",
+        visible_alias = "faces",
         value_name = "[...FACE_N]",
         help = "Faces to show problems for.",
         long_help = FACES_HELP
     )]
-    pub faces: Option<Vec<String>>,
+    pub question_faces: Option<Vec<String>>,
+    #[arg(
+        short = 'a',
+        long = "answer-faces",
+        value_name = "[...FACE_N]",
+        help = "Faces to use as answers.",
+        long_help = ANSWER_FACES_HELP
+    )]
+    pub answer_faces: Option<Vec<String>>,
+    #[arg(
+        short = 'd',
+        long = "direction",
+        value_name = "QUESTION->ANSWER",
+        help = "Question and answer face pairs to show problems for.",
+        long_help = DIRECTION_HELP
+    )]
+    pub directions: Vec<FaceDirection>,
+    #[arg(
+        long = "tag",
+        value_name = "EXPR",
+        help = "Only show cards whose tags match the expression.",
+        long_help = TAG_HELP
+    )]
+    pub tags: Vec<TagExpr>,
+    #[arg(
+        long = "exclude-tag",
+        value_name = "EXPR",
+        help = "Don't show cards whose tags match the expression.",
+        long_help = EXCLUDE_TAG_HELP
+    )]
+    pub exclude_tags: Vec<TagExpr>,
+    #[arg(
+        long = "choices",
+        value_name = "CHOICES",
+        default_value_t = DEFAULT_CHOICES,
+        value_parser = RangedU64ValueParser::<usize>::new().range(MIN_CHOICES..=MAX_CHOICES),
+        help = "Number of answer choices in match mode.",
+        long_help = CHOICES_HELP
+    )]
+    pub choices: usize,
+    #[arg(
+        long = "countdown",
+        value_name = "SECONDS",
+        default_value_t = DEFAULT_COUNTDOWN,
+        value_parser = RangedU64ValueParser::<u64>::new().range(MIN_COUNTDOWN..=MAX_COUNTDOWN),
+        help = "Seconds to answer each problem in true/false mode.",
+        long_help = COUNTDOWN_HELP
+    )]
+    pub countdown: u64,
     #[arg(long = "line", help = "Toggle the weight line", long_help = LINE_HELP, default_value_t = false)]
     pub line: bool,
     #[arg(short = 'm', long = "mode", default_value_t = Mode::Match, value_name = "MODE", help = "Program mode", long_help = MODE_HELP)]
     pub mode: Mode,
-    #[arg(help = "Deck JSON file/dir paths", long_help = PATHS_HELP)]
+    #[command(flatten)]
+    pub selection: DeckSelectionArgs,
+    #[arg(help = "Deck file/dir paths", long_help = PATHS_HELP)]
     pub paths: Vec<String>,
 }
 
+#[derive(Args, Debug, Default)]
+pub struct DeckSearchArgs {
+    #[arg(
+        long = "deck-dir",
+        value_name = "DIR",
+        env = "FLASHR_DECK_DIR",
+        help = "Directory to load decks from when no paths are given.",
+        long_help = DECK_DIR_HELP
+    )]
+    pub deck_dir: Option<String>,
+    #[arg(
+        long = "max-depth",
+        value_name = "DEPTH",
+        help = "Levels of subdirectories to search for decks.",
+        long_help = MAX_DEPTH_HELP
+    )]
+    pub max_depth: Option<usize>,
+    #[arg(
+        long = "no-follow-symlinks",
+        help = "Don't follow symlinks found in deck directories",
+        default_value_t = false
+    )]
+    pub no_follow_symlinks: bool,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct DeckSelectionArgs {
+    #[command(flatten)]
+    pub search: DeckSearchArgs,
+    #[arg(
+        long = "deck",
+        value_name = "PATTERN",
+        help = "Only use decks whose names match the pattern.",
+        long_help = DECK_HELP
+    )]
+    pub decks: Vec<Pattern>,
+    #[arg(
+        long = "exclude-deck",
+        value_name = "PATTERN",
+        help = "Don't use decks whose names match the pattern.",
+        long_help = EXCLUDE_DECK_HELP
+    )]
+    pub exclude_decks: Vec<Pattern>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FlashrCommand {
+    #[command(about = "Check decks for errors and warnings", long_about = VALIDATE_HELP)]
+    Validate {
+        #[arg(
+            long = "deny-warnings",
+            help = "Exit with an error if there are any warnings",
+            default_value_t = false
+        )]
+        deny_warnings: bool,
+        #[arg(
+            long = "fix",
+            help = "Rewrite deck files, fixing common problems",
+            long_help = FIX_HELP,
+            default_value_t = false
+        )]
+        fix: bool,
+        #[arg(
+            long = "dry-run",
+            requires = "fix",
+            help = "Show a diff of the fixes instead of writing them",
+            default_value_t = false
+        )]
+        dry_run: bool,
+        #[command(flatten)]
+        search: DeckSearchArgs,
+        #[arg(
+            help = "Deck file/dir paths, the deck directory if none are given",
+            long_help = PATHS_HELP
+        )]
+        paths: Vec<String>,
+    },
+    #[command(
+        about = "Report how well the cards of each deck are known, along with their metadata"
+    )]
+    Stats {
+        #[arg(
+            long = "by-tag",
+            help = "Group the stats of each deck by the tags of its cards",
+            default_value_t = false
+        )]
+        by_tag: bool,
+        #[command(flatten)]
+        selection: DeckSelectionArgs,
+        #[arg(help = "Deck file/dir paths", long_help = PATHS_HELP)]
+        paths: Vec<String>,
+    },
+    #[command(about = "List decks, or change their faces, rewriting every card to match")]
+    Deck {
+        #[command(subcommand)]
+        command: DeckCommand,
+    },
+    #[command(about = "Export decks for other flashcard applications, or for printing", long_about = EXPORT_HELP)]
+    Export {
+        #[arg(
+            short = 'f',
+            long = "format",
+            value_name = "FORMAT",
+            help = "Format to export decks as",
+            long_help = EXPORT_FORMAT_HELP
+        )]
+        format: ExportFormat,
+        #[arg(
+            long = "stats-tags",
+            help = "Tag each card with how well it's known, from its stats",
+            long_help = STATS_TAGS_HELP,
+            default_value_t = false
+        )]
+        stats_tags: bool,
+        #[arg(
+            short = 'o',
+            long = "out-dir",
+            value_name = "DIR",
+            help = "Write a file for each deck to this directory, instead of printing them"
+        )]
+        out_dir: Option<String>,
+        #[command(flatten)]
+        selection: DeckSelectionArgs,
+        #[arg(help = "Deck file/dir paths", long_help = PATHS_HELP)]
+        paths: Vec<String>,
+    },
+    #[command(about = "Convert decks from other flashcard applications to JSON decks")]
+    Import {
+        #[command(subcommand)]
+        command: ImportCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportCommand {
+    #[command(about = "Convert an Anki package (.apkg) to JSON decks", long_about = ANKI_HELP)]
+    Anki {
+        #[arg(help = "Path of the Anki package")]
+        path: String,
+        #[arg(
+            short = 'o',
+            long = "out-dir",
+            value_name = "DIR",
+            default_value = ".",
+            help = "Directory to write the decks to"
+        )]
+        out_dir: String,
+        #[arg(
+            long = "force",
+            help = "Overwrite existing deck files",
+            default_value_t = false
+        )]
+        force: bool,
+        #[arg(
+            long = "dry-run",
+            help = "Report what would be converted without writing any decks",
+            default_value_t = false
+        )]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DeckCommand {
+    #[command(
+        about = "List decks along with their metadata, such as description, author and languages"
+    )]
+    List {
+        #[command(flatten)]
+        selection: DeckSelectionArgs,
+        #[arg(help = "Deck file/dir paths", long_help = PATHS_HELP)]
+        paths: Vec<String>,
+    },
+    #[command(about = "Add a face to JSON decks, leaving it null on every card", long_about = ADD_FACE_HELP)]
+    AddFace {
+        #[arg(help = "Name of the new face")]
+        face: String,
+        #[arg(
+            long = "before",
+            value_name = "FACE",
+            help = "Add the face before this face"
+        )]
+        before: Option<String>,
+        #[arg(
+            long = "after",
+            value_name = "FACE",
+            conflicts_with = "before",
+            help = "Add the face after this face"
+        )]
+        after: Option<String>,
+        #[arg(long = "dry-run", help = DRY_RUN_HELP, default_value_t = false)]
+        dry_run: bool,
+        #[command(flatten)]
+        search: DeckSearchArgs,
+        #[arg(help = "Deck file/dir paths", long_help = PATHS_HELP)]
+        paths: Vec<String>,
+    },
+    #[command(about = "Remove a face from JSON decks, along with it from every card", long_about = REMOVE_FACE_HELP)]
+    RemoveFace {
+        #[arg(help = "Name of the face to remove")]
+        face: String,
+        #[arg(long = "dry-run", help = DRY_RUN_HELP, default_value_t = false)]
+        dry_run: bool,
+        #[command(flatten)]
+        search: DeckSearchArgs,
+        #[arg(help = "Deck file/dir paths", long_help = PATHS_HELP)]
+        paths: Vec<String>,
+    },
+    #[command(about = "Reorder the faces of JSON decks, along with every card's faces", long_about = REORDER_FACES_HELP)]
+    ReorderFaces {
+        #[arg(
+            short = 'f',
+            long = "face",
+            value_name = "FACE",
+            required = true,
+            help = "Every face of the decks, in their new order"
+        )]
+        faces: Vec<String>,
+        #[arg(long = "dry-run", help = DRY_RUN_HELP, default_value_t = false)]
+        dry_run: bool,
+        #[command(flatten)]
+        search: DeckSearchArgs,
+        #[arg(help = "Deck file/dir paths", long_help = PATHS_HELP)]
+        paths: Vec<String>,
+    },
+}
+
+const VALIDATE_HELP: &str = r#"Check decks for errors and warnings, reporting all of them at once rather than stopping at the first.
+Exits with an error if any errors are found, or any warnings with --deny-warnings, or if no deck files are found.
+Checks the deck directory, see --deck-dir, if no paths are given.
+Example Usage: flashr validate ./decks"#;
+const FIX_HELP: &str = r#"Rewrite JSON deck files in place, fixing common problems while keeping their formatting:
+    - Trailing commas and trailing whitespace
+    - Cards identical to an earlier card, in the same or another deck file
+    - Subface arrays with only one subface, and whitespace around faces
+    - Cards with too few faces, which are padded with nulls
+Any remaining errors and warnings are reported afterwards.
+Example Usage: flashr validate --fix --dry-run ./decks"#;
+const ADD_FACE_HELP: &str = r#"Add a face to decks, leaving it null on every card. Added at the end unless --before or --after is given.
+Only JSON decks can be changed, decks in other formats are skipped.
+Example Usage: flashr deck add-face "Example sentence" --after English ./decks"#;
+const REMOVE_FACE_HELP: &str = r#"Remove a face from decks, along with it from every card.
+Only JSON decks can be changed, decks in other formats are skipped.
+Stats of cards whose front face was removed are moved to their new front.
+Example Usage: flashr deck remove-face Romaji ./decks"#;
+const REORDER_FACES_HELP: &str = r#"Reorder the faces of decks, along with every card's faces. Every face must be given exactly once.
+Only JSON decks can be changed, decks in other formats are skipped.
+Stats of cards whose front face changed are moved to their new front.
+Example Usage: flashr deck reorder-faces -f Hiragana -f Kanji -f English ./decks"#;
+const ANKI_HELP: &str = r#"Convert an Anki package (.apkg) to JSON decks, one for each note type, whose faces are the note type's fields.
+HTML is stripped from fields, with line breaks splitting them into subfaces. Notes that can't be converted are reported, such as
+cloze notes, notes whose fields are only media, and notes with the same front as an earlier note.
+Packages from newer Anki versions must be exported with "Support older Anki versions" checked.
+Example Usage: flashr import anki Japanese.apkg -o ./decks"#;
+const EXPORT_HELP: &str = r#"Export decks for other flashcard applications, or for printing. Printed to stdout unless --out-dir is given.
+Example Usage: flashr export -f anki --stats-tags -o ./export ./decks"#;
+const EXPORT_FORMAT_HELP: &str = r#"Format to export decks as. Possible values:
+    anki        - Anki's tab separated text import format, one deck per file
+    csv         - CSV with a header row of faces, which can be loaded as a deck again, one deck per file
+    markdown    - Markdown tables
+    html        - Printable HTML sheet"#;
+const STATS_TAGS_HELP: &str = r#"Tag each card with how well it's known, from its stats, so that difficult cards carry over. Tags are:
+    flashr::new         - Never answered
+    flashr::weak        - Answered incorrectly at least as often as correctly
+    flashr::learning    - Answered correctly more often than incorrectly
+    flashr::strong      - Answered correctly at least 3 more times than incorrectly"#;
+const DRY_RUN_HELP: &str = "Show a diff of the changes instead of writing them";
 const COUNT_HELP: &str = r#"Number of problems to show. If omitted, will continue indefinitely."#;
-const FACES_HELP: &str = r#"Faces to show problems for.
+const FACES_HELP: &str = r#"Faces to show problems for, ie. the question faces.
 Example Usage: flashr -f Front -f Back ./decks"#;
+const ANSWER_FACES_HELP: &str = r#"Faces to use as answers. If omitted, any face other than the question may be used.
+Example Usage: flashr -f Kanji -a English ./decks"#;
+const DIRECTION_HELP: &str = r#"Question and answer face pairs to show problems for, in the form "Question->Answer".
+Can be given multiple times, and combined with --question-faces and --answer-faces.
+Example Usage: flashr -d Kanji->English -d English->Hiragana ./decks"#;
+const TAG_HELP: &str = r#"Only show cards whose tags, including their deck's tags, match the expression.
+Expressions combine tags with ! (not), & (and), | (or) and parentheses, and tags next to each other must all match.
+Tags are matched case-insensitively, and may be quoted with " or ' when they contain spaces.
+Can be given multiple times, in which case cards must match every expression.
+Example Usage: flashr --tag "'JLPT N5' & !(verb | adjective)" ./decks"#;
+const EXCLUDE_TAG_HELP: &str = r#"Don't show cards whose tags, including their deck's tags, match the expression. Written the same as --tag.
+Can be given multiple times, in which case cards matching any expression are left out.
+Example Usage: flashr --exclude-tag "chapter-1 | chapter-2" ./decks"#;
+const CHOICES_HELP: &str = r#"Number of answer choices shown for each match problem, between 2 and 9.
+Answers can be selected with the number keys 1 through CHOICES."#;
+const COUNTDOWN_HELP: &str = r#"Seconds to answer each problem in true/false mode, between 1 and 60.
+Problems not answered in time count as incorrect."#;
 const LINE_HELP: &str = r#"Toggle the weight line. This will render a bar chart at the top which represents the weights of the backing weighted list."#;
 const MODE_HELP: &str = r#"Program mode. Possible values:
     match   - Multiple choice matching problems
     flash   - Typical flashcards
-    type    - Shown a face, and asked to type the answer"#;
-const PATHS_HELP: &str = r#"Paths to load decks from. Can be individual files or directories."#;
+    type    - Shown a face, and asked to type the answer
+    order   - Shown a face, and asked to put the subfaces of another in order
+    tf      - True/false, shown a face paired with an answer, and asked whether it's right against the clock.
+              Also written true-false"#;
+const DECK_HELP: &str = r#"Only use decks whose names match the pattern, where * matches any text, ? any one character, and [abc] any of the characters.
+Names are matched case-insensitively. Can be given multiple times, in which case decks matching any pattern are used.
+Example Usage: flashr --deck "Kanji*" ./decks"#;
+const EXCLUDE_DECK_HELP: &str = r#"Don't use decks whose names match the pattern. Written the same as --deck.
+Can be given multiple times, in which case decks matching any pattern are left out.
+Example Usage: flashr --exclude-deck "*Verbs" ./decks"#;
+const DECK_DIR_HELP: &str = r#"Directory to load decks from when no paths are given, so that running flashr without any arguments starts a session.
+Defaults to ~/.config/flashr/decks, and may also be set with the FLASHR_DECK_DIR environment variable."#;
+const MAX_DEPTH_HELP: &str = r#"Levels of subdirectories to search for decks within each directory given. If omitted, every subdirectory is searched.
+0 only loads the decks directly within each directory.
+Example Usage: flashr --max-depth 1 ./decks"#;
+const PATHS_HELP: &str = r#"Paths to load decks from. Can be individual files or directories, or glob patterns such as "decks/**/kanji*.json".
+Decks may be JSON (.json), YAML (.yaml, .yml), TOML (.toml), CSV/TSV (.csv, .tsv), or Markdown (.md, .markdown) files."#;
 
 #[cfg(test)]
 mod tests {
-    use clap::CommandFactory;
+    use clap::{CommandFactory, Parser};
 
     use crate::cli;
 
@@ -62,4 +426,21 @@ mod tests {
     fn verify_cli() {
         cli::FlashrCli::command().debug_assert();
     }
+
+    #[test]
+    fn session_args_conflict_with_subcommands() {
+        let parse =
+            |args: &[&str]| cli::FlashrCli::try_parse_from(args).expect("Unable to parse args");
+
+        let cli = parse(&["flashr", "validate"]);
+        assert!(cli.command.is_some());
+
+        let cli = parse(&["flashr", "./decks", "validate"]);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.paths, vec!["./decks", "validate"]);
+
+        let cli = parse(&["flashr", "-m", "flash", "stats"]);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.paths, vec!["stats"]);
+    }
 }
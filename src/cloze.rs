@@ -0,0 +1,261 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+
+use crate::deck::Face;
+
+///Part of a face's text, either plain text or a cloze deletion, written as `{{c1::text}}`,
+///or `{{c1::text::hint}}` with a hint shown in place of the deleted text
+#[derive(PartialEq, Eq, Debug)]
+pub(crate) enum Segment<'a> {
+    Text(&'a str),
+    Cloze {
+        number: u32,
+        text: &'a str,
+        hint: Option<&'a str>,
+    },
+}
+
+const CLOZE_START: &str = "{{c";
+const CLOZE_SEPARATOR: &str = "::";
+const CLOZE_END: &str = "}}";
+
+///Text shown in place of a deleted cloze without a hint
+const BLANK: &str = "[...]";
+
+///Splits the text into plain text and cloze deletions. Deleted text may itself contain braces,
+///eg. `{{c1::{日本|にほん}}}`, and markers which don't form a cloze are left as plain text.
+pub(crate) fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = vec![];
+    let mut plain_start = 0;
+    let mut search_start = 0;
+
+    while let Some(open) = text[search_start..]
+        .find(CLOZE_START)
+        .map(|i| i + search_start)
+    {
+        search_start = open + 1;
+
+        let Some((len, segment)) = cloze_at(&text[open..]) else {
+            continue;
+        };
+
+        if plain_start < open {
+            segments.push(Segment::Text(&text[plain_start..open]));
+        }
+        segments.push(segment);
+        plain_start = open + len;
+        search_start = open + len;
+    }
+
+    if plain_start < text.len() {
+        segments.push(Segment::Text(&text[plain_start..]));
+    }
+
+    segments
+}
+
+///Length and segment of the cloze at the start of the text, if it's a valid cloze
+fn cloze_at(text: &str) -> Option<(usize, Segment<'_>)> {
+    let rest = &text[CLOZE_START.len()..];
+    let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+    let number = rest[..digits].parse::<u32>().ok().filter(|n| *n > 0)?;
+    let content_start = CLOZE_START.len() + digits + CLOZE_SEPARATOR.len();
+    if !rest[digits..].starts_with(CLOZE_SEPARATOR) {
+        return None;
+    }
+
+    let mut depth = 0usize;
+    let mut content_end = None;
+    for (i, c) in text[content_start..].char_indices() {
+        let i = i + content_start;
+        if depth == 0 && text[i..].starts_with(CLOZE_END) {
+            content_end = Some(i);
+            break;
+        }
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+    }
+
+    let content_end = content_end?;
+    let (text_part, hint) = match text[content_start..content_end].split_once(CLOZE_SEPARATOR) {
+        Some((text, hint)) => (text, Some(hint).filter(|hint| !hint.is_empty())),
+        None => (&text[content_start..content_end], None),
+    };
+    if text_part.is_empty() {
+        return None;
+    }
+
+    Some((
+        content_end + CLOZE_END.len(),
+        Segment::Cloze {
+            number,
+            text: text_part,
+            hint,
+        },
+    ))
+}
+
+///Whether the text has any cloze deletions
+pub(crate) fn has_cloze(text: &str) -> bool {
+    segments(text)
+        .iter()
+        .any(|segment| matches!(segment, Segment::Cloze { .. }))
+}
+
+///Replaces each cloze with the text given for its number, text and hint
+pub(crate) fn replace<'a>(
+    text: &'a str,
+    cloze: impl Fn(u32, &str, Option<&str>) -> String,
+) -> Cow<'a, str> {
+    if !has_cloze(text) {
+        return Cow::Borrowed(text);
+    }
+
+    segments(text)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.to_owned(),
+            Segment::Cloze { number, text, hint } => cloze(number, text, hint),
+        })
+        .collect::<String>()
+        .into()
+}
+
+///Text with every cloze showing its deleted text, eg. "Water is wet" for
+///"{{c1::Water}} is {{c2::wet}}"
+pub(crate) fn reveal(text: &str) -> Cow<'_, str> {
+    replace(text, |_, text, _| text.to_owned())
+}
+
+///Text with the cloze of the number deleted, showing its hint or [BLANK] in its place,
+///and every other cloze revealed, eg. "[...] is wet" for "{{c1::Water}} is {{c2::wet}}"
+pub(crate) fn blank(text: &str, number: u32) -> String {
+    replace(text, |other, text, hint| match other == number {
+        true => hint.map_or_else(|| BLANK.to_owned(), |hint| format!("[{hint}]")),
+        false => text.to_owned(),
+    })
+    .into_owned()
+}
+
+///Distinct cloze numbers of the face, in order
+pub(crate) fn numbers(face: &Face) -> Vec<u32> {
+    let mut numbers = face
+        .subfaces()
+        .iter()
+        .flat_map(|subface| segments(subface))
+        .filter_map(|segment| match segment {
+            Segment::Cloze { number, .. } => Some(number),
+            Segment::Text(_) => None,
+        })
+        .collect::<Vec<_>>();
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+}
+
+///Text deleted by the cloze of the number, as a face with a subface for each time the
+///number is used
+pub(crate) fn answer(face: &Face, number: u32) -> Option<Face> {
+    let mut texts = face
+        .subfaces()
+        .iter()
+        .flat_map(|subface| segments(subface))
+        .filter_map(|segment| match segment {
+            Segment::Cloze {
+                number: other,
+                text,
+                ..
+            } if other == number => Some(text.to_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    match texts.len() {
+        0 => None,
+        1 => texts.pop().map(Face::Single),
+        _ => Some(Face::Multi(texts)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Face;
+
+    use super::{answer, blank, numbers, reveal, segments, Segment};
+
+    #[test]
+    fn splits_clozes() {
+        assert_eq!(
+            segments("{{c1::水}}を{{c2::{飲|の}む::verb}}"),
+            [
+                Segment::Cloze {
+                    number: 1,
+                    text: "水",
+                    hint: None
+                },
+                Segment::Text("を"),
+                Segment::Cloze {
+                    number: 2,
+                    text: "{飲|の}む",
+                    hint: Some("verb")
+                },
+            ]
+        );
+
+        //NOTE: Markers which don't form a cloze are left as they are
+        for text in [
+            "{{c::x}}",
+            "{{c0::x}}",
+            "{{c1:x}}",
+            "{{c1::}}",
+            "{{c1::x",
+            "{{c1::x}",
+        ] {
+            assert_eq!(segments(text), [Segment::Text(text)]);
+        }
+    }
+
+    #[test]
+    fn blanks_clozes() {
+        let text = "{{c1::Water}} is {{c2::wet::adjective}}, {{c1::water}}";
+        assert_eq!(reveal(text), "Water is wet, water");
+        assert_eq!(blank(text, 1), "[...] is wet, [...]");
+        assert_eq!(blank(text, 2), "Water is [adjective], water");
+    }
+
+    #[test]
+    fn finds_cloze_answers() {
+        let face = Face::Multi(vec![
+            "{{c2::Fire}} is {{c1::hot}}".to_owned(),
+            "{{c1::Warm}} water".to_owned(),
+        ]);
+        assert_eq!(numbers(&face), [1, 2]);
+        assert_eq!(
+            answer(&face, 1),
+            Some(Face::Multi(vec!["hot".to_owned(), "Warm".to_owned()]))
+        );
+        assert_eq!(answer(&face, 2), Some(Face::Single("Fire".to_owned())));
+        assert_eq!(answer(&face, 3), None);
+    }
+}
@@ -0,0 +1,218 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::deck::{is_card_file, parse_card_file, parse_composed_deck, Card, Deck, DeckError};
+
+///Composes the deck from others: inheriting the faces, along with their languages and which
+///are info faces, of the deck it `extends`, then adding the cards of each deck or file of
+///cards it `include`s, keeping only those matching its `filter` if given. Included cards
+///keep the tags of the deck they're from.
+///
+///`chain` holds the decks being composed which led to this one, so that decks including or
+///extending each other in a cycle are found.
+pub(crate) fn compose_deck(
+    deck: Deck,
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Deck, DeckError> {
+    let metadata = &deck.metadata;
+    if metadata.extends.is_none() && metadata.include.is_empty() {
+        return Ok(deck);
+    }
+
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let this = canonical(path);
+    if let Some(start) = chain.iter().position(|other| canonical(other) == this) {
+        let mut cycle = chain[start..].to_vec();
+        cycle.push(path.to_path_buf());
+        return Err(DeckError::IncludeCycle(cycle));
+    }
+
+    chain.push(path.to_path_buf());
+    let composed = compose(deck, path, chain);
+    chain.pop();
+
+    composed
+}
+
+fn compose(mut deck: Deck, path: &Path, chain: &mut Vec<PathBuf>) -> Result<Deck, DeckError> {
+    //NOTE: Paths are relative to the deck including them
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    if let Some(extends) = deck.metadata.extends.as_ref() {
+        let parent = load_deck(&dir.join(extends), chain)?;
+        deck.inherit_faces(&parent.faces);
+        for (face, language) in parent.metadata.languages {
+            if deck.metadata.language(&face).is_none() {
+                deck.metadata.languages.push((face, language));
+            }
+        }
+        for face in parent.metadata.info_faces {
+            if !deck.is_info_face(&face) {
+                deck.metadata.info_faces.push(face);
+            }
+        }
+    }
+
+    let mut included: Vec<(Card, PathBuf)> = vec![];
+    for include in deck.metadata.include.iter() {
+        let include_path = dir.join(include);
+        let source = fs::read_to_string(&include_path)
+            .map_err(|err| DeckError::IoError(include_path.clone(), err))?;
+
+        let cards = if is_card_file(&include_path) {
+            parse_card_file(&include_path, &source, &deck.faces)?
+        } else {
+            let other = parse_composed_deck(&include_path, &source, chain)?;
+            other
+                .iter()
+                .map(|card| {
+                    let mut card = card
+                        .clone()
+                        .with_tags(other.tags_of(card).into_iter().cloned().collect());
+                    card.arrange(&other.faces, &deck.faces);
+                    card
+                })
+                .collect()
+        };
+        included.extend(cards.into_iter().map(|card| (card, include_path.clone())));
+    }
+
+    if let Some(filter) = deck.metadata.filter.as_ref() {
+        included.retain(|(card, _)| filter.matches(&deck.tags_of(card)));
+    }
+
+    let mut origins = vec![path.to_path_buf(); deck.cards.len()];
+    for (card, origin) in included {
+        let duplicate = card
+            .front()
+            .and_then(|front| deck.iter().position(|other| other.front() == Some(front)));
+        if let Some(i) = duplicate {
+            return Err(DeckError::DuplicateIncludedCard(
+                path.to_path_buf(),
+                card.front_string(),
+                origins[i].clone(),
+                origin,
+            ));
+        }
+
+        deck.cards.push(card);
+        origins.push(origin);
+    }
+
+    Ok(deck)
+}
+
+fn load_deck(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Deck, DeckError> {
+    let source =
+        fs::read_to_string(path).map_err(|err| DeckError::IoError(path.to_path_buf(), err))?;
+
+    parse_composed_deck(path, &source, chain)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        deck::{load_decks, DeckError, Face},
+        validate::validate_paths,
+    };
+
+    #[test]
+    fn inherits_faces() {
+        let decks = load_decks(vec!["./tests/compose/nature.json"]).expect("Unable to load deck");
+        let nature = &decks[0];
+
+        assert_eq!(nature.faces, vec!["Kanji", "Hiragana", "English"]);
+        assert_eq!(nature.metadata.language("Hiragana"), Some("ja"));
+        //NOTE: Keyed cards are arranged by the inherited faces
+        assert_eq!(nature[1][1], Some(Face::Single("き".to_owned())));
+        assert_eq!(nature[1][2], Some(Face::Single("Tree".to_owned())));
+    }
+
+    #[test]
+    fn includes_decks_and_card_files() {
+        let decks = load_decks(vec!["./tests/compose/master.json"]).expect("Unable to load deck");
+        let master = &decks[0];
+
+        let fronts = master
+            .iter()
+            .map(|card| card.front_string())
+            .collect::<Vec<_>>();
+        assert_eq!(fronts, vec!["水", "木", "食べる", "話す", "火", "山"]);
+        //NOTE: Included cards are arranged by the including deck's faces
+        assert_eq!(master[2][1], Some(Face::Single("たべる".to_owned())));
+        assert_eq!(master[5][1], None);
+        //NOTE: Included cards keep the tags of the deck they're from
+        assert_eq!(master[1].tags(), ["nature"]);
+        assert_eq!(master[0].tags(), ["nature", "JLPT N5"]);
+    }
+
+    #[test]
+    fn filters_included_cards() {
+        let decks = load_decks(vec!["./tests/compose/n5.json"]).expect("Unable to load deck");
+        let fronts = decks[0]
+            .iter()
+            .map(|card| card.front_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(fronts, vec!["水", "食べる"]);
+    }
+
+    #[test]
+    fn fails_on_cycles() {
+        let err = load_decks(vec!["./tests/compose_invalid/cycle_a.json"])
+            .expect_err("Loaded decks including each other");
+
+        let DeckError::IncludeCycle(paths) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(paths.len(), 3);
+        assert!(paths[0].ends_with("cycle_a.json") && paths[2].ends_with("cycle_a.json"));
+    }
+
+    #[test]
+    fn fails_on_duplicate_included_cards() {
+        let err = load_decks(vec!["./tests/compose_invalid/duplicate.json"])
+            .expect_err("Loaded deck with duplicate included card");
+
+        assert!(
+            matches!(&err, DeckError::DuplicateIncludedCard(_, front, _, other) if front == "水" && other.ends_with("nature.json")),
+            "Unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn validates_composed_decks() {
+        let report =
+            validate_paths(vec!["./tests/compose"]).expect("Unable to validate composed decks");
+        assert!(report.into_result(false).is_ok());
+
+        let report = validate_paths(vec!["./tests/compose_invalid"])
+            .expect("Unable to validate invalid composed decks");
+        assert!(report.to_string().contains(
+            "IncludeCycle: Decks include or extend each other in a cycle: ./tests/compose_invalid/cycle_a.json -> ./tests/compose_invalid/cycle_b.json -> ./tests/compose_invalid/cycle_a.json"
+        ));
+        assert!(report.into_result(false).is_err());
+    }
+}
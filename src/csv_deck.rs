@@ -0,0 +1,208 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{ops::Range, path::Path};
+
+use csv::{ReaderBuilder, Trim};
+
+use crate::{
+    deck::{Card, Deck, DeckMetadata, Face},
+    location::DeckSpans,
+};
+
+///Separator splitting a cell into subfaces, unless changed by a "#separator:" directive
+const DEFAULT_SEPARATOR: &str = "|";
+
+///Settings and metadata given by "#key: value" comment lines before the header row
+struct Directives<'a> {
+    name: Option<&'a str>,
+    separator: Option<&'a str>,
+    ///Column of each card's tags, counting from 1
+    tags_column: Option<usize>,
+    ///Deck's tags, not yet split by the separator
+    tags: Option<&'a str>,
+    metadata: DeckMetadata,
+}
+
+impl<'a> Directives<'a> {
+    fn new(source: &'a str) -> Self {
+        let directive = |key: &str| {
+            source[..header_start(source)].lines().find_map(|line| {
+                let (line_key, value) = line.trim().strip_prefix('#')?.split_once(':')?;
+                line_key
+                    .trim()
+                    .eq_ignore_ascii_case(key)
+                    .then_some(value.trim())
+            })
+        };
+
+        let metadata = |key: &str| directive(key).map(str::to_owned);
+
+        Self {
+            name: directive("name"),
+            separator: directive("separator"),
+            tags_column: directive("tags column").and_then(|column| column.parse().ok()),
+            tags: directive("tags"),
+            metadata: DeckMetadata {
+                description: metadata("description"),
+                author: metadata("author"),
+                version: metadata("version"),
+                license: metadata("license"),
+                source: metadata("source"),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+///Byte offset of the header row, after the leading comment and blank lines, which may hold
+///directives. Any later line starting with `#` is a row like any other, so that cards may
+///start with `#`.
+fn header_start(source: &str) -> usize {
+    source
+        .split_inclusive('\n')
+        .take_while(|line| {
+            let line = line.trim();
+            line.is_empty() || line.starts_with('#')
+        })
+        .map(str::len)
+        .sum()
+}
+
+///Reader of the rows of the source, from its header row on
+fn reader(source: &str, delimiter: u8) -> csv::Reader<&[u8]> {
+    ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .trim(Trim::All)
+        .has_headers(false)
+        .from_reader(&source.as_bytes()[header_start(source)..])
+}
+
+///Parses a CSV/TSV deck, whose header row is the deck's faces. Directives are given by
+///comment lines before the header row. The deck is named by a "#name:" directive, or otherwise the file name. Empty cells are null faces, and cells
+///are split into subfaces by the "#separator:" directive, "|" by default. Metadata may be
+///given by "#description:", "#author:", "#version:", "#license:" and "#source:" directives.
+///
+///The deck's tags may be given by a "#tags:" directive, split by the separator, and a column
+///of each card's tags by a "#tags column:" directive, counting from 1 as in Anki's format.
+pub(crate) fn parse_csv_deck(path: &Path, source: &str, delimiter: u8) -> Result<Deck, csv::Error> {
+    let mut directives = Directives::new(source);
+    let separator = directives.separator.unwrap_or(DEFAULT_SEPARATOR).to_owned();
+    let split_tags = |cell: &str| match cell_face(cell, &separator) {
+        Some(Face::Single(tag)) => vec![tag],
+        Some(Face::Multi(tags)) => tags,
+        None => vec![],
+    };
+    if let Some(tags) = directives.tags {
+        directives.metadata.tags = split_tags(tags);
+    }
+    let name = directives
+        .name
+        .map(str::to_owned)
+        .or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+
+    let mut records = reader(source, delimiter).into_records();
+    let mut faces = match records.next() {
+        Some(header) => header?.iter().map(str::to_owned).collect::<Vec<_>>(),
+        None => vec![],
+    };
+    let tags_column = directives
+        .tags_column
+        .and_then(|column| column.checked_sub(1))
+        .filter(|column| *column < faces.len());
+    if let Some(column) = tags_column {
+        faces.remove(column);
+    }
+
+    let cards = records
+        .map(|record| {
+            let record = record?;
+            let mut cells = record.iter().collect::<Vec<_>>();
+            let tags = match tags_column {
+                Some(column) if column < cells.len() => split_tags(cells.remove(column)),
+                _ => vec![],
+            };
+            //NOTE: Spreadsheets often leave empty cells at the end of rows
+            while cells.len() > faces.len() && cells.last().is_some_and(|cell| cell.is_empty()) {
+                cells.pop();
+            }
+
+            Ok(Card::from_faces(
+                cells
+                    .into_iter()
+                    .map(|cell| cell_face(cell, &separator))
+                    .collect(),
+            )
+            .with_tags(tags))
+        })
+        .collect::<Result<_, csv::Error>>()?;
+
+    Ok(Deck {
+        name,
+        metadata: directives.metadata,
+        faces,
+        cards,
+    })
+}
+
+fn cell_face(cell: &str, separator: &str) -> Option<Face> {
+    let mut subfaces = match separator {
+        "" => vec![cell],
+        separator => cell.split(separator).map(str::trim).collect(),
+    };
+    subfaces.retain(|subface| !subface.is_empty());
+
+    match subfaces.len() {
+        0 => None,
+        1 => Some(Face::Single(subfaces[0].to_owned())),
+        _ => Some(Face::Multi(
+            subfaces.into_iter().map(str::to_owned).collect(),
+        )),
+    }
+}
+
+///Finds the spans of the header row, as the "faces" key, and of each card's row
+pub(crate) fn csv_deck_spans(source: &str, delimiter: u8) -> DeckSpans {
+    let mut spans = DeckSpans::default();
+    let row_span = |start: usize| -> Range<usize> {
+        let line = source[start..].lines().next().unwrap_or_default();
+        start..start + line.trim_end().len()
+    };
+
+    let header_start = header_start(source);
+    let mut records = reader(source, delimiter).into_byte_records();
+    let mut starts = records.by_ref().map_while(|record| {
+        record
+            .ok()?
+            .position()
+            .map(|position| header_start + position.byte() as usize)
+    });
+
+    if let Some(header) = starts.next() {
+        spans.keys.push(("faces".to_owned(), row_span(header)));
+    }
+    spans.cards = starts.map(row_span).collect();
+
+    spans
+}
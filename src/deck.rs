@@ -21,53 +21,462 @@ use std::{
     ffi::OsStr,
     fmt::{Debug, Display},
     fs,
-    ops::Deref,
+    ops::{Deref, Range},
     path::{Path, PathBuf},
 };
 
 use rand::{rngs::ThreadRng, seq::SliceRandom};
-use serde::{de::Visitor, ser::SerializeSeq, Deserialize, Serialize};
+use serde::{
+    de::Visitor,
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Serialize,
+};
 
-use crate::{AndThen, DeckCard};
+use crate::{
+    cloze,
+    compose::compose_deck,
+    csv_deck::{csv_deck_spans, parse_csv_deck},
+    language,
+    location::{
+        json_deck_spans, snippet, toml_deck_spans, yaml_deck_spans, DeckSpans, SourceLocation,
+    },
+    markdown_deck::{markdown_deck_spans, parse_markdown_deck, MarkdownError},
+    rich, ruby, suggest,
+    tags::TagExpr,
+    DeckCard,
+};
 
 ///Represents a deck of flashcards. Each card must have the same number of faces as
 ///the deck's own faces array, though any number of those faces may optionally be null/None
 ///as long as at least two are non-nullish/Some. Faces may also be subdivided into subfaces
 ///which will be randomized when shown as questions/answers.
 ///
+///Cards may also be written as objects keyed by face name, in which case any faces
+///left out are null. Either style may be used for each card, and is kept when serialized.
+///
+///Decks may optionally describe themselves with a description, author, version, license,
+///source URL, and a BCP-47 language tag for each face, see [DeckMetadata].
+///
+///Cards may be tagged, either with a `{"tags": [...]}` object after the faces of a card
+///written as an array, or with a "tags" key for cards written as objects. Tags given by
+///the deck itself apply to every card.
+///
+///Decks may also be composed from others: inheriting the faces of the deck they "extends",
+///and adding the cards of the decks, or files of cards, they "include", optionally keeping
+///only those whose tags match their "filter".
+///
 ///Example:
 ///```
 ///# use flashr::deck::Deck;
 ///let json = r#"{
 ///  "name": "Kanji Words",
+///  "author": "Clownvin",
+///  "languages": {"Kanji": "ja", "Hiragana": "ja", "Definition": "en"},
 ///  "faces": ["Kanji", "Hiragana", "Definition"],
 ///  "cards": [
 ///    ["日本", "にほん", "Japan"],
-///    [null, "いいえ", ["No", "Don't mention it (eg in reply to apology/praise)"]]
+///    [null, "いいえ", ["No", "Don't mention it (eg in reply to apology/praise)"]],
+///    {"Kanji": "水", "Definition": ["Water"], "tags": ["nature"]}
 ///  ]
 ///}"#;
 ///assert!(serde_json::from_str::<Deck>(json)
 ///  .is_ok_and(|deck| {
-///    deck.name == "Kanji Words" && deck.cards.len() == 2
+///    deck.name == "Kanji Words" && deck.cards.len() == 3 && deck.cards[2][1].is_none()
+///      && deck.metadata.language("Hiragana") == Some("ja")
+///      && deck.cards[2].tags() == ["nature"]
 ///  }));
 ///```
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
+#[serde(from = "DeckJson")]
 pub struct Deck {
     pub name: String,
+    pub metadata: DeckMetadata,
     pub faces: Vec<String>,
     pub cards: Vec<Card>,
 }
 
+///Optional information about a deck. Any keys of the deck that aren't known are kept
+///here too, so that they're written back untouched.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeckMetadata {
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    pub license: Option<String>,
+    pub source: Option<String>,
+    ///BCP-47 language tag of each face that has one, in the order they were written
+    pub languages: Vec<(String, String)>,
+    ///Tags given to every card of the deck, in addition to their own
+    pub tags: Vec<String>,
+    ///Faces only shown for information, like example sentences or notes, which are never
+    ///used as a question or answer
+    pub info_faces: Vec<String>,
+    ///Path of the deck whose faces, and their languages, this deck inherits
+    pub extends: Option<String>,
+    ///Paths of the decks, or files of cards, whose cards are added to this deck's
+    pub include: Vec<String>,
+    ///Tags which included cards must match, making this deck a filter over the included decks
+    pub filter: Option<TagExpr>,
+    pub unknown: serde_json::Map<String, serde_json::Value>,
+}
+
+impl DeckMetadata {
+    pub fn language(&self, face: &str) -> Option<&str> {
+        self.languages
+            .iter()
+            .find_map(|(name, language)| (name == face).then_some(language.as_str()))
+    }
+
+    pub fn fields(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("description", &self.description),
+            ("author", &self.author),
+            ("version", &self.version),
+            ("license", &self.license),
+            ("source", &self.source),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value.as_deref()?)))
+        .collect()
+    }
+}
+
 impl Debug for Deck {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Deck")
             .field("name", &self.name)
+            .field("metadata", &self.metadata)
             .field("faces", &self.faces)
             .field("cards", &self.cards.len())
             .finish()
     }
 }
 
+impl Deck {
+    ///Tags differing only by case are duplicates, since they're matched case-insensitively
+    pub fn tags_of<'a>(&'a self, card: &'a Card) -> Vec<&'a String> {
+        let mut tags: Vec<&String> = Vec::with_capacity(self.metadata.tags.len() + card.tags.len());
+        for tag in self.metadata.tags.iter().chain(card.tags.iter()) {
+            if !tags
+                .iter()
+                .any(|other| other.to_lowercase() == tag.to_lowercase())
+            {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    ///Whether the face is only shown for information, and never quizzed
+    pub fn is_info_face(&self, face: &str) -> bool {
+        self.metadata.info_faces.iter().any(|info| info == face)
+    }
+
+    ///Faces which may be used as a question or answer, leaving out info faces
+    pub(crate) fn quizzed_face_count(&self) -> usize {
+        self.faces
+            .iter()
+            .filter(|face| !self.is_info_face(face))
+            .count()
+    }
+
+    ///Indices of the card's quizzed faces which have cloze deletions, eg. `{{c1::word}}`
+    pub(crate) fn cloze_faces(&self, card: &Card) -> Vec<usize> {
+        self.faces
+            .iter()
+            .zip(card.iter())
+            .enumerate()
+            .filter(|(_, (deck_face, card_face))| {
+                !self.is_info_face(deck_face)
+                    && card_face
+                        .as_ref()
+                        .is_some_and(|face| !cloze::numbers(face).is_empty())
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    ///Inherits the faces of the deck being extended, if the deck doesn't have its own
+    pub(crate) fn inherit_faces(&mut self, faces: &[String]) {
+        if self.faces.is_empty() {
+            self.faces = faces.to_vec();
+            for card in self.cards.iter_mut() {
+                card.arrange_keys(faces);
+            }
+        }
+    }
+
+    ///Name of the deck usable as a file name, replacing any troublesome characters
+    pub(crate) fn file_stem(&self) -> String {
+        self.name
+            .trim()
+            .chars()
+            .map(|c| match c.is_alphanumeric() || " -_()".contains(c) {
+                true => c,
+                false => '_',
+            })
+            .collect()
+    }
+
+    ///Serializes the deck as JSON, writing each card on its own line like hand-written decks
+    pub(crate) fn to_json(&self) -> String {
+        fn compact<T: Serialize + ?Sized>(value: &T) -> String {
+            let mut json = vec![];
+            value
+                .serialize(&mut serde_json::Serializer::with_formatter(
+                    &mut json,
+                    SpacedFormatter,
+                ))
+                .expect("Unable to serialize deck to JSON");
+            String::from_utf8(json).expect("Serialized JSON is not UTF-8")
+        }
+
+        let serde_json::Value::Object(deck) =
+            serde_json::to_value(self).expect("Unable to serialize deck to JSON")
+        else {
+            unreachable!("Decks are serialized as maps");
+        };
+
+        let entries = deck
+            .iter()
+            .map(|(key, value)| match value.as_array() {
+                Some(cards) if key == "cards" && !cards.is_empty() => format!(
+                    "    {}: [\n{}\n    ]",
+                    compact(key),
+                    cards
+                        .iter()
+                        .map(|card| format!("        {}", compact(card)))
+                        .collect::<Vec<_>>()
+                        .join(",\n")
+                ),
+                _ => format!("    {}: {}", compact(key), compact(value)),
+            })
+            .collect::<Vec<_>>();
+
+        format!("{{\n{}\n}}\n", entries.join(",\n"))
+    }
+}
+
+impl Serialize for Deck {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let cards = self
+            .cards
+            .iter()
+            .map(|card| StyledCard {
+                card,
+                faces: &self.faces,
+            })
+            .collect::<Vec<_>>();
+        let metadata = &self.metadata;
+
+        let mut deck = serializer.serialize_map(None)?;
+        deck.serialize_entry("name", &self.name)?;
+        if let Some(extends) = metadata.extends.as_ref() {
+            deck.serialize_entry("extends", extends)?;
+        }
+        for (key, value) in metadata.fields() {
+            deck.serialize_entry(key, value)?;
+        }
+        if !metadata.languages.is_empty() {
+            deck.serialize_entry("languages", &Languages(&metadata.languages))?;
+        }
+        if !metadata.tags.is_empty() {
+            deck.serialize_entry("tags", &metadata.tags)?;
+        }
+        if !metadata.info_faces.is_empty() {
+            deck.serialize_entry("info_faces", &metadata.info_faces)?;
+        }
+        if !metadata.include.is_empty() {
+            deck.serialize_entry("include", &metadata.include)?;
+        }
+        if let Some(filter) = metadata.filter.as_ref() {
+            deck.serialize_entry("filter", &filter.to_string())?;
+        }
+        deck.serialize_entry("faces", &self.faces)?;
+        deck.serialize_entry("cards", &cards)?;
+        for (key, value) in metadata.unknown.iter() {
+            deck.serialize_entry(key, value)?;
+        }
+        deck.end()
+    }
+}
+
+struct Languages<'a>(&'a [(String, String)]);
+
+impl<'a> Serialize for Languages<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (face, language) in self.0 {
+            map.serialize_entry(face, language)?;
+        }
+        map.end()
+    }
+}
+
+///Single line JSON formatter with spaces after commas and colons, as in hand-written decks
+struct SpacedFormatter;
+
+impl serde_json::ser::Formatter for SpacedFormatter {
+    fn begin_array_value<W: ?Sized + std::io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> std::io::Result<()> {
+        match first {
+            true => Ok(()),
+            false => writer.write_all(b", "),
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + std::io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> std::io::Result<()> {
+        self.begin_array_value(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + std::io::Write>(
+        &mut self,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        writer.write_all(b": ")
+    }
+}
+
+///Deck as written, before keyed cards are arranged by the deck's faces
+struct DeckJson {
+    name: String,
+    metadata: DeckMetadata,
+    faces: Vec<String>,
+    cards: Vec<CardJson>,
+}
+
+impl From<DeckJson> for Deck {
+    fn from(deck: DeckJson) -> Self {
+        let cards = deck
+            .cards
+            .into_iter()
+            .map(|card| card.into_card(&deck.faces))
+            .collect();
+
+        Self {
+            name: deck.name,
+            metadata: deck.metadata,
+            faces: deck.faces,
+            cards,
+        }
+    }
+}
+
+///Metadata text, which may also be written as a number, eg. a version of 1.2
+fn metadata_string<E: serde::de::Error>(key: &str, value: serde_json::Value) -> Result<String, E> {
+    match value {
+        serde_json::Value::String(value) => Ok(value),
+        serde_json::Value::Number(value) => Ok(value.to_string()),
+        _ => Err(E::custom(format_args!("\"{key}\" must be a string"))),
+    }
+}
+
+struct DeckVisitor;
+
+impl<'de> Visitor<'de> for DeckVisitor {
+    type Value = DeckJson;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a deck with a name, faces and cards")
+    }
+
+    //NOTE: Visited by hand, rather than flattening unknown keys into a map, so that
+    //errors within cards keep their location
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        use serde::de::Error;
+
+        let mut name = None;
+        let mut faces = None;
+        let mut cards = None;
+        let mut metadata = DeckMetadata::default();
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "name" if name.is_some() => return Err(A::Error::duplicate_field("name")),
+                "faces" if faces.is_some() => return Err(A::Error::duplicate_field("faces")),
+                "cards" if cards.is_some() => return Err(A::Error::duplicate_field("cards")),
+                "name" => name = Some(map.next_value()?),
+                "faces" => faces = Some(map.next_value()?),
+                "cards" => cards = Some(map.next_value()?),
+                "description" | "author" | "version" | "license" | "source" => {
+                    let value = Some(metadata_string(&key, map.next_value()?)?);
+                    match key.as_str() {
+                        "description" => metadata.description = value,
+                        "author" => metadata.author = value,
+                        "version" => metadata.version = value,
+                        "license" => metadata.license = value,
+                        _ => metadata.source = value,
+                    }
+                }
+                "languages" => {
+                    metadata.languages = map
+                        .next_value::<serde_json::Map<String, serde_json::Value>>()?
+                        .into_iter()
+                        .map(|(face, language)| Ok((face, metadata_string("languages", language)?)))
+                        .collect::<Result<_, A::Error>>()?;
+                }
+                "tags" => metadata.tags = map.next_value()?,
+                "info_faces" => metadata.info_faces = map.next_value()?,
+                "extends" => metadata.extends = Some(metadata_string(&key, map.next_value()?)?),
+                "include" => metadata.include = map.next_value()?,
+                "filter" => {
+                    let filter = metadata_string::<A::Error>(&key, map.next_value()?)?;
+                    metadata.filter = Some(filter.parse().map_err(A::Error::custom)?);
+                }
+                _ => {
+                    let value = map.next_value()?;
+                    metadata.unknown.insert(key, value);
+                }
+            }
+        }
+
+        //NOTE: Decks extending another inherit its faces once they're composed, and decks
+        //including others may have no cards of their own
+        let faces = match faces {
+            Some(faces) => faces,
+            None if metadata.extends.is_some() => vec![],
+            None => return Err(A::Error::missing_field("faces")),
+        };
+        let cards = match cards {
+            Some(cards) => cards,
+            None if !metadata.include.is_empty() => vec![],
+            None => return Err(A::Error::missing_field("cards")),
+        };
+
+        Ok(DeckJson {
+            name: name.ok_or_else(|| A::Error::missing_field("name"))?,
+            metadata,
+            faces,
+            cards,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for DeckJson {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(DeckVisitor)
+    }
+}
+
 impl PartialEq for Deck {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
@@ -82,12 +491,18 @@ impl Deref for Deck {
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
-#[repr(transparent)]
-pub struct Card(Vec<Option<Face>>);
+#[derive(Clone, Debug)]
+pub struct Card {
+    faces: Vec<Option<Face>>,
+    ///Face names in the order they were written, if the card was written as an object
+    keys: Option<Vec<String>>,
+    tags: Vec<String>,
+    ///Hints towards the card's faces, revealed one at a time when stuck on a problem
+    hints: Vec<String>,
+}
 
 ///Card within a deck must have at least two faces: a front and back
-const MIN_FACE_COUNT: usize = 2;
+pub(crate) const MIN_FACE_COUNT: usize = 2;
 
 impl Card {
     pub fn new(faces: Vec<Option<impl Into<Face>>>) -> Self {
@@ -96,14 +511,78 @@ impl Card {
             "Cards must have at least two non-none faces"
         );
 
-        Self({
-            let mut buf = Vec::with_capacity(faces.len());
-            faces
-                .into_iter()
-                .map(|face| face.map(|face| face.into()))
-                .for_each(|face| buf.push(face));
-            buf
-        })
+        Self {
+            faces: {
+                let mut buf = Vec::with_capacity(faces.len());
+                faces
+                    .into_iter()
+                    .map(|face| face.map(|face| face.into()))
+                    .for_each(|face| buf.push(face));
+                buf
+            },
+            keys: None,
+            tags: vec![],
+            hints: vec![],
+        }
+    }
+
+    ///Card with the faces, without checking that it has enough of them
+    pub(crate) fn from_faces(faces: Vec<Option<Face>>) -> Self {
+        Self {
+            faces,
+            keys: None,
+            tags: vec![],
+            hints: vec![],
+        }
+    }
+
+    pub(crate) fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    ///Tags of the card itself, without its deck's default tags, see [Deck::tags_of]
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    ///Hints given for the card, if any, each of which is revealed in turn
+    pub fn hints(&self) -> &[String] {
+        &self.hints
+    }
+
+    ///Whether the card was written as an object keyed by face name
+    pub fn is_keyed(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    ///Rearranges the faces from the order of the `from` faces to the order of the `to` faces,
+    ///by name. Faces missing from `to` are dropped, and faces missing from `from` are null.
+    pub(crate) fn arrange(&mut self, from: &[String], to: &[String]) {
+        self.faces = to
+            .iter()
+            .map(|face| {
+                from.iter()
+                    .position(|other| other == face)
+                    .and_then(|i| self.faces.get(i).cloned().flatten())
+            })
+            .collect();
+    }
+
+    ///Arranges the faces of a keyed card by the deck's faces, if they're in the order
+    ///of its keys because the deck's faces weren't known when it was parsed
+    fn arrange_keys(&mut self, deck_faces: &[String]) {
+        if let Some(keys) = self.keys.clone() {
+            self.arrange(&keys, deck_faces);
+        }
+    }
+
+    ///Keys of a keyed card which aren't faces of the deck
+    fn unknown_keys<'a>(&'a self, faces: &'a [String]) -> impl Iterator<Item = &'a String> {
+        self.keys
+            .iter()
+            .flatten()
+            .filter(move |key| !faces.contains(key))
     }
 
     pub fn join(&self, sep: &str) -> String {
@@ -123,6 +602,12 @@ impl Card {
             .map(Face::to_string)
             .expect("Unable to get front_string for card, most likely due to only empty faces")
     }
+
+    fn front_or_json(&self) -> String {
+        self.front()
+            .map(Face::to_string)
+            .unwrap_or_else(|| self.to_string())
+    }
 }
 
 impl Display for Card {
@@ -135,11 +620,348 @@ impl Deref for Card {
     type Target = Vec<Option<Face>>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.faces
+    }
+}
+
+impl PartialEq for Card {
+    fn eq(&self, other: &Self) -> bool {
+        self.faces == other.faces
+    }
+}
+
+impl Eq for Card {}
+
+impl Serialize for Card {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if self.tags.is_empty() && self.hints.is_empty() {
+            return self.faces.serialize(serializer);
+        }
+
+        let mut seq = serializer.serialize_seq(Some(self.faces.len() + 1))?;
+        for face in self.faces.iter() {
+            seq.serialize_element(face)?;
+        }
+        seq.serialize_element(&CardExtras {
+            tags: self.tags.clone(),
+            hint: hint_face(&self.hints),
+        })?;
+        seq.end()
+    }
+}
+
+///Tags and hint of a card written as an array, given by an object after its faces
+#[derive(Serialize, Default)]
+struct CardExtras {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<Face>,
+}
+
+///Key of the tags of a card written as an object, unless the deck has a face of the same name
+const TAGS_KEY: &str = "tags";
+
+///Key of the hint of a card written as an object, unless the deck has a face of the same name
+const HINT_KEY: &str = "hint";
+
+///Hints of a card, written as a single hint or a list of them
+fn hint_face(hints: &[String]) -> Option<Face> {
+    match hints {
+        [] => None,
+        [hint] => Some(Face::Single(hint.clone())),
+        hints => Some(Face::Multi(hints.to_vec())),
+    }
+}
+
+fn hint_list(hint: Option<Face>) -> Vec<String> {
+    match hint {
+        Some(Face::Single(hint)) => vec![hint],
+        Some(Face::Multi(hints)) => hints,
+        None => vec![],
+    }
+}
+
+///Card along with its deck's faces, so that keyed cards can be serialized as they were written
+struct StyledCard<'a> {
+    card: &'a Card,
+    faces: &'a [String],
+}
+
+impl<'a> Serialize for StyledCard<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let Some(keys) = self.card.keys.as_ref() else {
+            return self.card.serialize(serializer);
+        };
+
+        let tags = !self.card.tags.is_empty();
+        let hint = hint_face(&self.card.hints);
+        let mut map = serializer.serialize_map(Some(
+            keys.len() + usize::from(tags) + usize::from(hint.is_some()),
+        ))?;
+        for key in keys {
+            let face = self
+                .faces
+                .iter()
+                .position(|face| face == key)
+                .and_then(|i| self.card.get(i))
+                .and_then(Option::as_ref);
+            map.serialize_entry(key, &face)?;
+        }
+        if tags {
+            map.serialize_entry(TAGS_KEY, &self.card.tags)?;
+        }
+        if let Some(hint) = hint {
+            map.serialize_entry(HINT_KEY, &hint)?;
+        }
+        map.end()
+    }
+}
+
+///Card as written, either as an array of faces optionally followed by its tags and hint,
+///or an object keyed by face name
+enum CardJson {
+    Positional(Vec<Option<Face>>, CardExtras),
+    Keyed(Vec<(String, Option<Face>)>),
+}
+
+impl CardJson {
+    ///Arranges the faces of keyed cards by the deck's faces, leaving out unknown keys
+    ///so that they can be reported once the deck is validated
+    fn into_card(self, deck_faces: &[String]) -> Card {
+        match self {
+            Self::Positional(faces, CardExtras { tags, hint }) => Card {
+                faces,
+                keys: None,
+                tags,
+                hints: hint_list(hint),
+            },
+            //NOTE: Faces inherited from another deck aren't known until the deck is composed,
+            //so until then the faces are in the order of the card's keys, see [Card::arrange_keys]
+            Self::Keyed(entries) if deck_faces.is_empty() => {
+                let mut keys: Vec<String> = vec![];
+                for (key, _) in entries
+                    .iter()
+                    .filter(|(key, _)| key != TAGS_KEY && key != HINT_KEY)
+                {
+                    if !keys.contains(key) {
+                        keys.push(key.clone());
+                    }
+                }
+                Self::Keyed(entries).into_card(&keys)
+            }
+            Self::Keyed(entries) => {
+                let mut faces = vec![None; deck_faces.len()];
+                let mut keys: Vec<String> = Vec::with_capacity(entries.len());
+                let mut tags = vec![];
+                let mut hint = None;
+
+                for (key, face) in entries {
+                    if key == TAGS_KEY && !deck_faces.contains(&key) {
+                        tags = match face {
+                            Some(Face::Single(tag)) => vec![tag],
+                            Some(Face::Multi(face_tags)) => face_tags,
+                            None => vec![],
+                        };
+                        continue;
+                    }
+                    if key == HINT_KEY && !deck_faces.contains(&key) {
+                        hint = face;
+                        continue;
+                    }
+                    if let Some(i) = deck_faces.iter().position(|deck_face| *deck_face == key) {
+                        faces[i] = face;
+                    }
+                    if !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+
+                Card {
+                    faces,
+                    keys: Some(keys),
+                    tags,
+                    hints: hint_list(hint),
+                }
+            }
+        }
+    }
+}
+
+struct CardVisitor;
+
+impl<'de> Visitor<'de> for CardVisitor {
+    type Value = CardJson;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a sequence of faces, or a map of face names to faces")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut faces = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        let mut extras = None;
+        while let Some(element) = seq.next_element()? {
+            match (element, &extras) {
+                (_, Some(_)) => {
+                    return Err(serde::de::Error::custom(
+                        "card tags and hints must come after all of its faces",
+                    ))
+                }
+                (CardElement::Face(face), None) => faces.push(face),
+                (CardElement::Extras(card_extras), None) => extras = Some(card_extras),
+            }
+        }
+
+        Ok(CardJson::Positional(faces, extras.unwrap_or_default()))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+
+        Ok(CardJson::Keyed(entries))
+    }
+}
+
+///Element of a card written as an array, either a face or, after every face, its tags and hint
+enum CardElement {
+    Face(Option<Face>),
+    Extras(CardExtras),
+}
+
+struct CardElementVisitor;
+
+impl<'de> Visitor<'de> for CardElementVisitor {
+    type Value = CardElement;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str(
+            "a face, null, or the card's tags and hint as {\"tags\": [...], \"hint\": ...}",
+        )
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(CardElement::Face(None))
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(CardElement::Face(None))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        FaceVisitor
+            .visit_seq(seq)
+            .map(|face| CardElement::Face(Some(face)))
+    }
+
+    fn visit_str<E>(self, face: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        FaceVisitor
+            .visit_str(face)
+            .map(|face| CardElement::Face(Some(face)))
+    }
+
+    fn visit_i64<E>(self, face: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        FaceVisitor
+            .visit_i64(face)
+            .map(|face| CardElement::Face(Some(face)))
+    }
+
+    fn visit_u64<E>(self, face: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        FaceVisitor
+            .visit_u64(face)
+            .map(|face| CardElement::Face(Some(face)))
+    }
+
+    fn visit_f64<E>(self, face: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        FaceVisitor
+            .visit_f64(face)
+            .map(|face| CardElement::Face(Some(face)))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut tags = None;
+        let mut hint = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                TAGS_KEY if tags.is_none() => tags = Some(map.next_value()?),
+                HINT_KEY if hint.is_none() => hint = Some(map.next_value()?),
+                TAGS_KEY => return Err(serde::de::Error::duplicate_field(TAGS_KEY)),
+                HINT_KEY => return Err(serde::de::Error::duplicate_field(HINT_KEY)),
+                _ => return Err(serde::de::Error::unknown_field(&key, &[TAGS_KEY, HINT_KEY])),
+            }
+        }
+
+        Ok(CardElement::Extras(CardExtras {
+            tags: tags.unwrap_or_default(),
+            hint,
+        }))
+    }
+}
+
+impl<'de> Deserialize<'de> for CardElement {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(CardElementVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for CardJson {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(CardVisitor)
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
 #[repr(transparent)]
 pub struct CardId(String);
 
@@ -149,8 +971,44 @@ impl CardId {
         let card = card.front_string();
         Self(format!("{deck}:{card}"))
     }
+
+    ///Id of one of the card's cloze deletions, whose stats are kept apart from the card's
+    pub(crate) fn with_cloze(&self, number: u32) -> Self {
+        Self(format!("{}{CLOZE_ID_SEPARATOR}{number}", self.0))
+    }
+
+    ///Number of the cloze deletion of the card this is the id of, if it is one
+    pub(crate) fn cloze_of(&self, card: &CardId) -> Option<u32> {
+        self.0
+            .strip_prefix(card.0.as_str())?
+            .strip_prefix(CLOZE_ID_SEPARATOR)?
+            .parse()
+            .ok()
+    }
+
+    ///Ids of the card along with each of its cloze deletions
+    pub(crate) fn with_clozes(deck: &Deck, card: &Card) -> Vec<Self> {
+        let id = Self::get(deck, card);
+        let mut ids = deck
+            .cloze_faces(card)
+            .into_iter()
+            .filter_map(|i| card[i].as_ref())
+            .flat_map(cloze::numbers)
+            .map(|number| id.with_cloze(number))
+            .collect::<Vec<_>>();
+        ids.dedup();
+        ids.insert(0, id);
+        ids
+    }
+
+    ///Id a card would have within the deck if it had the given front
+    pub(crate) fn with_front(deck: &str, front: &Face) -> Self {
+        Self(format!("{deck}:{front}"))
+    }
 }
 
+const CLOZE_ID_SEPARATOR: &str = "::c";
+
 impl Deref for CardId {
     type Target = String;
 
@@ -161,17 +1019,24 @@ impl Deref for CardId {
 
 impl<'a> From<&DeckCard<'a>> for CardId {
     fn from(dc: &DeckCard<'a>) -> Self {
-        Self::get(dc.deck, dc.card)
+        let id = Self::get(dc.deck, dc.card);
+        match dc.cloze {
+            Some(cloze) => id.with_cloze(cloze.number),
+            None => id,
+        }
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+///Face of a card, which may be divided into subfaces. Faces may annotate text with its
+///reading (furigana), eg. `{日本|にほん}`, which is ignored when comparing faces.
+#[derive(Clone, Debug)]
 pub enum Face {
     Single(String),
     Multi(Vec<String>),
 }
 
 impl Face {
+    ///Subfaces joined by the inferred separator, keeping any reading annotations
     pub fn join(&self) -> String {
         match self {
             Self::Single(face) => face.clone(),
@@ -190,6 +1055,39 @@ impl Face {
         }
     }
 
+    ///Subfaces joined as they're read, without any markup, eg. "日本語" for
+    ///"**{日本|にほん}**語"
+    pub fn plain(&self) -> String {
+        plain_text(&self.join())
+    }
+
+    ///Whether any subface annotates text with its reading
+    pub fn has_ruby(&self) -> bool {
+        match self {
+            Self::Single(face) => ruby::has_ruby(face),
+            Self::Multi(faces) => faces.iter().any(|face| ruby::has_ruby(face)),
+        }
+    }
+
+    ///Whether the typed input matches this face, ignoring case and surrounding whitespace,
+    ///along with any normalization specific to the face's language, like romaji for Japanese.
+    ///Multi faces match either any one of their subfaces, or all of them joined. Annotated
+    ///text may be typed either as written or as its reading, and styles are ignored.
+    pub fn is_match(&self, input: &str, language: Option<&str>) -> bool {
+        let normalize = |face: &str| language::normalize(face, language);
+        let input = normalize(input);
+        let matches = |face: &str| {
+            let face = cloze::reveal(face);
+            let face = rich::strip(&face);
+            normalize(&ruby::plain(&face)) == input || normalize(&ruby::readings(&face)) == input
+        };
+
+        match self {
+            Self::Single(face) => matches(face),
+            Self::Multi(faces) => faces.iter().any(|face| matches(face)) || matches(&self.join()),
+        }
+    }
+
     pub fn infer_separator(&self) -> &str {
         if self.contains(",") {
             "; "
@@ -198,10 +1096,19 @@ impl Face {
         }
     }
 
+    ///Whether any subface contains the pattern, ignoring any markup
     pub fn contains(&self, pat: &str) -> bool {
         match self {
-            Self::Single(face) => face.contains(pat),
-            Self::Multi(faces) => faces.iter().any(|face| face.contains(pat)),
+            Self::Single(face) => plain_text(face).contains(pat),
+            Self::Multi(faces) => faces.iter().any(|face| plain_text(face).contains(pat)),
+        }
+    }
+
+    ///Subfaces in the order they're written, with a single face being its only subface
+    pub fn subfaces(&self) -> &[String] {
+        match self {
+            Self::Single(face) => std::slice::from_ref(face),
+            Self::Multi(faces) => faces,
         }
     }
 
@@ -216,6 +1123,30 @@ impl Face {
     }
 }
 
+//NOTE: Compared without markup, so that faces which are written the same are equal,
+//eg. when finding duplicate cards or distinct distractors
+impl PartialEq for Face {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Single(a), Self::Single(b)) => plain_text(a) == plain_text(b),
+            (Self::Multi(a), Self::Multi(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|(a, b)| plain_text(a) == plain_text(b))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Face {}
+
+///Text as it's compared and typed, without any styles, reading annotations or clozes
+pub(crate) fn plain_text(text: &str) -> String {
+    ruby::plain(&rich::strip(&cloze::reveal(text))).into_owned()
+}
+
 impl From<&str> for Face {
     fn from(face: &str) -> Self {
         Self::Single(face.to_owned())
@@ -277,6 +1208,28 @@ impl<'de> Visitor<'de> for FaceVisitor {
     {
         Ok(Face::Single(face.to_owned()))
     }
+
+    //NOTE: Unquoted numbers are common in hand-written YAML and TOML decks
+    fn visit_i64<E>(self, face: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Face::Single(face.to_string()))
+    }
+
+    fn visit_u64<E>(self, face: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Face::Single(face.to_string()))
+    }
+
+    fn visit_f64<E>(self, face: f64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Face::Single(face.to_string()))
+    }
 }
 
 impl<'de> Deserialize<'de> for Face {
@@ -288,14 +1241,113 @@ impl<'de> Deserialize<'de> for Face {
     }
 }
 
+///Where within a deck file an error originated, along with the offending source lines
+#[derive(Debug, Clone)]
+pub struct DeckSource {
+    pub path: PathBuf,
+    pub deck: Option<String>,
+    pub card: Option<usize>,
+    pub location: Option<SourceLocation>,
+    pub snippet: Vec<(usize, String)>,
+}
+
+///Maximum number of source lines shown for an error
+const MAX_SNIPPET_LINES: usize = 6;
+
+impl DeckSource {
+    fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            deck: None,
+            card: None,
+            location: None,
+            snippet: vec![],
+        }
+    }
+
+    fn deck(mut self, deck: &Deck) -> Self {
+        self.deck = Some(deck.name.clone());
+        self
+    }
+
+    fn card(mut self, card: usize) -> Self {
+        self.card = Some(card);
+        self
+    }
+
+    fn span(mut self, source: &str, span: Option<Range<usize>>) -> Self {
+        if let Some(span) = span {
+            self.location = Some(SourceLocation::from_offset(source, span.start));
+            self.snippet = snippet(source, span, MAX_SNIPPET_LINES);
+        }
+        self
+    }
+
+    fn location(mut self, source: &str, location: SourceLocation) -> Self {
+        self.location = Some(location);
+        self.snippet = source
+            .lines()
+            .nth(location.line.saturating_sub(1))
+            .map(|line| vec![(location.line, line.to_owned())])
+            .unwrap_or_default();
+        self
+    }
+}
+
+impl Display for DeckSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let gutter = self
+            .snippet
+            .last()
+            .map_or(1, |(line, _)| line.to_string().len());
+        let pad = " ".repeat(gutter);
+
+        f.write_fmt(format_args!(
+            "\n{pad}--> {}",
+            self.path.to_str().unwrap_or("unknown")
+        ))?;
+        if let Some(location) = self.location {
+            f.write_fmt(format_args!(":{location}"))?;
+        }
+
+        if !self.snippet.is_empty() {
+            f.write_fmt(format_args!("\n{pad} |"))?;
+            for (line, text) in self.snippet.iter() {
+                f.write_fmt(format_args!("\n{line:>gutter$} | {text}"))?;
+                //NOTE: Marked beneath the line the location is on, which is the first line
+                //of the snippet for spans over several lines
+                if let Some(location) = self.location.filter(|location| location.line == *line) {
+                    let marker = " ".repeat(location.column - 1);
+                    f.write_fmt(format_args!("\n{pad} | {marker}^"))?;
+                }
+            }
+        }
+
+        match (self.deck.as_ref(), self.card) {
+            (Some(deck), Some(card)) => {
+                f.write_fmt(format_args!("\n{pad} = in deck \"{deck}\", cards[{card}]"))
+            }
+            (Some(deck), None) => f.write_fmt(format_args!("\n{pad} = in deck \"{deck}\"")),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum DeckError {
     IoError(PathBuf, std::io::Error),
-    SerdeError(PathBuf, serde_json::Error),
-    NotEnoughFaces(Deck),
-    DuplicateFace(Deck, String),
-    DuplicateDeckNames(String),
-    InvalidCard(Deck, CardError),
+    SerdeError(Box<DeckSource>, ParseError),
+    NotEnoughFaces(Box<DeckSource>, usize),
+    DuplicateFace(Box<DeckSource>, String),
+    DuplicateDeckNames(String, PathBuf, PathBuf),
+    InvalidCard(Box<DeckSource>, CardError),
+    GlobError(String, glob::PatternError),
+    NoGlobMatches(String),
+    ///Decks which include or extend each other, in order, ending with the first again
+    IncludeCycle(Vec<PathBuf>),
+    ///Cards with the same front, from the paths of the deck or file of each, once included
+    ///in the deck at the first path
+    DuplicateIncludedCard(PathBuf, String, PathBuf, PathBuf),
 }
 
 impl Display for DeckError {
@@ -305,25 +1357,42 @@ impl Display for DeckError {
                 "IoError: {err}, path: {}",
                 path.to_str().unwrap_or("unknown")
             )),
-            Self::SerdeError(path, err) => f.write_fmt(format_args!(
-                "SerdeError: {err}, path: {}",
-                path.to_str().unwrap_or("unknown")
+            Self::SerdeError(source, err) => {
+                f.write_fmt(format_args!("SerdeError: {err}{source}"))
+            }
+            Self::NotEnoughFaces(source, face_count) => f.write_fmt(format_args!(
+                "NotEnoughFaces: Deck does not have enough faces. Requires {MIN_FACE_COUNT}, has {face_count}{source}"
             )),
-            Self::NotEnoughFaces(deck) => f.write_fmt(format_args!(
-                "NotEnoughFaces: Deck \"{}\" does not have enough faces. Requires two, has {}",
-                deck.name,
-                deck.faces.len()
+            Self::DuplicateFace(source, face) => f.write_fmt(format_args!(
+                "DuplicateFaces: Deck has more than one \"{face}\" face{source}"
             )),
-            Self::DuplicateFace(deck, face) => f.write_fmt(format_args!(
-                "DuplicateFaces: Deck \"{}\" has more than one \"{face}\" face",
-                deck.name
+            Self::DuplicateDeckNames(name, path_a, path_b) => f.write_fmt(format_args!(
+                "DuplicateDecks: Two decks loaded have the same name, {name}\n  --> {}\n  --> {}",
+                path_a.to_str().unwrap_or("unknown"),
+                path_b.to_str().unwrap_or("unknown")
             )),
-            Self::DuplicateDeckNames(name) => f.write_fmt(format_args!(
-                "DuplicateDecks: At least two decks loaded have the same name, {name}"
+            Self::InvalidCard(source, err) => {
+                f.write_fmt(format_args!("InvalidCard: {err}{source}"))
+            }
+            Self::GlobError(pattern, err) => {
+                f.write_fmt(format_args!("GlobError: {err}, pattern: {pattern}"))
+            }
+            Self::NoGlobMatches(pattern) => f.write_fmt(format_args!(
+                "NoGlobMatches: No files or directories match the pattern {pattern}"
             )),
-            Self::InvalidCard(deck, err) => f.write_fmt(format_args!(
-                "InvalidCard: Deck \"{}\" contains an invalid card: {err}",
-                deck.name
+            Self::IncludeCycle(paths) => f.write_fmt(format_args!(
+                "IncludeCycle: Decks include or extend each other in a cycle: {}",
+                paths
+                    .iter()
+                    .map(|path| path.to_str().unwrap_or("unknown"))
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            )),
+            Self::DuplicateIncludedCard(path, front, path_a, path_b) => f.write_fmt(format_args!(
+                "DuplicateIncludedCard: Cards with the same front, {front}, are in both {} and {}, once included in {}",
+                path_a.to_str().unwrap_or("unknown"),
+                path_b.to_str().unwrap_or("unknown"),
+                path.to_str().unwrap_or("unknown")
             )),
         }
     }
@@ -334,8 +1403,10 @@ pub enum CardError {
     DuplicateFront(Box<(Face, Card, Card)>),
     EmptyFace(Card),
     NotEnoughFaces(Card, usize),
-    NotEnoughUsableFaces(Card),
+    ///Card has fewer non-null faces, other than info faces, than needed
+    NotEnoughUsableFaces(Card, usize),
     TooManyFaces(Card, usize),
+    UnknownFace(Box<(Card, String, Option<String>)>),
 }
 
 impl Display for CardError {
@@ -351,20 +1422,140 @@ impl Display for CardError {
                 f.write_fmt(format_args!("\"{card}\" has at least one empty face"))
             }
             Self::NotEnoughFaces(card, expected) => {
-                let front = card.front_string();
+                let front = card.front_or_json();
                 let face_count = card.len();
                 f.write_fmt(format_args!("Card with front \"{front}\" does not have enough faces. Has {face_count}, needs {expected}"))
             }
-            Self::NotEnoughUsableFaces(card) => {
-                let front = card.front_string();
-                let face_count = card.len();
-                f.write_fmt(format_args!("Card with front \"{front}\" does not have enough usable (non-null) faces. Has {face_count}, needs {}", MIN_FACE_COUNT))
+            Self::NotEnoughUsableFaces(card, face_count) => {
+                let front = card.front_or_json();
+                f.write_fmt(format_args!("Card with front \"{front}\" does not have enough usable (non-null, non-info) faces. Has {face_count}, needs {}", MIN_FACE_COUNT))
             }
             Self::TooManyFaces(card, expected) => {
-                let front = card.front_string();
+                let front = card.front_or_json();
                 let face_count = card.len();
                 f.write_fmt(format_args!("Card with front \"{front}\" has too many faces. Has {face_count}, needs {expected}"))
             }
+            Self::UnknownFace(card_box) => {
+                let (card, key, suggestion) = card_box.as_ref();
+                let front = card.front_or_json();
+                f.write_fmt(format_args!(
+                    "Card with front \"{front}\" has a \"{key}\" face, which is not one of the deck's faces"
+                ))?;
+                match suggestion {
+                    Some(suggestion) => {
+                        f.write_fmt(format_args!(", did you mean \"{suggestion}\"?"))
+                    }
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+///Error from parsing a deck file in any of the supported formats
+#[derive(Debug)]
+pub enum ParseError {
+    Json(serde_json::Error),
+    Yaml(serde_yaml::Error),
+    Toml(toml::de::Error),
+    Csv(csv::Error),
+    Markdown(MarkdownError),
+}
+
+impl ParseError {
+    ///Where in the source the error occurred, if known
+    pub fn location(&self, source: &str) -> Option<SourceLocation> {
+        match self {
+            Self::Json(err) => Some(SourceLocation::new(err.line(), err.column().max(1))),
+            Self::Yaml(err) => err
+                .location()
+                .map(|location| SourceLocation::from_offset(source, location.index())),
+            Self::Toml(err) => err
+                .span()
+                .map(|span| SourceLocation::from_offset(source, span.start)),
+            Self::Csv(err) => err
+                .position()
+                .map(|position| SourceLocation::from_offset(source, position.byte() as usize)),
+            Self::Markdown(err) => Some(SourceLocation::new(err.line, err.column)),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(err) => f.write_fmt(format_args!("{err}")),
+            Self::Yaml(err) => f.write_fmt(format_args!("{err}")),
+            //NOTE: Toml errors include their own snippet, which is redundant with ours
+            Self::Toml(err) => f.write_str(err.message()),
+            Self::Csv(err) => f.write_fmt(format_args!("{err}")),
+            Self::Markdown(err) => f.write_fmt(format_args!("{err}")),
+        }
+    }
+}
+
+///Format of a deck file, decided by its extension
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum DeckFormat {
+    Json,
+    Yaml,
+    Toml,
+    Csv,
+    Tsv,
+    Markdown,
+}
+
+impl DeckFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match file_extension(path)?.to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "csv" => Some(Self::Csv),
+            "tsv" => Some(Self::Tsv),
+            "md" | "markdown" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    ///Parses the source, which was read from the path. Only tables take their name from the path.
+    pub fn parse(&self, path: &Path, source: &str) -> Result<Deck, ParseError> {
+        match self {
+            Self::Json => serde_json::from_str(source).map_err(ParseError::Json),
+            Self::Yaml => serde_yaml::from_str(source).map_err(ParseError::Yaml),
+            Self::Toml => toml::from_str(source).map_err(ParseError::Toml),
+            Self::Csv => parse_csv_deck(path, source, b',').map_err(ParseError::Csv),
+            Self::Tsv => parse_csv_deck(path, source, b'\t').map_err(ParseError::Csv),
+            Self::Markdown => parse_markdown_deck(path, source).map_err(ParseError::Markdown),
+        }
+    }
+
+    pub fn spans(&self, source: &str) -> DeckSpans {
+        match self {
+            Self::Json => json_deck_spans(source),
+            Self::Yaml => yaml_deck_spans(source),
+            Self::Toml => toml_deck_spans(source),
+            Self::Csv => csv_deck_spans(source, b','),
+            Self::Tsv => csv_deck_spans(source, b'\t'),
+            Self::Markdown => markdown_deck_spans(source),
+        }
+    }
+}
+
+///How deck files are searched for within directories
+#[derive(Clone, Copy, Debug)]
+pub struct DeckSearch {
+    ///Levels of subdirectories to search below each directory given, or unlimited if None
+    pub max_depth: Option<usize>,
+    ///Whether symlinks within directories are followed. Paths given directly are always followed.
+    pub follow_symlinks: bool,
+}
+
+impl Default for DeckSearch {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            follow_symlinks: true,
         }
     }
 }
@@ -372,145 +1563,358 @@ impl Display for CardError {
 pub fn load_decks<P: Into<PathBuf>>(
     paths: impl IntoIterator<Item = P>,
 ) -> Result<Vec<Deck>, DeckError> {
-    let decks = paths.into_iter().try_fold(vec![], |mut decks, path| {
-        decks.extend(load_decks_from_path(path.into())?.into_iter().flatten());
-        Ok(decks)
-    })?;
+    load_decks_with(paths, &DeckSearch::default())
+}
+
+///Loads the decks in the paths, searching directories as given by `search`
+pub fn load_decks_with<P: Into<PathBuf>>(
+    paths: impl IntoIterator<Item = P>,
+    search: &DeckSearch,
+) -> Result<Vec<Deck>, DeckError> {
+    let decks = find_deck_files_with(paths, search)?
+        .into_iter()
+        .filter_map(|path| {
+            load_deck_from_file(&path)
+                .transpose()
+                .map(|deck| deck.map(|deck| (path, deck)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
     validate_decks(&decks)?;
 
-    Ok(decks)
+    Ok(decks.into_iter().map(|(_, deck)| deck).collect())
+}
+
+pub(crate) fn find_deck_files<P: Into<PathBuf>>(
+    paths: impl IntoIterator<Item = P>,
+) -> Result<Vec<PathBuf>, DeckError> {
+    find_deck_files_with(paths, &DeckSearch::default())
+}
+
+pub(crate) fn find_deck_files_with<P: Into<PathBuf>>(
+    paths: impl IntoIterator<Item = P>,
+    search: &DeckSearch,
+) -> Result<Vec<PathBuf>, DeckError> {
+    paths.into_iter().try_fold(vec![], |mut files, path| {
+        for path in expand_glob(path.into())? {
+            files.extend(
+                find_deck_files_in_path(path, search, 0, &mut vec![])?
+                    .into_iter()
+                    .flatten(),
+            );
+        }
+        Ok(files)
+    })
+}
+
+///Paths matching the path, if it's a glob pattern such as "decks/**/kanji*.json" rather
+///than an existing path. Fails if the pattern matches nothing.
+fn expand_glob(path: PathBuf) -> Result<Vec<PathBuf>, DeckError> {
+    let pattern = match path.to_str() {
+        Some(pattern) if pattern.contains(['*', '?', '[']) && !path.exists() => pattern,
+        _ => return Ok(vec![path]),
+    };
+
+    let mut paths = glob::glob(pattern)
+        .map_err(|err| DeckError::GlobError(pattern.to_owned(), err))?
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    //NOTE: Sorting so that decks, and their errors, are always in the same order
+    paths.sort();
+
+    match paths.is_empty() {
+        true => Err(DeckError::NoGlobMatches(pattern.to_owned())),
+        false => Ok(paths),
+    }
 }
 
-fn load_decks_from_path(path: PathBuf) -> Result<Option<Vec<Deck>>, DeckError> {
+///Finds the deck files of the path, where `depth` is how many directories below a given
+///path it is, and `ancestors` the directories being searched which contain it
+fn find_deck_files_in_path(
+    path: PathBuf,
+    search: &DeckSearch,
+    depth: usize,
+    ancestors: &mut Vec<PathBuf>,
+) -> Result<Option<Vec<PathBuf>>, DeckError> {
     let metadata = std::fs::metadata(&path).map_err(|err| DeckError::IoError(path.clone(), err))?;
 
     if metadata.is_dir() {
-        load_decks_from_dir(path).map(Some)
-    } else if file_extension(&path).is_some_and(|ext| ext.to_lowercase() == "json") {
-        load_deck_from_file(path).map(|deck| Some(vec![deck]))
+        find_deck_files_in_dir(path, search, depth, ancestors).map(Some)
+    } else if DeckFormat::from_path(&path).is_some() {
+        Ok(Some(vec![path]))
     } else {
         Ok(None)
     }
 }
 
-fn file_extension(path: &PathBuf) -> Option<&str> {
-    let path = Path::new(path);
+fn file_extension(path: &Path) -> Option<&str> {
     path.extension().and_then(OsStr::to_str)
 }
 
-fn load_decks_from_dir(path: PathBuf) -> Result<Vec<Deck>, DeckError> {
-    let files = fs::read_dir(&path)
+fn find_deck_files_in_dir(
+    path: PathBuf,
+    search: &DeckSearch,
+    depth: usize,
+    ancestors: &mut Vec<PathBuf>,
+) -> Result<Vec<PathBuf>, DeckError> {
+    //NOTE: Symlinks may link back to a directory being searched, which would never end
+    let canonical = fs::canonicalize(&path).map_err(|err| DeckError::IoError(path.clone(), err))?;
+    if ancestors.contains(&canonical) {
+        return Ok(vec![]);
+    }
+
+    let mut files = fs::read_dir(&path)
         .map_err(|err| DeckError::IoError(path, err))?
         .filter_map(|file| file.ok())
+        .filter(|file| {
+            search.follow_symlinks || file.file_type().is_ok_and(|kind| !kind.is_symlink())
+        })
         .collect::<Vec<_>>();
+    files.sort_by_key(|file| file.path());
     let len = files.len();
 
-    files
+    ancestors.push(canonical);
+    let decks = files
         .into_iter()
+        .filter(|file| {
+            search.max_depth.is_none_or(|max_depth| depth < max_depth)
+                || fs::metadata(file.path()).is_ok_and(|metadata| !metadata.is_dir())
+        })
         .try_fold(Vec::with_capacity(len), |mut decks, file| {
-            decks.extend(load_decks_from_path(file.path())?.into_iter().flatten());
+            decks.extend(
+                find_deck_files_in_path(file.path(), search, depth + 1, ancestors)?
+                    .into_iter()
+                    .flatten(),
+            );
             Ok(decks)
-        })
+        });
+    ancestors.pop();
+
+    decks
 }
 
-fn load_deck_from_file(path: PathBuf) -> Result<Deck, DeckError> {
+///Loads the deck in the file, or None for files of cards, which are only loaded when
+///included by a deck
+fn load_deck_from_file(path: &Path) -> Result<Option<Deck>, DeckError> {
+    if is_card_file(path) {
+        return Ok(None);
+    }
+
     let json =
-        std::fs::read_to_string(&path).map_err(|err| DeckError::IoError(path.clone(), err))?;
-    let deck = serde_json::from_str(&json).map_err(|err| DeckError::SerdeError(path, err))?;
+        std::fs::read_to_string(path).map_err(|err| DeckError::IoError(path.to_path_buf(), err))?;
+    parse_deck(path, &json).map(Some)
+}
+
+///Parses, composes and validates the deck source, which was read from the path.
+///The format of the source is decided by the path's extension, defaulting to JSON.
+pub(crate) fn parse_deck(path: &Path, source: &str) -> Result<Deck, DeckError> {
+    parse_composed_deck(path, source, &mut vec![])
+}
+
+///Parses the deck source like [parse_deck], where `chain` holds the decks being composed
+///which led to this one, see [compose_deck]
+pub(crate) fn parse_composed_deck(
+    path: &Path,
+    source: &str,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Deck, DeckError> {
+    let format = DeckFormat::from_path(path).unwrap_or(DeckFormat::Json);
+    let deck = format
+        .parse(path, source)
+        .map_err(|err| serde_error(path, source, err))?;
+    let deck = compose_deck(deck, path, chain)?;
+
+    validate_deck(deck, path, format, source)
+}
+
+fn serde_error(path: &Path, source: &str, err: ParseError) -> DeckError {
+    let deck_source = match err.location(source) {
+        Some(location) => DeckSource::new(path).location(source, location),
+        None => DeckSource::new(path),
+    };
+    DeckError::SerdeError(Box::new(deck_source), err)
+}
+
+///Whether the path is of a file of cards, written as a list of cards like a deck's
+///"cards", rather than a deck. Files of cards are named like "extra.cards.json", and
+///only JSON and YAML files may be files of cards.
+pub(crate) fn is_card_file(path: &Path) -> bool {
+    matches!(
+        DeckFormat::from_path(path),
+        Some(DeckFormat::Json | DeckFormat::Yaml)
+    ) && path
+        .file_stem()
+        .and_then(|stem| Path::new(stem).extension())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("cards"))
+}
+
+///Parses a file of cards, see [is_card_file], arranging keyed cards by the faces given
+pub(crate) fn parse_card_file(
+    path: &Path,
+    source: &str,
+    faces: &[String],
+) -> Result<Vec<Card>, DeckError> {
+    let cards: Vec<CardJson> = match DeckFormat::from_path(path).unwrap_or(DeckFormat::Json) {
+        DeckFormat::Yaml => serde_yaml::from_str(source).map_err(ParseError::Yaml),
+        _ => serde_json::from_str(source).map_err(ParseError::Json),
+    }
+    .map_err(|err| serde_error(path, source, err))?;
+
+    Ok(cards
+        .into_iter()
+        .map(|card| card.into_card(faces))
+        .collect())
+}
+
+#[derive(Debug)]
+pub(crate) enum DeckIssue {
+    NotEnoughFaces,
+    DuplicateFace(String),
+    InvalidCard(usize, CardError),
+}
 
-    validate_deck(deck)
+fn validate_deck(
+    deck: Deck,
+    path: &Path,
+    format: DeckFormat,
+    source: &str,
+) -> Result<Deck, DeckError> {
+    let issue = match deck_issues(&deck).into_iter().next() {
+        None => return Ok(deck),
+        Some(issue) => issue,
+    };
+
+    let spans = format.spans(source);
+    let deck_source = DeckSource::new(path).deck(&deck);
+
+    Err(match issue {
+        DeckIssue::NotEnoughFaces => DeckError::NotEnoughFaces(
+            Box::new(deck_source.span(source, spans.key("faces"))),
+            deck.quizzed_face_count(),
+        ),
+        DeckIssue::DuplicateFace(face) => {
+            DeckError::DuplicateFace(Box::new(deck_source.span(source, spans.key("faces"))), face)
+        }
+        DeckIssue::InvalidCard(card, err) => DeckError::InvalidCard(
+            Box::new(
+                deck_source
+                    .card(card)
+                    .span(source, spans.cards.get(card).cloned()),
+            ),
+            err,
+        ),
+    })
 }
 
-fn validate_deck(deck: Deck) -> Result<Deck, DeckError> {
+///Finds every issue with the deck, rather than stopping at the first
+pub(crate) fn deck_issues(deck: &Deck) -> Vec<DeckIssue> {
     let expected_face_count = deck.faces.len();
 
-    if expected_face_count < MIN_FACE_COUNT {
-        return Err(DeckError::NotEnoughFaces(deck));
+    //NOTE: Cards with clozes are quizzed on those alone, so need only one quizzed face
+    let has_clozes = deck.iter().any(|card| !deck.cloze_faces(card).is_empty());
+    if deck.quizzed_face_count() < MIN_FACE_COUNT && !(has_clozes && deck.quizzed_face_count() > 0)
+    {
+        //NOTE: Every card would be invalid, so there's no use checking them
+        return vec![DeckIssue::NotEnoughFaces];
     }
 
-    let mut seen_faces = Vec::with_capacity(expected_face_count);
+    let mut issues = vec![];
 
-    if let Some(face) = deck.faces.iter().find(|face| {
-        if seen_faces.contains(face) {
-            true
+    let mut seen_faces = Vec::with_capacity(expected_face_count);
+    for face in deck.faces.iter() {
+        if seen_faces.contains(&face) {
+            issues.push(DeckIssue::DuplicateFace(face.clone()));
         } else {
             seen_faces.push(face);
-            false
         }
-    }) {
-        let face = face.clone();
-        return Err(DeckError::DuplicateFace(deck, face));
     }
 
-    if let Some(card) = deck.iter().find(|card| card.len() != expected_face_count) {
-        let card = card.clone();
-        return Err(DeckError::InvalidCard(
-            deck,
-            if card.len() > expected_face_count {
-                CardError::TooManyFaces(card, expected_face_count)
-            } else {
-                CardError::NotEnoughFaces(card, expected_face_count)
-            },
-        ));
+    for (i, card) in deck.iter().enumerate() {
+        if card.len() > expected_face_count {
+            issues.push(DeckIssue::InvalidCard(
+                i,
+                CardError::TooManyFaces(card.clone(), expected_face_count),
+            ));
+        } else if card.len() < expected_face_count {
+            issues.push(DeckIssue::InvalidCard(
+                i,
+                CardError::NotEnoughFaces(card.clone(), expected_face_count),
+            ));
+        }
     }
 
-    if let Some(card) = deck
-        .iter()
-        .find(|card| card.iter().flatten().count() < MIN_FACE_COUNT)
-    {
-        let card = card.clone();
-        return Err(DeckError::InvalidCard(
-            deck,
-            CardError::NotEnoughUsableFaces(card),
-        ));
+    let deck_faces = deck.faces.iter().collect::<Vec<_>>();
+    for (i, card) in deck.iter().enumerate() {
+        for key in card.unknown_keys(&deck.faces) {
+            issues.push(DeckIssue::InvalidCard(
+                i,
+                CardError::UnknownFace(Box::new((
+                    card.clone(),
+                    key.clone(),
+                    suggest(key, &deck_faces).map(|face| face.to_string()),
+                ))),
+            ));
+        }
+    }
+
+    for (i, card) in deck.iter().enumerate() {
+        let quizzed_faces = deck
+            .faces
+            .iter()
+            .zip(card.iter())
+            .filter(|(deck_face, card_face)| card_face.is_some() && !deck.is_info_face(deck_face))
+            .count();
+        if quizzed_faces < MIN_FACE_COUNT && deck.cloze_faces(card).is_empty() {
+            issues.push(DeckIssue::InvalidCard(
+                i,
+                CardError::NotEnoughUsableFaces(card.clone(), quizzed_faces),
+            ));
+        }
     }
 
-    if let Some(card) = deck.iter().find(|card| {
-        card.iter()
+    for (i, card) in deck.iter().enumerate() {
+        if card
+            .iter()
             .flatten()
             .any(|face| face.is_multi_and(|faces| faces.is_empty()))
-    }) {
-        let card = card.clone();
-        return Err(DeckError::InvalidCard(deck, CardError::EmptyFace(card)));
-    }
-
-    if let Some(card_box) = deck.iter().enumerate().find_map(|(i, card_a)| {
-        card_a.front().and_then(|front_a| {
-            deck.iter().enumerate().find_map(|(j, card_b)| {
-                (i != j).and_then(|| {
-                    card_b.front().and_then(|front_b| {
-                        (front_a == front_b)
-                            .then(|| Box::new((front_a.clone(), card_a.clone(), card_b.clone())))
-                    })
+        {
+            issues.push(DeckIssue::InvalidCard(
+                i,
+                CardError::EmptyFace(card.clone()),
+            ));
+        }
+    }
+
+    for (j, card_b) in deck.iter().enumerate() {
+        if let Some(card_box) = card_b.front().and_then(|front_b| {
+            deck.iter().take(j).find_map(|card_a| {
+                card_a.front().and_then(|front_a| {
+                    (front_a == front_b)
+                        .then(|| Box::new((front_a.clone(), card_a.clone(), card_b.clone())))
                 })
             })
-        })
-    }) {
-        return Err(DeckError::InvalidCard(
-            deck,
-            CardError::DuplicateFront(card_box),
-        ));
+        }) {
+            issues.push(DeckIssue::InvalidCard(
+                j,
+                CardError::DuplicateFront(card_box),
+            ));
+        }
     }
 
-    Ok(deck)
+    issues
 }
 
-fn validate_decks(decks: &[Deck]) -> Result<(), DeckError> {
-    let deck_names = {
-        let mut buf = Vec::with_capacity(decks.len());
-        decks.iter().for_each(|deck| buf.push(&deck.name));
-        buf
-    };
-
-    if let Some(name) = deck_names.iter().enumerate().find_map(|(i, deck_a)| {
-        deck_names
-            .iter()
-            .enumerate()
-            .any(|(j, deck_b)| i != j && deck_a == deck_b)
-            .then_some(deck_a)
-    }) {
-        return Err(DeckError::DuplicateDeckNames((*name).clone()));
+fn validate_decks(decks: &[(PathBuf, Deck)]) -> Result<(), DeckError> {
+    if let Some((name, path_a, path_b)) =
+        decks.iter().enumerate().find_map(|(j, (path_b, deck_b))| {
+            decks
+                .iter()
+                .take(j)
+                .find(|(_, deck_a)| deck_a.name == deck_b.name)
+                .map(|(path_a, _)| (deck_b.name.clone(), path_a.clone(), path_b.clone()))
+        })
+    {
+        return Err(DeckError::DuplicateDeckNames(name, path_a, path_b));
     }
 
     Ok(())
@@ -518,18 +1922,22 @@ fn validate_decks(decks: &[Deck]) -> Result<(), DeckError> {
 
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::BufWriter};
+    use std::{fs::File, io::BufWriter, path::Path};
 
-    use crate::deck::{CardError, Deck, DeckError, Face};
+    use crate::{
+        deck::{CardError, Deck, DeckError, DeckFormat, Face},
+        location::SourceLocation,
+    };
 
-    use super::{load_decks, Card};
+    use super::{load_decks, load_decks_with, parse_deck, Card, CardId, DeckSearch};
 
     #[test]
     fn serialize_deck() {
         let deck: Deck = Deck {
             name: "Test".to_owned(),
+            metadata: Default::default(),
             faces: vec!["Face 1".to_owned(), "Face 2".to_owned()],
-            cards: vec![Card(vec![
+            cards: vec![Card::new(vec![
                 Some(Face::Single("Front".to_owned())),
                 Some(Face::Multi(vec!["Back".to_owned(), "With many".to_owned()])),
                 None,
@@ -564,6 +1972,400 @@ mod tests {
         assert_eq!(deck[0][2], Some(Face::Single("Japan".into())));
     }
 
+    #[test]
+    fn load_deck_with_keyed_cards() {
+        let decks = load_decks(vec!["./tests/keyed_cards.json"])
+            .expect("Unable to load deck with keyed cards");
+        let deck = &decks[0];
+
+        assert!(!deck[0].is_keyed());
+        assert!(deck[1].is_keyed());
+        assert_eq!(
+            *deck[1],
+            vec![
+                Some(Face::Single("水".to_owned())),
+                None,
+                Some(Face::Multi(vec!["Water".to_owned()]))
+            ]
+        );
+        assert_eq!(deck[2][0], None);
+    }
+
+    #[test]
+    fn serialize_keeps_card_style() {
+        let decks = load_decks(vec!["./tests/keyed_cards.json"])
+            .expect("Unable to load deck with keyed cards");
+        let json = serde_json::to_string(&decks[0]).expect("Unable to serialize deck");
+
+        assert!(json.contains(r#"["日本","にほん","Japan"]"#));
+        assert!(json.contains(r#"{"English":["Water"],"Kanji":"水"}"#));
+        assert!(json.contains(r#"{"Hiragana":"いいえ","English":"No","Kanji":null}"#));
+    }
+
+    #[test]
+    fn load_deck_unknown_card_face() {
+        let err = load_decks(vec!["./tests/unknown_card_face.json"])
+            .expect_err("Loaded deck with unknown card face");
+
+        let DeckError::InvalidCard(source, CardError::UnknownFace(card_box)) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        let (_, key, suggestion) = card_box.as_ref();
+        assert_eq!(key, "Englsh");
+        assert_eq!(suggestion.as_deref(), Some("English"));
+        assert_eq!(source.location, Some(SourceLocation::new(5, 9)));
+    }
+
+    #[test]
+    fn load_decks_from_yaml_and_toml() {
+        let decks = load_decks(vec![
+            "./tests/formats/deck.yaml",
+            "./tests/formats/deck.toml",
+        ])
+        .expect("Unable to load YAML and TOML decks");
+        assert_eq!(decks.len(), 2);
+
+        let yaml = &decks[0];
+        assert_eq!(yaml.name, "YAML Deck");
+        assert_eq!(yaml[1][0], None);
+        assert_eq!(
+            yaml[2][2],
+            Some(Face::Single(
+                "Water, or a multiline definition of it".to_owned()
+            ))
+        );
+        assert_eq!(yaml[3][2], Some(Face::Single("10".to_owned())));
+
+        let toml = &decks[1];
+        assert_eq!(toml.name, "TOML Deck");
+        assert!(toml.iter().all(|card| card.is_keyed() && card.len() == 3));
+        assert_eq!(toml[1][0], None);
+    }
+
+    #[test]
+    fn yaml_and_toml_errors_have_source() {
+        let err = load_decks(vec!["./tests/formats/invalid_card.yaml"])
+            .expect_err("Loaded invalid YAML deck");
+        let DeckError::InvalidCard(source, CardError::NotEnoughFaces(..)) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(source.location, Some(SourceLocation::new(5, 3)));
+
+        let err = load_decks(vec!["./tests/formats/invalid_card.toml"])
+            .expect_err("Loaded invalid TOML deck");
+        let DeckError::InvalidCard(source, CardError::UnknownFace(..)) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(source.location, Some(SourceLocation::new(8, 1)));
+        assert_eq!(source.snippet.len(), 3);
+
+        let err = load_decks(vec!["./tests/formats/malformed.yaml"])
+            .expect_err("Loaded malformed YAML deck");
+        let DeckError::SerdeError(source, _) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(source.location, Some(SourceLocation::new(3, 6)));
+    }
+
+    #[test]
+    fn load_decks_from_csv_and_tsv() {
+        let decks = load_decks(vec![
+            "./tests/formats/vocab.csv",
+            "./tests/formats/vocab.tsv",
+        ])
+        .expect("Unable to load CSV and TSV decks");
+        assert_eq!(decks.len(), 2);
+
+        let csv = &decks[0];
+        assert_eq!(csv.name, "CSV Deck");
+        assert_eq!(csv.faces, vec!["Kanji", "Hiragana", "English"]);
+        assert_eq!(csv[1][0], None);
+        assert_eq!(
+            csv[1][2],
+            Some(Face::Multi(vec!["Fire".to_owned(), "Flame".to_owned()]))
+        );
+        assert_eq!(csv[2][0], Some(Face::Single("木, 樹".to_owned())));
+        assert_eq!(csv[2].len(), 3);
+
+        let tsv = &decks[1];
+        assert_eq!(tsv.name, "vocab");
+        assert_eq!(
+            tsv[0][2],
+            Some(Face::Multi(vec!["Gold".to_owned(), "Money".to_owned()]))
+        );
+        assert_eq!(tsv[1][1], None);
+
+        let err = load_decks(vec!["./tests/formats/invalid_card.csv"])
+            .expect_err("Loaded invalid CSV deck");
+        let DeckError::InvalidCard(source, CardError::NotEnoughFaces(..)) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(source.location, Some(SourceLocation::new(3, 1)));
+    }
+
+    #[test]
+    fn csv_cards_may_start_with_hash() {
+        //NOTE: Only the lines before the header row are comments and directives
+        let deck = load_decks(vec!["./tests/formats/hash_cards.csv"])
+            .expect("Unable to load CSV deck")
+            .remove(0);
+        assert_eq!(deck.name, "Symbols");
+        assert_eq!(deck.faces, vec!["Symbol", "Name"]);
+        let fronts = deck
+            .iter()
+            .map(|card| card.front_string())
+            .collect::<Vec<_>>();
+        assert_eq!(fronts, ["#", "#include", "+"]);
+
+        let source = std::fs::read_to_string("./tests/formats/hash_cards.csv")
+            .expect("Unable to read CSV deck");
+        let spans = DeckFormat::Csv.spans(&source);
+        assert_eq!(spans.cards.len(), 3);
+        assert_eq!(&source[spans.cards[0].clone()], "#,Hash");
+    }
+
+    #[test]
+    fn load_deck_from_markdown() {
+        let deck = load_decks(vec!["./tests/formats/vocab.md"])
+            .expect("Unable to load Markdown deck")
+            .remove(0);
+        assert_eq!(deck.name, "Markdown Deck");
+        assert_eq!(deck.faces, vec!["Kanji", "Hiragana", "English"]);
+        assert_eq!(deck.cards.len(), 4);
+        assert_eq!(
+            deck[0][2],
+            Some(Face::Multi(vec!["Water".to_owned(), "Liquid".to_owned()]))
+        );
+        assert_eq!(deck[1][0], None);
+        assert_eq!(deck[1][1], Some(Face::Single("ひ".to_owned())));
+        assert_eq!(deck[2][2], Some(Face::Single("Tree ; Wood".to_owned())));
+
+        let err = load_decks(vec!["./tests/formats/invalid_card.md"])
+            .expect_err("Loaded invalid Markdown deck");
+        let DeckError::InvalidCard(source, CardError::NotEnoughFaces(..)) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(source.location, Some(SourceLocation::new(5, 3)));
+
+        let err = load_decks(vec!["./tests/formats/malformed.md"])
+            .expect_err("Loaded malformed Markdown deck");
+        let DeckError::SerdeError(source, _) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(source.location, Some(SourceLocation::new(5, 3)));
+    }
+
+    #[test]
+    fn metadata_round_trips() {
+        let path = Path::new("./tests/metadata.json");
+        let deck = load_decks(vec![path])
+            .expect("Unable to load deck with metadata")
+            .remove(0);
+        let metadata = &deck.metadata;
+        assert_eq!(metadata.version.as_deref(), Some("1.2"));
+        assert_eq!(metadata.language("Kanji"), Some("ja"));
+        assert_eq!(metadata.language("Romaji"), None);
+        assert_eq!(
+            metadata.unknown.keys().collect::<Vec<_>>(),
+            vec!["x-difficulty", "notes"]
+        );
+
+        let json = deck.to_json();
+        assert!(json.contains("\n    \"x-difficulty\": {\"level\": \"N5\", \"order\": [2, 1]},\n"));
+        let reloaded = parse_deck(path, &json).expect("Unable to reload deck");
+        assert_eq!(reloaded.metadata, deck.metadata);
+        assert_eq!(reloaded.cards, deck.cards);
+    }
+
+    #[test]
+    fn load_deck_with_tags() {
+        let path = Path::new("./tests/tags.json");
+        let deck = load_decks(vec![path])
+            .expect("Unable to load deck with tags")
+            .remove(0);
+        assert_eq!(deck.metadata.tags, vec!["JLPT N5"]);
+        assert_eq!(deck.cards[0].tags(), ["nature"]);
+        assert_eq!(deck.cards[1].tags(), ["nature"]);
+        assert!(deck.cards[3].tags().is_empty());
+        assert_eq!(deck.cards[1].len(), 3);
+        assert_eq!(deck.tags_of(&deck.cards[2]), vec!["JLPT N5", "verb"]);
+
+        let json = deck.to_json();
+        assert!(
+            json.contains("\n        [\"水\", \"みず\", \"Water\", {\"tags\": [\"nature\"]}],\n")
+        );
+        assert!(json.contains("\"English\": \"Fire\", \"tags\": [\"nature\"]},\n"));
+        let reloaded = parse_deck(path, &json).expect("Unable to reload deck");
+        assert_eq!(reloaded.metadata, deck.metadata);
+        for (reloaded, card) in reloaded.cards.iter().zip(deck.cards.iter()) {
+            assert_eq!(reloaded.tags(), card.tags());
+        }
+    }
+
+    #[test]
+    fn load_deck_with_info_faces() {
+        let path = Path::new("./tests/info_faces.json");
+        let deck = load_decks(vec![path])
+            .expect("Unable to load deck with info faces")
+            .remove(0);
+        assert_eq!(deck.metadata.info_faces, vec!["Example", "Notes"]);
+        assert!(deck.is_info_face("Notes"));
+        assert!(!deck.is_info_face("Kanji"));
+        assert!(deck
+            .to_json()
+            .contains("\"info_faces\": [\"Example\", \"Notes\"],\n"));
+
+        //NOTE: Info faces don't count towards the faces needed to quiz a card
+        let json = r#"{"name": "Info", "info_faces": ["Notes"], "faces": ["Front", "Back", "Notes"],
+            "cards": [["Front", null, "Notes"]]}"#;
+        assert!(parse_deck(path, json).is_err_and(|err| matches!(
+            err,
+            DeckError::InvalidCard(_, CardError::NotEnoughUsableFaces(_, 1))
+        )));
+
+        let json = r#"{"name": "Info", "info_faces": ["Back"], "faces": ["Front", "Back"],
+            "cards": []}"#;
+        assert!(
+            parse_deck(path, json).is_err_and(|err| matches!(err, DeckError::NotEnoughFaces(_, 1)))
+        );
+    }
+
+    #[test]
+    fn load_deck_with_hints() {
+        let path = Path::new("./tests/hints.json");
+        let deck = load_decks(vec![path])
+            .expect("Unable to load deck with hints")
+            .remove(0);
+        assert_eq!(deck.cards[0].hints(), ["Has the water radical"]);
+        assert_eq!(deck.cards[1].hints(), ["Burns", "fire"]);
+        assert_eq!(deck.cards[2].hints(), ["Something done at meals", "Verb"]);
+        assert_eq!(deck.cards[2].tags(), ["verb"]);
+        assert!(deck.cards[3].hints().is_empty());
+        assert_eq!(deck.cards[1].len(), 3);
+
+        let json = deck.to_json();
+        assert!(json
+            .contains("[\"水\", \"みず\", \"Water\", {\"hint\": \"Has the water radical\"}],\n"));
+        assert!(json.contains("\"English\": \"Fire\", \"hint\": [\"Burns\", \"fire\"]},\n"));
+        let reloaded = parse_deck(path, &json).expect("Unable to reload deck");
+        for (reloaded, card) in reloaded.cards.iter().zip(deck.cards.iter()) {
+            assert_eq!(reloaded.hints(), card.hints());
+            assert_eq!(reloaded.tags(), card.tags());
+        }
+    }
+
+    #[test]
+    fn load_deck_tags_not_last() {
+        let json = r#"{"name": "Tags", "faces": ["Front", "Back"], "cards": [
+            ["Front", {"tags": ["a"]}, "Back"]
+        ]}"#;
+        assert!(serde_json::from_str::<Deck>(json).is_err());
+
+        let json = r#"{"name": "Tags", "faces": ["Front", "Back"], "cards": [
+            ["Front", "Back", {"tag": ["a"]}]
+        ]}"#;
+        assert!(serde_json::from_str::<Deck>(json).is_err());
+    }
+
+    #[test]
+    fn face_is_match() {
+        let single = Face::Single("Japan".to_owned());
+        assert!(single.is_match(" japan ", None));
+        assert!(!single.is_match("Japa", None));
+
+        let multi = Face::Multi(vec!["No".to_owned(), "Don't mention it".to_owned()]);
+        assert!(multi.is_match("no", None));
+        assert!(multi.is_match("No, Don't mention it", None));
+        assert!(!multi.is_match("Yes", None));
+
+        let kana = Face::Multi(vec!["みず".to_owned(), "すい".to_owned()]);
+        assert!(kana.is_match("mizu", Some("ja")));
+        assert!(kana.is_match("SUI", Some("ja")));
+        assert!(!kana.is_match("mizu", Some("en")));
+
+        //NOTE: Styles are ignored, both when typing and comparing faces
+        let styled = Face::Single("To **eat** [red]{食|た}べる[/]".to_owned());
+        assert!(styled.is_match("to eat 食べる", None));
+        assert!(styled.is_match("To eat たべる", None));
+        assert_eq!(styled, Face::Single("To eat 食べる".to_owned()));
+        assert_eq!(styled.plain(), "To eat 食べる");
+    }
+
+    #[test]
+    fn faces_with_furigana() {
+        let deck = load_decks(vec!["./tests/furigana.json"])
+            .expect("Unable to load deck with furigana")
+            .remove(0);
+        let japan = deck[0][0].as_ref().expect("Card has no front");
+
+        //NOTE: Readings are ignored when comparing faces, but not by card ids, so that
+        //cards written with braces before readings were supported keep their stats
+        assert_eq!(japan, &Face::Single("日本".to_owned()));
+        assert_eq!(japan.plain(), "日本");
+        assert_eq!(japan.join(), "{日本|にほん}");
+        assert_eq!(*CardId::get(&deck, &deck[0]), "Furigana:{日本|にほん}");
+        assert!(japan.is_match("日本", Some("ja")));
+        assert!(japan.is_match("nihon", Some("ja")));
+        assert!(!japan.is_match("{日本|にほん}", Some("ja")));
+
+        let eat = deck[2][0].as_ref().expect("Card has no front");
+        assert!(eat.has_ruby() && eat.contains("食べる") && !eat.contains("|"));
+
+        let json = r#"{"name": "Furigana", "faces": ["Front", "Back"],
+            "cards": [["{日本|にほん}", "Japan"], ["日本", "Japan"]]}"#;
+        assert!(
+            parse_deck(Path::new("furigana.json"), json).is_err_and(|err| matches!(
+                err,
+                DeckError::InvalidCard(_, CardError::DuplicateFront(_))
+            ))
+        );
+    }
+
+    #[test]
+    fn styled_fronts_are_duplicates_but_keep_ids() {
+        let json = r#"{"name": "Styled", "faces": ["Front", "Back"],
+            "cards": [["**dog**", "Hund"], ["[red]cat[/]", "Katze"]]}"#;
+        let deck = parse_deck(Path::new("styled.json"), json).expect("Unable to parse deck");
+        assert_eq!(*CardId::get(&deck, &deck[0]), "Styled:**dog**");
+        assert_eq!(*CardId::get(&deck, &deck[1]), "Styled:[red]cat[/]");
+
+        let json = r#"{"name": "Styled", "faces": ["Front", "Back"],
+            "cards": [["**dog**", "Hund"], ["dog", "Hund"]]}"#;
+        assert!(
+            parse_deck(Path::new("styled.json"), json).is_err_and(|err| matches!(
+                err,
+                DeckError::InvalidCard(_, CardError::DuplicateFront(_))
+            ))
+        );
+    }
+
+    #[test]
+    fn faces_with_clozes() {
+        //NOTE: Clozes are quizzed on their own, so a single quizzed face is enough
+        let deck = load_decks(vec!["./tests/cloze.json"])
+            .expect("Unable to load deck with clozes")
+            .remove(0);
+        let cat = &deck[0];
+        let sentence = cat[0].as_ref().expect("Card has no sentence");
+
+        assert_eq!(deck.cloze_faces(cat), [0]);
+        assert_eq!(sentence.plain(), "The cat sat on the mat.");
+        assert!(sentence.is_match("The cat sat on the mat.", None));
+
+        let id = CardId::get(&deck, cat);
+        let ids = CardId::with_clozes(&deck, cat);
+        assert_eq!(*ids[1], format!("{}::c1", *id));
+        assert_eq!(ids[2].cloze_of(&id), Some(2));
+        assert_eq!(id.cloze_of(&id), None);
+        assert_eq!(ids.len(), 3);
+
+        //NOTE: Both deletions of the birds card are part of the same cloze
+        assert_eq!(CardId::with_clozes(&deck, &deck[2]).len(), 2);
+
+        let json = r#"{"name": "Cloze", "faces": ["Sentence"], "cards": [["No clozes"]]}"#;
+        assert!(parse_deck(Path::new("cloze.json"), json).is_err());
+    }
+
     #[test]
     fn load_decks_from_files() {
         let decks = load_decks(vec![
@@ -578,7 +2380,7 @@ mod tests {
     #[test]
     fn load_decks_duplicate_deck_names() {
         assert!(load_decks(vec!["./tests/duplicate_deck_names"])
-            .is_err_and(|err| matches!(err, DeckError::DuplicateDeckNames(_))))
+            .is_err_and(|err| matches!(err, DeckError::DuplicateDeckNames(..))))
     }
 
     #[test]
@@ -608,6 +2410,66 @@ mod tests {
         assert_eq!(decks.len(), 2);
     }
 
+    #[test]
+    fn load_decks_to_max_depth() {
+        let names = |max_depth| {
+            let search = DeckSearch {
+                max_depth,
+                follow_symlinks: true,
+            };
+            load_decks_with(vec!["./tests/nested"], &search)
+                .expect("Unable to load nested decks")
+                .into_iter()
+                .map(|deck| deck.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(Some(0)), vec!["Top"]);
+        assert_eq!(names(Some(1)), vec!["Sub", "Top"]);
+        assert_eq!(names(None), vec!["Deeper", "Sub", "Top"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn load_decks_through_symlinks() {
+        let dir = std::env::temp_dir().join(format!("flashr-symlinks-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Unable to create test directory");
+        let nested = Path::new("./tests/nested/sub")
+            .canonicalize()
+            .expect("Unable to find nested decks");
+        std::os::unix::fs::symlink(nested, dir.join("linked")).expect("Unable to link decks");
+        //NOTE: Links back to the directory itself, which must not be searched forever
+        std::os::unix::fs::symlink(&dir, dir.join("cycle")).expect("Unable to link directory");
+
+        let count = |follow_symlinks| {
+            let search = DeckSearch {
+                max_depth: None,
+                follow_symlinks,
+            };
+            load_decks_with(vec![&dir], &search).map(|decks| decks.len())
+        };
+        let followed = count(true);
+        let unfollowed = count(false);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(followed.expect("Unable to load linked decks"), 2);
+        assert_eq!(unfollowed.expect("Unable to load decks"), 0);
+    }
+
+    #[test]
+    fn load_decks_from_glob() {
+        let decks = load_decks(vec!["./tests/dir/deck*.json", "./tests/nested/*/deeper"])
+            .expect("Unable to load decks from glob");
+        assert_eq!(decks.len(), 3);
+        assert_eq!(decks[2].name, "Deeper");
+
+        assert!(matches!(
+            load_decks(vec!["./tests/dir/*.yaml"]),
+            Err(DeckError::NoGlobMatches(_))
+        ));
+    }
+
     #[test]
     fn load_decks_with_subfaces() {
         let decks = load_decks(vec!["./tests/deck_subfaces.json"])
@@ -631,7 +2493,7 @@ mod tests {
     #[test]
     fn load_deck_not_enough_faces() {
         assert!(load_decks(vec!["./tests/not_enough_faces.json"])
-            .is_err_and(|err| matches!(err, DeckError::NotEnoughFaces(_))));
+            .is_err_and(|err| matches!(err, DeckError::NotEnoughFaces(..))));
     }
 
     #[test]
@@ -650,6 +2512,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn invalid_card_error_has_source() {
+        let err = load_decks(vec!["./tests/not_enough_card_faces.json"])
+            .expect_err("Loaded deck with not enough card faces");
+
+        let DeckError::InvalidCard(source, _) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(source.card, Some(1));
+        assert_eq!(source.location, Some(SourceLocation::new(9, 9)));
+        assert_eq!(
+            source.snippet,
+            vec![(9, r#"        ["Front, no back"]"#.to_owned())]
+        );
+        assert!(err
+            .to_string()
+            .contains("--> ./tests/not_enough_card_faces.json:9:9"));
+    }
+
+    #[test]
+    fn error_marks_first_line_of_multiline_card() {
+        let json = "{\n  \"name\": \"D\",\n  \"faces\": [\"A\", \"B\", \"C\"],\n  \"cards\": [\n    [\"x\",\n     \"z\"]\n  ]\n}";
+        let err = parse_deck(Path::new("d.json"), json).expect_err("Loaded invalid card");
+
+        let DeckError::InvalidCard(source, _) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(source.location, Some(SourceLocation::new(5, 5)));
+        assert!(err
+            .to_string()
+            .contains("5 |     [\"x\",\n  |     ^\n6 |      \"z\"]"));
+        assert_eq!(err.to_string().matches('^').count(), 1);
+    }
+
+    #[test]
+    fn duplicate_deck_names_error_has_paths() {
+        let err = load_decks(vec!["./tests/duplicate_deck_names"])
+            .expect_err("Loaded decks with duplicate names");
+
+        let DeckError::DuplicateDeckNames(_, path_a, path_b) = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert!(path_a.ends_with("duplicate_deckname_1.json"));
+        assert!(path_b.ends_with("duplicate_deckname_2.json"));
+    }
+
     #[test]
     fn load_deck_too_many_card_faces() {
         assert!(
@@ -666,7 +2574,7 @@ mod tests {
             load_decks(vec!["./tests/not_enough_usable_card_faces.json"]).is_err_and(
                 |err| matches!(
                     err,
-                    DeckError::InvalidCard(_, CardError::NotEnoughUsableFaces(_))
+                    DeckError::InvalidCard(_, CardError::NotEnoughUsableFaces(..))
                 )
             )
         );
@@ -17,7 +17,7 @@
  * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crossterm::event::{self, Event};
 
@@ -28,7 +28,34 @@ pub fn clear_and_match_event<T>(match_fn: impl Fn(Event) -> Option<T>) -> Result
     match_user_input(match_fn)
 }
 
-fn clear_event_loop() -> Result<(), FlashrError> {
+///Matches the next event without clearing those already queued, so that
+///fast typing isn't dropped
+pub fn match_event<T>(match_fn: impl Fn(Event) -> Option<T>) -> Result<T, FlashrError> {
+    match_user_input(match_fn)
+}
+
+///Matches the next event without clearing those already queued, unless none matches
+///within the timeout
+pub fn match_event_within<T>(
+    match_fn: impl Fn(Event) -> Option<T>,
+    timeout: Duration,
+) -> Result<Option<T>, FlashrError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break Ok(None);
+        }
+        if event::poll(remaining).map_err(UiError::IoError)? {
+            let event = event::read().map_err(UiError::IoError)?;
+            if let Some(value) = match_fn(event) {
+                break Ok(Some(value));
+            }
+        }
+    }
+}
+
+pub fn clear_event_loop() -> Result<(), FlashrError> {
     loop {
         if event::poll(Duration::from_millis(0)).map_err(UiError::IoError)? {
             event::read().map_err(UiError::IoError)?;
@@ -0,0 +1,617 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use csv::WriterBuilder;
+use ratatui::style::Color;
+
+use crate::{
+    cloze,
+    deck::{Card, CardId, Deck, Face},
+    rich::{self, Piece},
+    ruby,
+    stats::{CardStats, Stats, Strength},
+};
+
+///Subface separator of exported CSV decks, matching the default separator when loading them
+const CSV_SEPARATOR: &str = " | ";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Anki,
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Anki => "txt",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+
+    ///Whether a single file can hold more than one deck
+    fn is_multi_deck(&self) -> bool {
+        matches!(self, Self::Markdown | Self::Html)
+    }
+}
+
+impl FromStr for ExportFormat {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "anki" => Ok(Self::Anki),
+            "csv" => Ok(Self::Csv),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            _ => Err(format!("Export format not recognized: {s}")),
+        }
+    }
+
+    type Err = String;
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Anki => "anki",
+            Self::Csv => "csv",
+            Self::Markdown => "markdown",
+            Self::Html => "html",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    IoError(PathBuf, std::io::Error),
+    MultipleDecks(ExportFormat, usize),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(path, err) => f.write_fmt(format_args!(
+                "IoError: {err}, path: {}",
+                path.to_str().unwrap_or("unknown")
+            )),
+            Self::MultipleDecks(format, count) => f.write_fmt(format_args!(
+                "MultipleDecks: {format} exports hold a single deck, but {count} were given. Use --out-dir to write a file for each"
+            )),
+        }
+    }
+}
+
+fn stats_tag(stats: Option<&CardStats>) -> String {
+    format!("flashr::{}", Strength::of(stats))
+}
+
+///Tags of the card as exported, followed by its stats tag if given stats. Includes the
+///deck's tags too, unless `own_only` for formats which give the deck's tags separately.
+fn card_tags(deck: &Deck, card: &Card, stats: Option<&Stats>, own_only: bool) -> Vec<String> {
+    let mut tags = match own_only {
+        true => card.tags().to_vec(),
+        false => deck.tags_of(card).into_iter().cloned().collect(),
+    };
+    if let Some(stats) = stats {
+        tags.push(stats_tag(
+            stats.total(&CardId::with_clozes(deck, card)).as_ref(),
+        ));
+    }
+    tags
+}
+
+fn has_tags(deck: &Deck, stats: Option<&Stats>, own_only: bool) -> bool {
+    stats.is_some()
+        || deck
+            .iter()
+            .any(|card| !card_tags(deck, card, None, own_only).is_empty())
+}
+
+///Rows of the deck's cards, with each face joined into a single string, and the
+///tags of each card as an extra column if `tags` is given
+fn deck_rows(
+    deck: &Deck,
+    join: impl Fn(&Face) -> String,
+    tags: Option<impl Fn(&Card) -> String>,
+) -> Vec<Vec<String>> {
+    deck.iter()
+        .map(|card| {
+            let mut row = (0..deck.faces.len())
+                .map(|i| {
+                    card.get(i)
+                        .and_then(Option::as_ref)
+                        .map(&join)
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+            if let Some(tags) = tags.as_ref() {
+                row.push(tags(card));
+            }
+            row
+        })
+        .collect()
+}
+
+fn header(deck: &Deck, has_tags: bool) -> Vec<String> {
+    let mut header = deck.faces.clone();
+    if has_tags {
+        header.push("Tags".to_owned());
+    }
+    header
+}
+
+///Tags column of the formats which are only read by people, with tags separated by commas
+fn readable_tags<'a>(
+    deck: &'a Deck,
+    stats: Option<&'a Stats>,
+) -> Option<impl Fn(&Card) -> String + 'a> {
+    has_tags(deck, stats, false)
+        .then_some(move |card: &Card| card_tags(deck, card, stats, false).join(", "))
+}
+
+///Fails for formats holding a single deck if given more than one
+pub fn export_decks(
+    decks: &[Deck],
+    format: ExportFormat,
+    stats: Option<&Stats>,
+) -> Result<String, ExportError> {
+    if decks.len() > 1 && !format.is_multi_deck() {
+        return Err(ExportError::MultipleDecks(format, decks.len()));
+    }
+
+    Ok(match format {
+        ExportFormat::Anki => decks.iter().map(|deck| anki_tsv(deck, stats)).collect(),
+        ExportFormat::Csv => decks.iter().map(|deck| csv(deck, stats)).collect(),
+        ExportFormat::Markdown => decks
+            .iter()
+            .map(|deck| markdown(deck, stats))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ExportFormat::Html => html(decks, stats),
+    })
+}
+
+///Exports each deck to its own file in the directory, returning the paths written
+pub fn export_to_dir(
+    decks: &[Deck],
+    format: ExportFormat,
+    stats: Option<&Stats>,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, ExportError> {
+    std::fs::create_dir_all(dir).map_err(|err| ExportError::IoError(dir.to_path_buf(), err))?;
+
+    decks
+        .iter()
+        .map(|deck| {
+            let path = dir.join(format!("{}.{}", deck.file_stem(), format.extension()));
+            let export = export_decks(std::slice::from_ref(deck), format, stats)?;
+            std::fs::write(&path, export).map_err(|err| ExportError::IoError(path.clone(), err))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+fn write_delimited(rows: &[Vec<String>], delimiter: u8) -> String {
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_writer(vec![]);
+    for row in rows {
+        writer
+            .write_record(row)
+            .expect("Unable to write record to memory");
+    }
+    let bytes = writer
+        .into_inner()
+        .expect("Unable to flush records to memory");
+    String::from_utf8(bytes).expect("Written records are not UTF-8")
+}
+
+///Anki's text import format, with the deck and columns given as file headers. Anki tags
+///can't contain spaces, so they're replaced by underscores, and readings are written in
+///Anki's furigana format, eg. " 日本[にほん]". Styles are left out, as fields are plain text,
+///while clozes are kept as they are, being written the same way in Anki.
+fn anki_tsv(deck: &Deck, stats: Option<&Stats>) -> String {
+    let has_tags = has_tags(deck, stats, false);
+    let header = header(deck, has_tags);
+    let single_line = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let tags = |card: &Card| {
+        card_tags(deck, card, stats, false)
+            .iter()
+            .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join("_"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let rows = deck_rows(
+        deck,
+        |face| {
+            single_line(&ruby::replace(
+                &rich::strip(&face.join()),
+                |base, reading| format!(" {base}[{reading}]"),
+            ))
+        },
+        has_tags.then_some(tags),
+    );
+
+    let mut tsv = format!(
+        "#separator:tab\n#html:false\n#deck:{}\n#columns:{}\n",
+        single_line(&deck.name),
+        header.join("\t")
+    );
+    if has_tags {
+        tsv.push_str(&format!("#tags column:{}\n", header.len()));
+    }
+    tsv.push_str(&write_delimited(&rows, b'\t'));
+    tsv
+}
+
+///CSV with the same directives and subface separator as loaded CSV decks
+fn csv(deck: &Deck, stats: Option<&Stats>) -> String {
+    let has_tags = has_tags(deck, stats, true);
+    let tags = |card: &Card| card_tags(deck, card, stats, true).join(CSV_SEPARATOR);
+    let rows = deck_rows(
+        deck,
+        |face| match face {
+            Face::Single(face) => face.clone(),
+            Face::Multi(faces) => faces.join(CSV_SEPARATOR),
+        },
+        has_tags.then_some(tags),
+    );
+
+    let mut csv = format!("#name: {}\n", deck.name);
+    if !deck.metadata.tags.is_empty() {
+        csv.push_str(&format!(
+            "#tags: {}\n",
+            deck.metadata.tags.join(CSV_SEPARATOR)
+        ));
+    }
+    if has_tags {
+        csv.push_str(&format!("#tags column: {}\n", deck.faces.len() + 1));
+    }
+    csv.push_str(&write_delimited(&[header(deck, has_tags)], b','));
+    csv.push_str(&write_delimited(&rows, b','));
+    csv
+}
+
+fn markdown(deck: &Deck, stats: Option<&Stats>) -> String {
+    let cell = |text: &str| {
+        text.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace('|', "\\|")
+    };
+    let row = |cells: &[String]| {
+        format!(
+            "| {} |\n",
+            cells
+                .iter()
+                .map(|text| cell(text))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        )
+    };
+    let tags = readable_tags(deck, stats);
+    let header = header(deck, tags.is_some());
+
+    let mut markdown = format!("# {}\n\n", cell(&deck.name));
+    markdown.push_str(&row(&header));
+    markdown.push_str(&row(&vec!["---".to_owned(); header.len()]));
+    //NOTE: Colors are left out, as Markdown can't show them
+    let join = |face: &Face| {
+        let face = cloze::reveal(&face.join()).into_owned();
+        let styled = rich::replace(&face, |piece| {
+            let bold = if piece.bold { "**" } else { "" };
+            let italic = if piece.italic { "_" } else { "" };
+            format!("{bold}{italic}{}{italic}{bold}", piece.text)
+        });
+        ruby::inline(&styled).into_owned()
+    };
+    for cells in deck_rows(deck, join, tags) {
+        markdown.push_str(&row(&cells));
+    }
+    markdown
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+///Piece of styled text as escaped HTML
+fn html_piece(piece: &Piece) -> String {
+    let mut html = escape_html(piece.text);
+    if piece.italic {
+        html = format!("<i>{html}</i>");
+    }
+    if piece.bold {
+        html = format!("<b>{html}</b>");
+    }
+    match piece.color {
+        Some(Color::Rgb(r, g, b)) => {
+            format!("<span style=\"color: #{r:02x}{g:02x}{b:02x}\">{html}</span>")
+        }
+        Some(color) => format!(
+            "<span style=\"color: {}\">{html}</span>",
+            color.to_string().to_lowercase()
+        ),
+        None => html,
+    }
+}
+
+const HTML_STYLE: &str = r#"body { font-family: sans-serif; }
+h1 { break-before: page; }
+h1:first-of-type { break-before: avoid; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }
+thead { display: table-header-group; }
+tr { break-inside: avoid; }
+th, td { border: 1px solid #444; padding: 0.4em; text-align: left; vertical-align: top; }
+td.tags { color: #666; font-size: 0.8em; }"#;
+
+///Printable sheet with a table for each deck, starting each deck on a new page
+fn html(decks: &[Deck], stats: Option<&Stats>) -> String {
+    let title = match decks {
+        [deck] => deck.name.as_str(),
+        _ => "Flashr decks",
+    };
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n",
+        escape_html(title)
+    );
+    for deck in decks {
+        let face_count = deck.faces.len();
+        html.push_str(&format!(
+            "<h1>{}</h1>\n<table>\n<thead>\n<tr>",
+            escape_html(&deck.name)
+        ));
+        let tags = readable_tags(deck, stats);
+        for face in header(deck, tags.is_some()) {
+            html.push_str(&format!("<th>{}</th>", escape_html(&face)));
+        }
+        html.push_str("</tr>\n</thead>\n<tbody>\n");
+        let join = |face: &Face| cloze::reveal(&face.join()).into_owned();
+        for cells in deck_rows(deck, join, tags) {
+            html.push_str("<tr>");
+            for (i, cell) in cells.iter().enumerate() {
+                let class = match i >= face_count {
+                    true => " class=\"tags\"",
+                    false => "",
+                };
+                let cell = cell
+                    .split('\n')
+                    .map(|line| {
+                        rich::pieces(line)
+                            .iter()
+                            .map(html_piece)
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let cell = ruby::replace(&cell, |base, reading| {
+                    format!("<ruby>{base}<rt>{reading}</rt></ruby>")
+                })
+                .into_owned();
+                html.push_str(&format!("<td{class}>{cell}</td>"));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        deck::{load_decks, parse_deck, CardId, Deck},
+        stats::Stats,
+    };
+
+    use super::{export_decks, ExportError, ExportFormat};
+
+    fn example_deck() -> Deck {
+        load_decks(vec!["./tests/example.json"])
+            .expect("Unable to load test deck")
+            .remove(0)
+    }
+
+    #[test]
+    fn exports_anki_with_stats_tags() {
+        let deck = example_deck();
+        let mut stats = Stats::new("./tests/export_stats.json");
+        for (card, (correct, incorrect)) in deck.iter().zip([(1, 2), (2, 1), (5, 1), (0, 0)]) {
+            let card_stats = stats.for_card_mut(CardId::get(&deck, card));
+            card_stats.correct = correct;
+            card_stats.incorrect = incorrect;
+        }
+
+        let tsv = export_decks(
+            std::slice::from_ref(&deck),
+            ExportFormat::Anki,
+            Some(&stats),
+        )
+        .expect("Unable to export deck");
+        let lines = tsv.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[2], "#deck:Example");
+        assert_eq!(lines[3], "#columns:Front\tMiddle\tBack\tTags");
+        assert_eq!(lines[4], "#tags column:4");
+        assert_eq!(lines[5], "Front 1\tMiddle 1\tBack 1\tflashr::weak");
+        assert!(lines[6].ends_with("\tflashr::learning"));
+        assert!(lines[7].starts_with("\tMiddle 3\t"));
+        assert!(lines[7].ends_with("\tflashr::strong"));
+        assert!(lines[8].ends_with("\tflashr::new"));
+        assert!(lines[9].ends_with("\tflashr::new"));
+    }
+
+    #[test]
+    fn exported_csv_loads_as_same_deck() {
+        let deck = example_deck();
+        let csv = export_decks(std::slice::from_ref(&deck), ExportFormat::Csv, None)
+            .expect("Unable to export deck");
+
+        let loaded = parse_deck(Path::new("example.csv"), &csv).expect("Unable to load CSV");
+        assert_eq!(loaded.name, deck.name);
+        assert_eq!(loaded.faces, deck.faces);
+        //NOTE: Single subfaces are loaded as plain faces
+        assert_eq!(loaded.cards[..3], deck.cards[..3]);
+        assert_eq!(loaded.cards[4], deck.cards[4]);
+
+        //NOTE: Cards starting with "#" aren't taken for comments when loaded again
+        let mut deck = example_deck();
+        deck.cards = vec![crate::deck::Card::new(vec![Some("#"), Some("Hash"), None])];
+        let csv = export_decks(std::slice::from_ref(&deck), ExportFormat::Csv, None)
+            .expect("Unable to export deck");
+        let loaded = parse_deck(Path::new("example.csv"), &csv).expect("Unable to load CSV");
+        assert_eq!(loaded.cards, deck.cards);
+    }
+
+    #[test]
+    fn exports_card_and_deck_tags() {
+        let path = Path::new("./tests/tags.json");
+        let deck = load_decks(vec![path])
+            .expect("Unable to load test deck")
+            .remove(0);
+        let decks = std::slice::from_ref(&deck);
+
+        let tsv = export_decks(decks, ExportFormat::Anki, None).expect("Unable to export deck");
+        let lines = tsv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[4], "#tags column:4");
+        assert_eq!(lines[5], "水\tみず\tWater\tJLPT_N5 nature");
+        assert_eq!(lines[8], "日本\tにほん\tJapan\tJLPT_N5");
+
+        let csv = export_decks(decks, ExportFormat::Csv, None).expect("Unable to export deck");
+        let loaded = parse_deck(Path::new("tags.csv"), &csv).expect("Unable to load CSV");
+        assert_eq!(loaded.faces, deck.faces);
+        assert_eq!(loaded.metadata.tags, deck.metadata.tags);
+        for (loaded, card) in loaded.cards.iter().zip(deck.cards.iter()) {
+            assert_eq!(loaded, card);
+            assert_eq!(loaded.tags(), card.tags());
+        }
+
+        let markdown = export_decks(decks, ExportFormat::Markdown, None).expect("Unable to export");
+        assert!(markdown.contains("| 食べる | たべる | To eat | JLPT N5, verb |"));
+    }
+
+    #[test]
+    fn escapes_markdown_and_html() {
+        let deck = Deck {
+            name: "Symbols".to_owned(),
+            metadata: Default::default(),
+            faces: vec!["Symbol".to_owned(), "Meaning".to_owned()],
+            cards: vec![crate::deck::Card::new(vec![Some("|"), Some("<or>")])],
+        };
+        let decks = std::slice::from_ref(&deck);
+
+        let markdown = export_decks(decks, ExportFormat::Markdown, None).expect("Unable to export");
+        assert!(markdown.contains("| \\| | <or> |"));
+
+        let html = export_decks(decks, ExportFormat::Html, None).expect("Unable to export");
+        assert!(html.contains("<td>|</td><td>&lt;or&gt;</td>"));
+    }
+
+    #[test]
+    fn exports_furigana() {
+        let deck = load_decks(vec!["./tests/furigana.json"])
+            .expect("Unable to load test deck")
+            .remove(0);
+        let decks = std::slice::from_ref(&deck);
+
+        let anki = export_decks(decks, ExportFormat::Anki, None).expect("Unable to export");
+        assert!(anki.contains("日本[にほん]\tにほん\tJapan\n"));
+        assert!(anki.contains("食[た]べる, 食[く]う\t"));
+
+        let markdown = export_decks(decks, ExportFormat::Markdown, None).expect("Unable to export");
+        assert!(markdown.contains("| 日本(にほん) | にほん | Japan |"));
+
+        let html = export_decks(decks, ExportFormat::Html, None).expect("Unable to export");
+        assert!(html.contains("<td><ruby>日本<rt>にほん</rt></ruby></td>"));
+
+        let csv = export_decks(decks, ExportFormat::Csv, None).expect("Unable to export");
+        assert!(csv.contains("{日本|にほん},にほん,Japan"));
+    }
+
+    #[test]
+    fn exports_clozes() {
+        let deck = load_decks(vec!["./tests/cloze.json"])
+            .expect("Unable to load test deck")
+            .remove(0);
+        let decks = std::slice::from_ref(&deck);
+
+        //NOTE: Anki writes clozes the same way, so they're kept as they are
+        let anki = export_decks(decks, ExportFormat::Anki, None).expect("Unable to export");
+        assert!(anki.contains("The {{c1::cat}} sat on the {{c2::mat::floor covering}}.\t"));
+
+        let markdown = export_decks(decks, ExportFormat::Markdown, None).expect("Unable to export");
+        assert!(markdown.contains("| The cat sat on the mat. |"));
+
+        let html = export_decks(decks, ExportFormat::Html, None).expect("Unable to export");
+        assert!(html.contains("<td>I drink <ruby>水<rt>みず</rt></ruby> every day.</td>"));
+    }
+
+    #[test]
+    fn exports_styled_text() {
+        let deck = Deck {
+            name: "Styled".to_owned(),
+            metadata: Default::default(),
+            faces: vec!["Word".to_owned(), "Example".to_owned()],
+            cards: vec![crate::deck::Card::new(vec![
+                Some("食べる"),
+                Some("**{食|た}べる** _<now>_ [red]please[/]"),
+            ])],
+        };
+        let decks = std::slice::from_ref(&deck);
+
+        let anki = export_decks(decks, ExportFormat::Anki, None).expect("Unable to export");
+        assert!(anki.contains("\t食[た]べる <now> please\n"));
+
+        let markdown = export_decks(decks, ExportFormat::Markdown, None).expect("Unable to export");
+        assert!(markdown.contains("| **食(た)べる** _<now>_ please |"));
+
+        let html = export_decks(decks, ExportFormat::Html, None).expect("Unable to export");
+        assert!(html.contains("<td><b><ruby>食<rt>た</rt></ruby>べる</b> <i>&lt;now&gt;</i> <span style=\"color: red\">please</span></td>"));
+    }
+
+    #[test]
+    fn single_deck_formats_fail_on_many_decks() {
+        let decks = load_decks(vec!["./tests/deck1.json", "./tests/example.json"])
+            .expect("Unable to load test decks");
+
+        assert!(matches!(
+            export_decks(&decks, ExportFormat::Anki, None),
+            Err(ExportError::MultipleDecks(ExportFormat::Anki, 2))
+        ));
+        let html = export_decks(&decks, ExportFormat::Html, None).expect("Unable to export");
+        assert_eq!(html.matches("<h1>").count(), 2);
+    }
+}
@@ -0,0 +1,419 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    deck::{find_deck_files, Deck, DeckError, DeckFormat, Face},
+    location::{entry_value_span, json_deck_spans},
+    rewrite::{apply_edits, DeckRewrite, Edit},
+};
+
+///Faces of a card as they would be after being fixed, used to find identical cards
+type FixedFaces = Vec<Option<Face>>;
+
+///Finds fixes for every JSON deck file in the given paths, without writing them.
+///Files without anything to fix are omitted.
+pub fn fix_paths<P: Into<PathBuf>>(
+    paths: impl IntoIterator<Item = P>,
+) -> Result<Vec<DeckRewrite>, DeckError> {
+    let mut fixes = vec![];
+    let mut seen_cards: Vec<(FixedFaces, PathBuf, usize)> = vec![];
+
+    for path in find_deck_files(paths)?
+        .into_iter()
+        .filter(|path| DeckFormat::from_path(path) == Some(DeckFormat::Json))
+    {
+        let source =
+            fs::read_to_string(&path).map_err(|err| DeckError::IoError(path.clone(), err))?;
+        let mut edits = syntax_edits(&source);
+
+        //NOTE: Cards can only be fixed once the deck can be parsed, so the syntax
+        //edits are applied first, and the card edits mapped back onto the source.
+        let stripped = apply_edits(&source, &edits, 0..source.len());
+        if let Ok(deck) = serde_json::from_str::<Deck>(&stripped) {
+            let deletions = edits
+                .iter()
+                .map(|edit| edit.range.clone())
+                .collect::<Vec<_>>();
+            let card_edits = card_edits(&deck, &path, &stripped, &mut seen_cards)
+                .into_iter()
+                .map(|edit| Edit {
+                    range: if edit.range.is_empty() {
+                        let offset = original_offset(&deletions, edit.range.start, false);
+                        offset..offset
+                    } else {
+                        original_offset(&deletions, edit.range.start, true)
+                            ..original_offset(&deletions, edit.range.end, false)
+                    },
+                    offset: original_offset(&deletions, edit.offset, true),
+                    ..edit
+                })
+                .collect::<Vec<_>>();
+
+            edits.retain(|edit| {
+                !card_edits.iter().any(|card_edit| {
+                    card_edit.range.start <= edit.range.start
+                        && edit.range.end <= card_edit.range.end
+                        && !card_edit.range.is_empty()
+                })
+            });
+            edits.extend(card_edits);
+            edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+        }
+
+        if !edits.is_empty() {
+            fixes.push(DeckRewrite {
+                path,
+                source,
+                edits,
+            });
+        }
+    }
+
+    Ok(fixes)
+}
+
+///Maps an offset in the source with the deletions applied back onto the original source.
+///Deletions at the offset itself are only skipped over if `after_deletions`.
+fn original_offset(deletions: &[Range<usize>], offset: usize, after_deletions: bool) -> usize {
+    let mut original = offset;
+
+    for deletion in deletions {
+        let position = deletion.start - (original - offset);
+        if position < offset || (after_deletions && position == offset) {
+            original += deletion.len();
+        } else {
+            break;
+        }
+    }
+
+    original
+}
+
+///Deletions of trailing commas and trailing whitespace, which don't need the deck to be valid
+fn syntax_edits(source: &str) -> Vec<Edit> {
+    let bytes = source.as_bytes();
+    let mut edits = vec![];
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if in_string => i += 1,
+            b'"' => in_string = !in_string,
+            b',' if !in_string => {
+                let next = bytes[i + 1..]
+                    .iter()
+                    .find(|byte| !byte.is_ascii_whitespace());
+                if matches!(next, Some(b']' | b'}')) {
+                    edits.push(Edit::new(i..i + 1, "", "Removed trailing comma"));
+                }
+            }
+            //NOTE: Strings can't contain raw newlines, so this is always outside of one
+            b'\n' => in_string = false,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let trimmed = content.trim_end_matches([' ', '\t']);
+        if trimmed.len() < content.len() {
+            let start = offset + trimmed.len();
+            edits.push(Edit::new(
+                start..offset + content.len(),
+                "",
+                "Removed trailing whitespace",
+            ));
+        }
+        offset += line.len();
+    }
+
+    edits.sort_by_key(|edit| edit.range.start);
+    edits
+}
+
+///Edits fixing the cards of a parsed deck: removing cards identical to ones already seen,
+///in this or any previous deck file, and fixing the faces of every other card
+fn card_edits(
+    deck: &Deck,
+    path: &Path,
+    json: &str,
+    seen_cards: &mut Vec<(FixedFaces, PathBuf, usize)>,
+) -> Vec<Edit> {
+    let spans = json_deck_spans(json);
+    let face_count = deck.faces.len();
+    let mut edits = vec![];
+
+    if spans.cards.len() != deck.len() {
+        return edits;
+    }
+
+    let mut removed = vec![None; deck.len()];
+    for (i, card) in deck.iter().enumerate() {
+        let faces = (0..face_count.max(card.len()))
+            .map(|face| {
+                let face = card.get(face).cloned().flatten();
+                face.map(|face| fixed_face(&face).map_or(face, |(fixed, _)| fixed))
+            })
+            .collect::<Vec<_>>();
+
+        match seen_cards.iter().find(|(seen, ..)| *seen == faces) {
+            Some((_, seen_path, j)) if seen_path == path => {
+                removed[i] = Some(format!("Removed cards[{i}], identical to cards[{j}]"));
+            }
+            Some((_, seen_path, j)) => {
+                removed[i] = Some(format!(
+                    "Removed cards[{i}], identical to cards[{j}] in {}",
+                    seen_path.to_str().unwrap_or("unknown")
+                ));
+            }
+            None => seen_cards.push((faces, path.to_path_buf(), i)),
+        }
+    }
+
+    for (i, description) in removed.iter().enumerate() {
+        if let Some(description) = description {
+            edits.push(
+                Edit::new(
+                    removal_range(&spans.cards, &removed, i, json),
+                    "",
+                    description,
+                )
+                .offset(spans.cards[i].start),
+            );
+        }
+    }
+
+    for (i, card) in deck.iter().enumerate() {
+        if removed[i].is_some() {
+            continue;
+        }
+
+        //NOTE: Keyed cards are arranged by the deck's faces, and leave out null faces,
+        //so only need their faces fixed, found by key
+        if card.is_keyed() {
+            let entries = spans.card_keys.get(i).map_or(&[][..], Vec::as_slice);
+            for (face, name) in card.iter().zip(deck.faces.iter()) {
+                let span = entries
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .and_then(|(_, entry)| entry_value_span(json, entry));
+                if let Some(((fixed, description), span)) =
+                    face.as_ref().and_then(fixed_face).zip(span)
+                {
+                    edits.push(Edit::new(
+                        span,
+                        face_json(&fixed),
+                        format!("{description} in cards[{i}]"),
+                    ));
+                }
+            }
+            continue;
+        }
+
+        let Some(face_spans) = spans
+            .card_faces
+            .get(i)
+            .filter(|faces| faces.len() == card.len())
+        else {
+            continue;
+        };
+
+        for (face, span) in card.iter().zip(face_spans.iter()) {
+            if let Some((fixed, description)) = face.as_ref().and_then(fixed_face) {
+                edits.push(Edit::new(
+                    span.clone(),
+                    face_json(&fixed),
+                    format!("{description} in cards[{i}]"),
+                ));
+            }
+        }
+
+        if card.len() < face_count {
+            let nulls = vec!["null"; face_count - card.len()].join(", ");
+            let (at, text) = match face_spans.last() {
+                Some(face) => (face.end, format!(", {nulls}")),
+                None => (spans.cards[i].start + 1, nulls),
+            };
+            edits.push(
+                Edit::new(
+                    at..at,
+                    text,
+                    format!("Padded cards[{i}] with nulls to match the deck's {face_count} faces"),
+                )
+                .offset(spans.cards[i].start),
+            );
+        } else if card.len() > face_count && card[face_count..].iter().all(Option::is_none) {
+            edits.push(
+                Edit::new(
+                    face_spans[face_count - 1].end..face_spans[card.len() - 1].end,
+                    "",
+                    format!("Removed extra null faces from cards[{i}]"),
+                )
+                .offset(spans.cards[i].start),
+            );
+        }
+    }
+
+    edits
+}
+
+///Range removing the card along with its separating comma, such that the ranges
+///of every removed card never overlap
+fn removal_range<T>(
+    cards: &[Range<usize>],
+    removed: &[Option<T>],
+    i: usize,
+    json: &str,
+) -> Range<usize> {
+    let kept_before = removed[..i].iter().any(Option::is_none);
+    let kept_after = removed[i + 1..].iter().any(Option::is_none);
+
+    match i {
+        _ if kept_before => cards[i - 1].end..cards[i].end,
+        _ if kept_after => cards[i].start..cards[i + 1].start,
+        //NOTE: Every card is removed, so the first is removed up to the opening bracket
+        0 => {
+            json[..cards[0].start]
+                .rfind('[')
+                .map_or(cards[0].start, |i| i + 1)..cards[0].end
+        }
+        _ => cards[i - 1].end..cards[i].end,
+    }
+}
+
+///Fixed version of the face along with a description of the fix, if it needs fixing
+fn fixed_face(face: &Face) -> Option<(Face, &'static str)> {
+    match face {
+        Face::Multi(faces) if faces.len() == 1 => Some((
+            Face::Single(faces[0].trim().to_owned()),
+            "Replaced single subface with a plain face",
+        )),
+        Face::Single(face) if face.trim() != face => Some((
+            Face::Single(face.trim().to_owned()),
+            "Trimmed whitespace from face",
+        )),
+        Face::Multi(faces) if faces.iter().any(|face| face.trim() != face) => Some((
+            Face::Multi(faces.iter().map(|face| face.trim().to_owned()).collect()),
+            "Trimmed whitespace from subfaces",
+        )),
+        _ => None,
+    }
+}
+
+///Formats the face as JSON the same way decks are typically written
+fn face_json(face: &Face) -> String {
+    let string = |face: &String| serde_json::to_string(face).expect("Unable to format face");
+    match face {
+        Face::Single(face) => string(face),
+        Face::Multi(faces) => format!(
+            "[{}]",
+            faces.iter().map(string).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Deck;
+
+    use super::fix_paths;
+
+    #[test]
+    fn fixes_common_problems() {
+        let fixes = fix_paths(vec!["./tests/fix/fixable.json"]).expect("Unable to fix test deck");
+        assert_eq!(fixes.len(), 1);
+
+        let fixed = fixes[0].fixed();
+        assert_eq!(
+            fixed,
+            r#"{
+    "name": "Fixable",
+    "faces": ["Front", "Middle", "Back"],
+    "cards": [
+        ["Front 1", "Middle 1", "Back 1"],
+        ["Front 2", "Middle 2", "Back 2"],
+        ["Front 3", "Middle 3", null],
+        ["Front 4", "Middle 4", "Back 4"]
+    ]
+}
+"#
+        );
+        assert!(serde_json::from_str::<Deck>(&fixed).is_ok());
+    }
+
+    #[test]
+    fn fixes_faces_of_keyed_cards() {
+        let fixes = fix_paths(vec!["./tests/fix/keyed.json"]).expect("Unable to fix test deck");
+        assert_eq!(fixes.len(), 1);
+
+        let fixed = fixes[0].fixed();
+        assert_eq!(
+            fixed,
+            r#"{
+    "name": "Keyed Fixable",
+    "faces": ["Kanji", "English"],
+    "cards": [
+        {"English": "Water", "Kanji": "水"},
+        {"Kanji": "火", "English": "Fire"},
+        {"Kanji": "木", "Unknown": " Tree "}
+    ]
+}
+"#
+        );
+        assert!(serde_json::from_str::<Deck>(&fixed).is_ok());
+    }
+
+    #[test]
+    fn removes_identical_cards_across_files() {
+        let fixes = fix_paths(vec!["./tests/duplicate_cards"]).expect("Unable to fix test decks");
+        assert_eq!(fixes.len(), 3);
+
+        for fix in fixes {
+            let deck = serde_json::from_str::<Deck>(&fix.fixed()).expect("Unable to parse fix");
+            assert!(deck.cards.is_empty());
+        }
+    }
+
+    #[test]
+    fn leaves_valid_decks_alone() {
+        let fixes =
+            fix_paths(vec!["./tests/deck1.json", "./tests/dir"]).expect("Unable to fix test decks");
+        assert!(fixes.is_empty());
+    }
+
+    #[test]
+    fn diffs_changed_lines() {
+        let fixes = fix_paths(vec!["./tests/fix/fixable.json"]).expect("Unable to fix test deck");
+        let diff = fixes[0].diff();
+
+        assert!(diff.starts_with("--- ./tests/fix/fixable.json\n+++ ./tests/fix/fixable.json\n"));
+        assert!(diff.contains(
+            "@@ -7,2 +7,1 @@\n-        [\"Front 3\", \"Middle 3\"],\n-        [\"Front 1\", \"Middle 1\", \"Back 1\"],\n+        [\"Front 3\", \"Middle 3\", null],\n"
+        ));
+    }
+}
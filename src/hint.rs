@@ -0,0 +1,114 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::deck::{plain_text, Card, Face};
+
+///Hints towards the answer of a problem about the card, from least to most revealing.
+///The card's own hints come first, leaving out any that would give the answer away,
+///followed by how many subfaces the answer has, how long each is, and what each starts with.
+pub(crate) fn hints(card: &Card, answer: &Face) -> Vec<String> {
+    let mut hints = card
+        .hints()
+        .iter()
+        .filter(|hint| !answer.is_match(hint, None))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let subfaces = match answer {
+        Face::Single(face) => std::slice::from_ref(face),
+        Face::Multi(faces) => {
+            hints.push(format!("{} parts", faces.len()));
+            faces.as_slice()
+        }
+    };
+
+    //NOTE: Without any markup, as only the text itself is answered
+    let subfaces = subfaces
+        .iter()
+        .map(|face| plain_text(face))
+        .collect::<Vec<_>>();
+
+    let lengths = subfaces
+        .iter()
+        .map(|face| face.trim().chars().count())
+        .collect::<Vec<_>>();
+    let plural = if lengths.iter().any(|length| *length != 1) {
+        "characters"
+    } else {
+        "character"
+    };
+    hints.push(format!("{} {plural} long", join(&lengths)));
+
+    let starts = subfaces
+        .iter()
+        .filter_map(|face| face.trim().chars().next())
+        .map(|start| format!("\"{start}\""))
+        .collect::<Vec<_>>();
+    if !starts.is_empty() {
+        hints.push(format!("Starts with {}", join(&starts)));
+    }
+
+    hints
+}
+
+fn join(items: &[impl ToString]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::{load_decks, Card, Face};
+
+    use super::hints;
+
+    #[test]
+    fn generates_hints_for_answer() {
+        let card = Card::new(vec![Some("水"), Some("Water")]);
+        assert_eq!(
+            hints(&card, &Face::Single("Water".to_owned())),
+            ["5 characters long", "Starts with \"W\""]
+        );
+
+        let answer = Face::Multi(vec!["みず".to_owned(), "すい".to_owned()]);
+        assert_eq!(
+            hints(&card, &answer),
+            [
+                "2 parts",
+                "2, 2 characters long",
+                "Starts with \"み\", \"す\""
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_out_hints_giving_answer_away() {
+        let deck = load_decks(vec!["./tests/hints.json"])
+            .expect("Unable to load test deck")
+            .remove(0);
+        let answer = Face::Single("Fire".to_owned());
+        assert_eq!(
+            hints(&deck[1], &answer),
+            ["Burns", "4 characters long", "Starts with \"F\""]
+        );
+    }
+}
@@ -0,0 +1,256 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+///Only the tag's syntax is checked, not whether its subtags are registered
+pub fn is_valid_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let is_alpha = |subtag: &str| subtag.chars().all(|c| c.is_ascii_alphabetic());
+
+    let primary_is_valid = subtags.next().is_some_and(|primary| {
+        matches!(primary.len(), 2..=3 | 5..=8) && is_alpha(primary)
+            || primary.eq_ignore_ascii_case("x")
+            || primary.eq_ignore_ascii_case("i")
+    });
+
+    primary_is_valid
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+fn primary_language(tag: &str) -> String {
+    tag.split('-').next().unwrap_or_default().to_lowercase()
+}
+
+///Japanese text also has full-width letters and katakana folded, and romaji converted to
+///hiragana, so that answers may be typed without a Japanese keyboard
+pub fn normalize(text: &str, language: Option<&str>) -> String {
+    let text = text.trim().to_lowercase();
+
+    match language.map(primary_language).as_deref() {
+        Some("ja") => romaji_to_hiragana(&text.chars().map(fold_japanese).collect::<String>()),
+        _ => text,
+    }
+}
+
+fn fold_japanese(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        '\u{30A1}'..='\u{30F6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c.to_ascii_lowercase(),
+    }
+}
+
+///Hepburn, and common Kunrei-shiki, romaji with their hiragana, longest first where it matters
+const ROMAJI: &[(&str, &str)] = &[
+    ("kya", "きゃ"),
+    ("kyu", "きゅ"),
+    ("kyo", "きょ"),
+    ("sha", "しゃ"),
+    ("shu", "しゅ"),
+    ("sho", "しょ"),
+    ("shi", "し"),
+    ("sya", "しゃ"),
+    ("syu", "しゅ"),
+    ("syo", "しょ"),
+    ("cha", "ちゃ"),
+    ("chu", "ちゅ"),
+    ("cho", "ちょ"),
+    ("chi", "ち"),
+    ("tya", "ちゃ"),
+    ("tyu", "ちゅ"),
+    ("tyo", "ちょ"),
+    ("tsu", "つ"),
+    ("nya", "にゃ"),
+    ("nyu", "にゅ"),
+    ("nyo", "にょ"),
+    ("hya", "ひゃ"),
+    ("hyu", "ひゅ"),
+    ("hyo", "ひょ"),
+    ("mya", "みゃ"),
+    ("myu", "みゅ"),
+    ("myo", "みょ"),
+    ("rya", "りゃ"),
+    ("ryu", "りゅ"),
+    ("ryo", "りょ"),
+    ("gya", "ぎゃ"),
+    ("gyu", "ぎゅ"),
+    ("gyo", "ぎょ"),
+    ("jya", "じゃ"),
+    ("jyu", "じゅ"),
+    ("jyo", "じょ"),
+    ("bya", "びゃ"),
+    ("byu", "びゅ"),
+    ("byo", "びょ"),
+    ("pya", "ぴゃ"),
+    ("pyu", "ぴゅ"),
+    ("pyo", "ぴょ"),
+    ("ja", "じゃ"),
+    ("ju", "じゅ"),
+    ("jo", "じょ"),
+    ("ji", "じ"),
+    ("si", "し"),
+    ("ti", "ち"),
+    ("tu", "つ"),
+    ("hu", "ふ"),
+    ("fu", "ふ"),
+    ("zi", "じ"),
+    ("ka", "か"),
+    ("ki", "き"),
+    ("ku", "く"),
+    ("ke", "け"),
+    ("ko", "こ"),
+    ("sa", "さ"),
+    ("su", "す"),
+    ("se", "せ"),
+    ("so", "そ"),
+    ("ta", "た"),
+    ("te", "て"),
+    ("to", "と"),
+    ("na", "な"),
+    ("ni", "に"),
+    ("nu", "ぬ"),
+    ("ne", "ね"),
+    ("no", "の"),
+    ("ha", "は"),
+    ("hi", "ひ"),
+    ("he", "へ"),
+    ("ho", "ほ"),
+    ("ma", "ま"),
+    ("mi", "み"),
+    ("mu", "む"),
+    ("me", "め"),
+    ("mo", "も"),
+    ("ya", "や"),
+    ("yu", "ゆ"),
+    ("yo", "よ"),
+    ("ra", "ら"),
+    ("ri", "り"),
+    ("ru", "る"),
+    ("re", "れ"),
+    ("ro", "ろ"),
+    ("wa", "わ"),
+    ("wo", "を"),
+    ("ga", "が"),
+    ("gi", "ぎ"),
+    ("gu", "ぐ"),
+    ("ge", "げ"),
+    ("go", "ご"),
+    ("za", "ざ"),
+    ("zu", "ず"),
+    ("ze", "ぜ"),
+    ("zo", "ぞ"),
+    ("da", "だ"),
+    ("di", "ぢ"),
+    ("du", "づ"),
+    ("de", "で"),
+    ("do", "ど"),
+    ("ba", "ば"),
+    ("bi", "び"),
+    ("bu", "ぶ"),
+    ("be", "べ"),
+    ("bo", "ぼ"),
+    ("pa", "ぱ"),
+    ("pi", "ぴ"),
+    ("pu", "ぷ"),
+    ("pe", "ぺ"),
+    ("po", "ぽ"),
+];
+
+fn romaji_to_hiragana(text: &str) -> String {
+    let mut hiragana = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let next = rest[c.len_utf8()..].chars().next();
+
+        if let Some((romaji, kana)) = ROMAJI.iter().find(|(romaji, _)| rest.starts_with(romaji)) {
+            hiragana.push_str(kana);
+            rest = &rest[romaji.len()..];
+            continue;
+        }
+
+        match (c, next) {
+            ('a', _) => hiragana.push('あ'),
+            ('i', _) => hiragana.push('い'),
+            ('u', _) => hiragana.push('う'),
+            ('e', _) => hiragana.push('え'),
+            ('o', _) => hiragana.push('お'),
+            ('-', _) => hiragana.push('ー'),
+            //NOTE: "nn" and "n'" are both a single ん, unless the second n starts a syllable
+            ('n', Some('n'))
+                if rest[2..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| "aiueoy".contains(c)) =>
+            {
+                hiragana.push('ん')
+            }
+            ('n', Some('n' | '\'')) => {
+                hiragana.push('ん');
+                rest = &rest[2..];
+                continue;
+            }
+            ('n', _) => hiragana.push('ん'),
+            //NOTE: Doubled consonants are a small っ, eg. "kitte" for きって
+            (c, Some(next)) if c == next && c.is_ascii_alphabetic() => hiragana.push('っ'),
+            ('t', Some('c')) => hiragana.push('っ'),
+            (c, _) => hiragana.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    hiragana
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_valid_tag, normalize};
+
+    #[test]
+    fn validates_language_tags() {
+        for tag in ["ja", "en-US", "zh-Hant-TW", "es-419", "x-klingon"] {
+            assert!(is_valid_tag(tag), "{tag} is valid");
+        }
+        for tag in ["", "j", "japanese-", "en_US", "en--US", "日本"] {
+            assert!(!is_valid_tag(tag), "{tag} is invalid");
+        }
+    }
+
+    #[test]
+    fn normalizes_japanese() {
+        let ja = Some("ja-JP");
+        for (romaji, kana) in [
+            ("mizu", "みず"),
+            ("Konnichiwa", "こんにちわ"),
+            ("kitte", "きって"),
+            ("shinbun", "しんぶん"),
+            ("kon'ya", "こんや"),
+            ("ko-hi-", "こーひー"),
+            ("matcha", "まっちゃ"),
+        ] {
+            assert_eq!(normalize(romaji, ja), normalize(kana, ja));
+        }
+        assert_eq!(normalize("テレビ", ja), normalize("terebi", ja));
+        assert_eq!(normalize("ＡＢＣ", ja), normalize("abc", ja));
+        assert_eq!(normalize(" Water ", Some("en")), "water");
+        assert_ne!(normalize("mizu", None), normalize("みず", None));
+    }
+}
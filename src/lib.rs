@@ -17,33 +17,78 @@
  * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+use anki::AnkiError;
 use clap::Parser;
-use stats::StatsError;
-use std::{
-    fmt::Display,
-    ops::{Deref, Not},
-    str::FromStr,
-};
+use cli::{DeckCommand, DeckSearchArgs, DeckSelectionArgs, FlashrCommand, ImportCommand};
+use export::ExportError;
+use schema::{FaceChange, FacePosition, SchemaError};
+use stats::{Stats, StatsError};
+use std::{fmt::Display, ops::Deref, path::PathBuf, str::FromStr, time::Duration};
+use tags::TagFilter;
 
-use deck::{load_decks, Card, CardId, Deck, DeckError, Face};
-use modes::{flashcards::show_flashcards, match_faces::match_faces};
+use deck::{
+    find_deck_files_with, load_decks_with, Card, CardId, Deck, DeckError, DeckSearch, Face,
+};
+use glob::{MatchOptions, Pattern};
+use modes::{
+    flashcards::show_flashcards,
+    match_faces::{match_faces, validate_match_faces},
+    order_faces::{order_faces, validate_order_faces},
+    true_false::true_false,
+    type_faces::type_faces,
+};
 use terminal::TerminalWrapper;
 
+mod anki;
 mod cli;
+mod cloze;
 mod color;
+mod compose;
+mod csv_deck;
 pub mod deck;
 mod event;
+mod export;
+mod fix;
+mod hint;
+mod language;
+mod location;
+mod markdown_deck;
 mod modes;
 mod random;
 mod render_utils;
+mod report;
+mod rewrite;
+mod rich;
+mod ruby;
+mod schema;
 mod stats;
+mod tags;
 mod terminal;
+mod validate;
 mod weighted_list;
 
 pub fn run() -> Result<Option<Progress>, FlashrError> {
     let cli = cli::FlashrCli::parse();
-    let decks = load_decks(cli.paths)?;
-    let args = ModeArguments::new(&decks, cli.problem_count, cli.faces, cli.line);
+
+    if let Some(command) = cli.command {
+        return run_command(command).map(|_| None);
+    }
+
+    let decks = load_selected_decks(cli.paths, cli.selection)?;
+    let faces = FaceSelection::new(cli.question_faces, cli.answer_faces, cli.directions);
+    let tags = TagFilter::new(cli.tags, cli.exclude_tags);
+    //NOTE: True/false problems only need a single distractor for each question
+    let choices = match cli.mode {
+        Mode::TrueFalse => cli::MIN_CHOICES as usize,
+        _ => cli.choices,
+    };
+    let args = ModeArguments::new(&decks, cli.problem_count, faces, &tags, choices, cli.line)?;
+
+    match cli.mode {
+        Mode::Match | Mode::TrueFalse => validate_match_faces(&args)?,
+        Mode::Order => validate_order_faces(&args)?,
+        Mode::Type | Mode::Flash => {}
+    }
 
     std::panic::catch_unwind(|| {
         //NOTE: From this point, stdout/stderr will not be usable, hence we
@@ -52,8 +97,10 @@ pub fn run() -> Result<Option<Progress>, FlashrError> {
 
         let correct_incorrect = match cli.mode {
             Mode::Match => match_faces(term, args).map(Some),
-            Mode::Flash => show_flashcards(term, args.deck_cards).map(|_| None),
-            Mode::Type => todo!("Type mode not yet implemented"),
+            Mode::Flash => show_flashcards(term, args.cards(), &args.faces).map(|_| None),
+            Mode::Type => type_faces(term, args).map(Some),
+            Mode::Order => order_faces(term, args).map(Some),
+            Mode::TrueFalse => true_false(term, args, Duration::from_secs(cli.countdown)).map(Some),
         }?;
 
         Ok(correct_incorrect)
@@ -71,29 +118,448 @@ pub fn run() -> Result<Option<Progress>, FlashrError> {
     })?
 }
 
+///Deck directory used when no paths are given, unless another is configured
+const DEFAULT_HOME_DECKS_PATH: &str = ".config/flashr/decks";
+
+///Paths to load decks from, which are the deck directory if none are given
+fn deck_paths(paths: Vec<String>, deck_dir: Option<String>) -> Result<Vec<PathBuf>, ArgError> {
+    if !paths.is_empty() {
+        return Ok(paths.into_iter().map(PathBuf::from).collect());
+    }
+
+    let deck_dir = match deck_dir {
+        Some(deck_dir) => PathBuf::from(deck_dir),
+        None => dirs::home_dir()
+            .map(|home| home.join(DEFAULT_HOME_DECKS_PATH))
+            .ok_or(ArgError::NoDeckPaths(None))?,
+    };
+
+    match deck_dir.is_dir() {
+        true => Ok(vec![deck_dir]),
+        false => Err(ArgError::NoDeckPaths(Some(deck_dir))),
+    }
+}
+
+fn deck_search(args: &DeckSearchArgs) -> DeckSearch {
+    DeckSearch {
+        max_depth: args.max_depth,
+        follow_symlinks: !args.no_follow_symlinks,
+    }
+}
+
+///Deck files in the paths, or the deck directory if none are given. Fails if there are none.
+fn find_selected_deck_files(
+    paths: Vec<String>,
+    args: DeckSearchArgs,
+) -> Result<Vec<PathBuf>, FlashrError> {
+    let search = deck_search(&args);
+    let paths = deck_paths(paths, args.deck_dir)?;
+    let files = find_deck_files_with(paths.iter(), &search)?;
+    match files.is_empty() {
+        true => Err(ArgError::NoDeckFiles(paths).into()),
+        false => Ok(files),
+    }
+}
+
+///Loads the decks in the paths, or the deck directory if none are given, which are
+///allowed by the deck filters
+fn load_selected_decks(
+    paths: Vec<String>,
+    args: DeckSelectionArgs,
+) -> Result<Vec<Deck>, FlashrError> {
+    let search = deck_search(&args.search);
+    let decks = load_decks_with(deck_paths(paths, args.search.deck_dir)?, &search)?;
+    Ok(DeckFilter::new(args.decks, args.exclude_decks).apply(decks)?)
+}
+
+///Which loaded decks are used, by glob patterns matched against their names
+#[derive(Clone, Debug, Default)]
+struct DeckFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+const DECK_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+impl DeckFilter {
+    fn new(include: Vec<Pattern>, exclude: Vec<Pattern>) -> Self {
+        Self { include, exclude }
+    }
+
+    fn allows(&self, name: &str) -> bool {
+        let matches = |pattern: &Pattern| pattern.matches_with(name, DECK_MATCH_OPTIONS);
+
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+
+    ///Keeps only the decks allowed by the filter. Fails if any included pattern
+    ///doesn't match a single deck, or if no decks are left.
+    fn apply(&self, decks: Vec<Deck>) -> Result<Vec<Deck>, ArgError> {
+        let names = decks.iter().map(|deck| &deck.name).collect::<Vec<_>>();
+        let unknown_decks = self
+            .include
+            .iter()
+            .filter(|pattern| {
+                !names
+                    .iter()
+                    .any(|name| pattern.matches_with(name, DECK_MATCH_OPTIONS))
+            })
+            .map(|pattern| ArgError::UnknownDeck {
+                pattern: pattern.to_string(),
+                suggestion: suggest(pattern.as_str(), &names).cloned(),
+            })
+            .collect::<Vec<_>>();
+
+        if !unknown_decks.is_empty() {
+            return Err(ArgError::from_errors(unknown_decks));
+        }
+
+        let filtered = decks
+            .into_iter()
+            .filter(|deck| self.allows(&deck.name))
+            .collect::<Vec<_>>();
+
+        match filtered.is_empty() && !self.exclude.is_empty() {
+            true => Err(ArgError::NoMatchingDecks),
+            false => Ok(filtered),
+        }
+    }
+}
+
+fn run_command(command: FlashrCommand) -> Result<(), FlashrError> {
+    match command {
+        FlashrCommand::Validate {
+            deny_warnings,
+            fix,
+            dry_run,
+            search,
+            paths,
+        } => {
+            let paths = find_selected_deck_files(paths, search)?;
+            if fix {
+                for deck_fix in fix::fix_paths(paths.iter())? {
+                    print!("{deck_fix}");
+                    if dry_run {
+                        print!("{}", deck_fix.diff());
+                    } else {
+                        deck_fix.write()?;
+                    }
+                }
+            }
+
+            if dry_run {
+                return Ok(());
+            }
+
+            let report = validate::validate_paths(paths)?;
+            print!("{report}");
+            report.into_result(deny_warnings)
+        }
+        FlashrCommand::Stats {
+            by_tag,
+            selection,
+            paths,
+        } => {
+            let decks = load_selected_decks(paths, selection)?;
+            let stats = Stats::load_from_user_home()?;
+            print!("{}", report::stats_report(&decks, &stats, by_tag));
+            Ok(())
+        }
+        FlashrCommand::Deck { command } => {
+            let (change, dry_run, search, paths) = match command {
+                DeckCommand::List { selection, paths } => {
+                    print!(
+                        "{}",
+                        report::deck_list(&load_selected_decks(paths, selection)?)
+                    );
+                    return Ok(());
+                }
+                DeckCommand::AddFace {
+                    face,
+                    before,
+                    after,
+                    dry_run,
+                    search,
+                    paths,
+                } => {
+                    let position = match (before, after) {
+                        (Some(face), _) => FacePosition::Before(face),
+                        (None, Some(face)) => FacePosition::After(face),
+                        (None, None) => FacePosition::End,
+                    };
+                    (FaceChange::Add(face, position), dry_run, search, paths)
+                }
+                DeckCommand::RemoveFace {
+                    face,
+                    dry_run,
+                    search,
+                    paths,
+                } => (FaceChange::Remove(face), dry_run, search, paths),
+                DeckCommand::ReorderFaces {
+                    faces,
+                    dry_run,
+                    search,
+                    paths,
+                } => (FaceChange::Reorder(faces), dry_run, search, paths),
+            };
+
+            let paths = find_selected_deck_files(paths, search)?;
+            let (migrations, skipped) = schema::migrate_paths(paths, &change)?;
+            for path in skipped.iter() {
+                println!(
+                    "{}: skipped, only the faces of JSON decks can be changed",
+                    path.to_str().unwrap_or("unknown")
+                );
+            }
+            if dry_run {
+                for migration in migrations.iter() {
+                    print!("{}", migration.rewrite.diff());
+                }
+                return Ok(());
+            }
+
+            let mut stats = Stats::load_from_user_home()?;
+            for migration in migrations.iter() {
+                migration.rewrite.write()?;
+                println!(
+                    "{}: {change}",
+                    migration.rewrite.path.to_str().unwrap_or("unknown")
+                );
+            }
+            schema::remap_stats(&mut stats, &migrations);
+            stats.save_to_file()?;
+
+            let renamed = migrations
+                .iter()
+                .map(|migration| migration.renamed.len())
+                .sum::<usize>();
+            if renamed > 0 {
+                println!("Moved stats of {renamed} cards whose front changed");
+            }
+            Ok(())
+        }
+        FlashrCommand::Import {
+            command:
+                ImportCommand::Anki {
+                    path,
+                    out_dir,
+                    force,
+                    dry_run,
+                },
+        } => {
+            let import = anki::import_apkg(path.as_ref())?;
+            let paths = match dry_run {
+                true => import
+                    .decks
+                    .iter()
+                    .map(|deck| anki::AnkiImport::deck_path(out_dir.as_ref(), deck))
+                    .collect(),
+                false => import.write(out_dir.as_ref(), force)?,
+            };
+
+            for (deck, path) in import.decks.iter().zip(paths.iter()) {
+                println!(
+                    "{}: \"{}\", {} cards",
+                    path.to_str().unwrap_or("unknown"),
+                    deck.name,
+                    deck.cards.len()
+                );
+            }
+            print!("{import}");
+            Ok(())
+        }
+        FlashrCommand::Export {
+            format,
+            stats_tags,
+            out_dir,
+            selection,
+            paths,
+        } => {
+            let decks = load_selected_decks(paths, selection)?;
+            let stats = match stats_tags {
+                true => Some(Stats::load_from_user_home()?),
+                false => None,
+            };
+
+            match out_dir {
+                Some(dir) => {
+                    for path in export::export_to_dir(&decks, format, stats.as_ref(), dir.as_ref())?
+                    {
+                        println!("{}", path.to_str().unwrap_or("unknown"));
+                    }
+                }
+                None => print!("{}", export::export_decks(&decks, format, stats.as_ref())?),
+            }
+            Ok(())
+        }
+    }
+}
+
 type Faces = Option<Vec<String>>;
 type ProblemCount = Option<usize>;
+type FaceEntry<'a> = (usize, &'a String, &'a Face);
+type FacePair<'a> = (FaceEntry<'a>, FaceEntry<'a>);
 
 #[derive(Clone, Copy)]
 struct DeckCard<'a> {
     deck: &'a Deck,
     card: &'a Card,
+    ///Cloze deletion of the card quizzed instead of its faces, see [DeckCard::clozes]
+    cloze: Option<Cloze>,
+}
+
+///Cloze deletion of a card, given by the index of the face it's in and its number
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Cloze {
+    face: usize,
+    number: u32,
 }
 
 impl<'a> DeckCard<'a> {
     fn new(deck: &'a Deck, card: &'a Card) -> Self {
-        Self { deck, card }
+        Self {
+            deck,
+            card,
+            cloze: None,
+        }
+    }
+
+    ///Each cloze deletion of this card allowed by the selection, to be quizzed on its own.
+    ///Clozes are only quizzed when answers aren't restricted, as they're answered by the
+    ///deleted text rather than a face.
+    fn clozes(&self, faces: &FaceSelection) -> Vec<DeckCard<'a>> {
+        let mut clozes = vec![];
+        for face in self.deck.cloze_faces(self.card) {
+            if !faces.allows_cloze(&self.deck.faces[face]) {
+                continue;
+            }
+            if let Some(card_face) = self.card[face].as_ref() {
+                for number in cloze::numbers(card_face) {
+                    clozes.push(Self {
+                        cloze: Some(Cloze { face, number }),
+                        ..*self
+                    });
+                }
+            }
+        }
+        clozes
+    }
+
+    ///Face with this card's cloze deletion, along with the text it deletes, if this is a cloze
+    fn cloze_entry(&self) -> Option<(FaceEntry<'a>, Face)> {
+        let Cloze { face, number } = self.cloze?;
+        let card_face = self.card[face].as_ref()?;
+        let answer = cloze::answer(card_face, number)?;
+        Some(((face, &self.deck.faces[face], card_face), answer))
     }
 
-    fn possible_faces(&self) -> Vec<(usize, &String, &Face)> {
+    ///Faces of this card which may be used as a question or answer, leaving out info faces
+    fn possible_faces(&self) -> Vec<FaceEntry<'a>> {
         let mut possible_faces = Vec::with_capacity(self.deck.faces.len());
         for (index, deck_face) in self.deck.faces.iter().enumerate() {
+            if self.deck.is_info_face(deck_face) {
+                continue;
+            }
             if let Some(card_face) = self.card[index].as_ref() {
                 possible_faces.push((index, deck_face, card_face));
             }
         }
         possible_faces
     }
+
+    ///Faces of this card which are only shown for information, like example sentences
+    fn info_faces(&self) -> Vec<FaceEntry<'a>> {
+        let mut info_faces = Vec::with_capacity(self.deck.metadata.info_faces.len());
+        for (index, deck_face) in self.deck.faces.iter().enumerate() {
+            if !self.deck.is_info_face(deck_face) {
+                continue;
+            }
+            if let Some(card_face) = self.card[index].as_ref() {
+                info_faces.push((index, deck_face, card_face));
+            }
+        }
+        info_faces
+    }
+
+    ///Faces which may be quizzed joined by the separator, leaving out info faces
+    fn join_quizzed(&self, sep: &str) -> String {
+        self.possible_faces()
+            .into_iter()
+            .map(|(_, _, face)| face.to_string())
+            .collect::<Vec<_>>()
+            .join(sep)
+    }
+
+    fn possible_pairs(&self, faces: &FaceSelection) -> Vec<FacePair<'a>> {
+        let possible_faces = self.possible_faces();
+        let mut pairs = vec![];
+
+        for question in possible_faces.iter() {
+            for answer in possible_faces.iter() {
+                if faces.allows_pair(question, answer) {
+                    pairs.push((*question, *answer));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    ///Whether the readings of annotated text can be shown for a problem with the faces,
+    ///which isn't the case when they'd give away the answer, eg. for Kanji->Hiragana
+    fn shows_readings(
+        &self,
+        ((_, question_face, question), (_, answer_face, answer)): &FacePair,
+    ) -> bool {
+        let language = |face: &str| self.deck.metadata.language(face);
+        !ruby::gives_away(
+            (question, language(question_face)),
+            (answer, language(answer_face)),
+        )
+    }
+
+    ///Question faces are ordered first, followed by the answer faces
+    fn selected_faces(&self, faces: &FaceSelection) -> Vec<FaceEntry<'a>> {
+        let pairs = self.possible_pairs(faces);
+
+        if pairs.is_empty() {
+            return self.possible_faces();
+        }
+
+        let mut selected: Vec<FaceEntry<'a>> = Vec::with_capacity(self.deck.faces.len());
+        let questions = pairs.iter().map(|(question, _)| question);
+        let answers = pairs.iter().map(|(_, answer)| answer);
+
+        for face in questions.chain(answers) {
+            if !selected.iter().any(|(i, _, _)| *i == face.0) {
+                selected.push(*face);
+            }
+        }
+
+        selected
+    }
+
+    ///Whether this card is covered by the selection, in which case it must be able
+    ///to produce at least one problem. Cards are selected by their question faces,
+    ///or by their answer faces when only those are specified.
+    fn is_selected_by(&self, faces: &FaceSelection) -> bool {
+        let possible_faces = self.possible_faces();
+
+        if faces.restricts_questions() {
+            possible_faces
+                .iter()
+                .any(|(_, face, _)| faces.allows_question(face))
+        } else {
+            possible_faces
+                .iter()
+                .any(|(_, face, _)| faces.allows_answer(face))
+        }
+    }
 }
 
 impl<'a> Deref for DeckCard<'a> {
@@ -121,6 +587,8 @@ enum Mode {
     Match,
     Type,
     Flash,
+    Order,
+    TrueFalse,
 }
 
 impl FromStr for Mode {
@@ -133,6 +601,10 @@ impl FromStr for Mode {
             Ok(Self::Flash)
         } else if s == "type" {
             Ok(Self::Type)
+        } else if s == "order" {
+            Ok(Self::Order)
+        } else if s == "true-false" || s == "tf" {
+            Ok(Self::TrueFalse)
         } else {
             Err(format!("Mode argument not recognized: {s}"))
         }
@@ -147,19 +619,179 @@ impl Display for Mode {
             Mode::Match => "match",
             Mode::Type => "type",
             Mode::Flash => "flash",
+            Mode::Order => "order",
+            Mode::TrueFalse => "tf",
         })
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct FaceDirection {
+    question: String,
+    answer: String,
+}
+
+const DIRECTION_SEPARATOR: &str = "->";
+
+impl FromStr for FaceDirection {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(DIRECTION_SEPARATOR) {
+            Some((question, answer)) => {
+                let (question, answer) = (question.trim(), answer.trim());
+
+                if question.is_empty() || answer.is_empty() {
+                    Err(format!("Direction is missing a face: {s}"))
+                } else if question == answer {
+                    Err(format!(
+                        "Direction cannot have the same question and answer: {s}"
+                    ))
+                } else {
+                    Ok(Self {
+                        question: question.to_owned(),
+                        answer: answer.to_owned(),
+                    })
+                }
+            }
+            None => Err(format!(
+                "Direction must be in the form \"Question{DIRECTION_SEPARATOR}Answer\": {s}"
+            )),
+        }
+    }
+
+    type Err = String;
+}
+
+impl Display for FaceDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "{}{DIRECTION_SEPARATOR}{}",
+            self.question, self.answer
+        ))
+    }
+}
+
+///Empty/None restrictions allow any face
+#[derive(Clone, Debug, Default)]
+struct FaceSelection {
+    question: Faces,
+    answer: Faces,
+    directions: Vec<FaceDirection>,
+}
+
+impl FaceSelection {
+    fn new(question: Faces, answer: Faces, directions: Vec<FaceDirection>) -> Self {
+        Self {
+            question,
+            answer,
+            directions,
+        }
+    }
+
+    fn is_restricted(&self) -> bool {
+        self.restricts_questions() || self.answer.is_some()
+    }
+
+    ///Every face name given in the selection, without duplicates
+    fn names(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = vec![];
+        let questions = self.question.iter().flatten();
+        let answers = self.answer.iter().flatten();
+        let directions = self
+            .directions
+            .iter()
+            .flat_map(|direction| [&direction.question, &direction.answer]);
+
+        for name in questions.chain(answers).chain(directions) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        names
+    }
+
+    ///Face names which decide whether a card is selected, see [DeckCard::is_selected_by]
+    fn selecting_names(&self) -> Vec<String> {
+        match (self.question.as_ref(), self.answer.as_ref()) {
+            _ if !self.directions.is_empty() => {
+                let mut names = self.question.clone().unwrap_or_default();
+                self.directions
+                    .iter()
+                    .filter(|direction| !names.contains(&direction.question))
+                    .map(|direction| direction.question.clone())
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .for_each(|name| names.push(name));
+                names
+            }
+            (Some(question), _) => question.clone(),
+            (None, Some(answer)) => answer.clone(),
+            (None, None) => vec![],
+        }
+    }
+
+    fn restricts_questions(&self) -> bool {
+        self.question.is_some() || !self.directions.is_empty()
+    }
+
+    fn allows_question(&self, face: &str) -> bool {
+        self.question
+            .as_ref()
+            .is_none_or(|faces| faces.iter().any(|question| question == face))
+            && (self.directions.is_empty()
+                || self
+                    .directions
+                    .iter()
+                    .any(|direction| direction.question == face))
+    }
+
+    fn allows_answer(&self, face: &str) -> bool {
+        self.answer
+            .as_ref()
+            .is_none_or(|faces| faces.iter().any(|answer| answer == face))
+            && (self.directions.is_empty()
+                || self
+                    .directions
+                    .iter()
+                    .any(|direction| direction.answer == face))
+    }
+
+    ///Whether the face's cloze deletions may be quizzed, which are answered by the text
+    ///they delete, so only when answers and directions aren't restricted
+    fn allows_cloze(&self, face: &str) -> bool {
+        self.answer.is_none() && self.directions.is_empty() && self.allows_question(face)
+    }
+
+    fn allows_pair(&self, question: &FaceEntry, answer: &FaceEntry) -> bool {
+        let ((question_index, question, _), (answer_index, answer, _)) = (question, answer);
+
+        question_index != answer_index
+            && self.allows_question(question)
+            && self.allows_answer(answer)
+            && (self.directions.is_empty()
+                || self.directions.iter().any(|direction| {
+                    &&direction.question == question && &&direction.answer == answer
+                }))
+    }
+}
+
 struct ModeArguments<'a> {
     problem_count: ProblemCount,
-    faces: Faces,
+    faces: FaceSelection,
     deck_cards: Vec<DeckCard<'a>>,
+    choices: usize,
     line: bool,
 }
 
 impl<'a> ModeArguments<'a> {
-    fn new(decks: &'a [Deck], problem_count: ProblemCount, faces: Faces, line: bool) -> Self {
+    fn new(
+        decks: &'a [Deck],
+        problem_count: ProblemCount,
+        faces: FaceSelection,
+        tags: &TagFilter,
+        choices: usize,
+        line: bool,
+    ) -> Result<Self, ArgError> {
         let mut deck_cards = {
             let max_num_problems = decks.iter().fold(0, |total, deck| {
                 total + (deck.cards.len() * deck.faces.len())
@@ -167,60 +799,205 @@ impl<'a> ModeArguments<'a> {
             Vec::with_capacity(max_num_problems)
         };
 
-        if let Some(faces) = faces.as_ref() {
+        let unknown_faces = {
+            let deck_faces = decks
+                .iter()
+                .flat_map(|deck| deck.faces.iter())
+                .collect::<Vec<_>>();
+
+            faces
+                .names()
+                .into_iter()
+                .filter(|face| !deck_faces.contains(face))
+                .map(|face| ArgError::UnknownFace {
+                    face: face.clone(),
+                    suggestion: suggest(face, &deck_faces).cloned(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let unknown_tags = {
+            let mut deck_tags: Vec<&String> = vec![];
             for deck in decks {
-                let deck_faces = {
-                    let mut buf = Vec::with_capacity(deck.faces.len());
-                    deck.faces
-                        .iter()
-                        .enumerate()
-                        .filter(|(_, deck_face)| faces.iter().any(|face| face == *deck_face))
-                        .for_each(|(i, _)| buf.push(i));
-                    buf
-                };
-
-                deck_faces.is_empty().not().then(|| {
-                    for card in deck.cards.iter() {
-                        if deck_faces.iter().any(|i| card[*i].is_some()) {
-                            deck_cards.push(DeckCard::new(deck, card));
+                for card in deck.cards.iter() {
+                    for tag in deck.tags_of(card) {
+                        if !deck_tags.contains(&tag) {
+                            deck_tags.push(tag);
                         }
                     }
-                });
+                }
             }
-        } else {
-            for deck in decks {
-                for card in deck.cards.iter() {
-                    deck_cards.push(DeckCard::new(deck, card));
+
+            tags.names()
+                .into_iter()
+                .filter(|tag| {
+                    !deck_tags
+                        .iter()
+                        .any(|other| other.to_lowercase() == tag.to_lowercase())
+                })
+                .map(|tag| ArgError::UnknownTag {
+                    tag: tag.clone(),
+                    suggestion: suggest(tag, &deck_tags).cloned(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let info_faces = faces
+            .names()
+            .into_iter()
+            //NOTE: Unknown faces are already errors
+            .filter(|face| {
+                let mut having = decks.iter().filter(|deck| deck.faces.contains(face));
+                having.clone().next().is_some() && having.all(|deck| deck.is_info_face(face))
+            })
+            .map(|face| ArgError::InfoFace(face.clone()))
+            .collect::<Vec<_>>();
+
+        //NOTE: Any other errors would just be noise caused by the unknown faces and tags
+        if !unknown_faces.is_empty() || !unknown_tags.is_empty() || !info_faces.is_empty() {
+            return Err(ArgError::from_errors(
+                unknown_faces
+                    .into_iter()
+                    .chain(unknown_tags)
+                    .chain(info_faces)
+                    .collect(),
+            ));
+        }
+
+        let mut errors = vec![];
+
+        for deck in decks {
+            let num_selected = deck_cards.len();
+            let mut num_tagged = 0;
+
+            for card in deck.cards.iter() {
+                if !tags.allows(&deck.tags_of(card)) {
+                    continue;
+                }
+                num_tagged += 1;
+
+                let deck_card = DeckCard::new(deck, card);
+
+                if !deck_card.is_selected_by(&faces) {
+                    continue;
                 }
+
+                let has_pairs = !deck_card.possible_pairs(&faces).is_empty();
+                let clozes = deck_card.clozes(&faces);
+                if !has_pairs && clozes.is_empty() {
+                    errors.push(ArgError::NoUsableFacePair {
+                        deck: deck.name.clone(),
+                        card: card.front_string(),
+                    });
+                    continue;
+                }
+
+                if has_pairs {
+                    deck_cards.push(deck_card);
+                }
+                deck_cards.extend(clozes);
+            }
+
+            //NOTE: Decks without any cards left by the tag filter are simply not used
+            if faces.is_restricted() && num_tagged > 0 && deck_cards.len() == num_selected {
+                let selecting_names = faces.selecting_names();
+
+                errors.push(
+                    if deck.faces.iter().any(|face| selecting_names.contains(face)) {
+                        ArgError::DeckNoSelectedCards(deck.name.clone())
+                    } else {
+                        ArgError::DeckNotEnoughFaces(selecting_names, deck.name.clone())
+                    },
+                );
             }
         }
-        Self {
+
+        if tags.is_restricted() && errors.is_empty() && deck_cards.is_empty() {
+            errors.push(ArgError::NoTaggedCards);
+        }
+
+        if !errors.is_empty() {
+            return Err(ArgError::from_errors(errors));
+        }
+
+        Ok(Self {
             problem_count,
             faces,
             deck_cards,
+            choices,
             line,
+        })
+    }
+
+    ///Each selected card once, without which of its clozes were selected
+    fn cards(&self) -> Vec<DeckCard<'a>> {
+        let mut cards: Vec<DeckCard<'a>> = Vec::with_capacity(self.deck_cards.len());
+        for deck_card in self.deck_cards.iter() {
+            //NOTE: A card's clozes are selected right after the card itself
+            if !cards
+                .last()
+                .is_some_and(|last| std::ptr::eq(last.card, deck_card.card))
+            {
+                cards.push(DeckCard::new(deck_card.deck, deck_card.card));
+            }
         }
+        cards
     }
-}
 
-#[repr(transparent)]
-struct OptionTuple<T>(Option<(T, T)>);
+    ///Ensures that every face which will be used as an answer has at least
+    ///`choices` distinct values across the selected cards, otherwise match
+    ///problems could never be filled. Clozes are checked along with their distractors,
+    ///see [validate_match_faces].
+    fn validate_choices(&self) -> Result<(), ArgError> {
+        let answer_faces = {
+            let mut buf: Vec<&String> = vec![];
+            for deck_card in self.deck_cards.iter().filter(|card| card.cloze.is_none()) {
+                for (_, (_, answer_face, _)) in deck_card.possible_pairs(&self.faces) {
+                    if !buf.contains(&answer_face) {
+                        buf.push(answer_face);
+                    }
+                }
+            }
+            buf
+        };
+
+        let mut distinct_faces: Vec<(&String, Vec<&Face>)> = vec![];
 
-impl<T> Deref for OptionTuple<T> {
-    type Target = Option<(T, T)>;
+        for deck_card in self.deck_cards.iter().filter(|card| card.cloze.is_none()) {
+            for (_, deck_face, card_face) in deck_card.possible_faces() {
+                if !answer_faces.contains(&deck_face) {
+                    continue;
+                }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
+                match distinct_faces
+                    .iter_mut()
+                    .find(|(face, _)| *face == deck_face)
+                {
+                    Some((_, seen)) => {
+                        if !seen.contains(&card_face) {
+                            seen.push(card_face);
+                        }
+                    }
+                    None => distinct_faces.push((deck_face, vec![card_face])),
+                }
+            }
+        }
 
-impl<T> FromIterator<T> for OptionTuple<T> {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut iter = iter.into_iter();
-        Self(
-            iter.next()
-                .and_then(|first| iter.next().map(|second| (first, second))),
-        )
+        let errors = distinct_faces
+            .into_iter()
+            .filter(|(_, seen)| seen.len() < self.choices)
+            .map(|(face, seen)| ArgError::NotEnoughAnswers {
+                face: face.clone(),
+                available: seen.len(),
+                choices: self.choices,
+            })
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ArgError::from_errors(errors))
+        }
     }
 }
 
@@ -265,6 +1042,13 @@ impl Progress {
     fn add_incorrect(&mut self) {
         self.total += 1;
     }
+
+    ///Adds a problem scored by how many of its parts were right, such as the subfaces
+    ///put in their right place in order mode
+    fn add_partial(&mut self, correct: usize, total: usize) {
+        self.correct += correct;
+        self.total += total;
+    }
 }
 
 #[derive(Debug)]
@@ -274,6 +1058,10 @@ pub enum FlashrError {
     DeckMismatch(String),
     Arg(ArgError),
     Stats(StatsError),
+    Schema(SchemaError),
+    Anki(AnkiError),
+    Export(ExportError),
+    Validation { errors: usize, warnings: usize },
     Panic(String),
 }
 
@@ -285,6 +1073,12 @@ impl Display for FlashrError {
             Self::Arg(err) => f.write_fmt(format_args!("Arg: {err}")),
             Self::Ui(err) => f.write_fmt(format_args!("Ui: {err}")),
             Self::Stats(err) => f.write_fmt(format_args!("Stats: {err}")),
+            Self::Schema(err) => f.write_fmt(format_args!("Schema: {err}")),
+            Self::Anki(err) => f.write_fmt(format_args!("Anki: {err}")),
+            Self::Export(err) => f.write_fmt(format_args!("Export: {err}")),
+            Self::Validation { errors, warnings } => f.write_fmt(format_args!(
+                "Validation: Decks have {errors} errors and {warnings} warnings"
+            )),
             Self::Panic(err) => f.write_fmt(format_args!("Panicked: {err}")),
         }
     }
@@ -314,6 +1108,24 @@ impl From<StatsError> for FlashrError {
     }
 }
 
+impl From<SchemaError> for FlashrError {
+    fn from(err: SchemaError) -> Self {
+        Self::Schema(err)
+    }
+}
+
+impl From<AnkiError> for FlashrError {
+    fn from(err: AnkiError) -> Self {
+        Self::Anki(err)
+    }
+}
+
+impl From<ExportError> for FlashrError {
+    fn from(err: ExportError) -> Self {
+        Self::Export(err)
+    }
+}
+
 #[derive(Debug)]
 pub enum UiError {
     IoError(std::io::Error),
@@ -336,6 +1148,55 @@ impl From<std::io::Error> for UiError {
 #[derive(Debug)]
 pub enum ArgError {
     DeckNotEnoughFaces(Vec<String>, String),
+    NotEnoughAnswers {
+        face: String,
+        available: usize,
+        choices: usize,
+    },
+    NoUsableFacePair {
+        deck: String,
+        card: String,
+    },
+    DeckNoSelectedCards(String),
+    ImpossibleMatchProblem {
+        deck: String,
+        card: String,
+        direction: String,
+        available: usize,
+        needed: usize,
+    },
+    UnknownFace {
+        face: String,
+        suggestion: Option<String>,
+    },
+    UnknownTag {
+        tag: String,
+        suggestion: Option<String>,
+    },
+    ///Face is an info face of every deck which has it, so can't be selected
+    InfoFace(String),
+    NoTaggedCards,
+    ///No selected card has an answer face with subfaces to put in order
+    NoOrderableFaces,
+    UnknownDeck {
+        pattern: String,
+        suggestion: Option<String>,
+    },
+    NoMatchingDecks,
+    ///No paths were given, and the deck directory, if known, doesn't exist
+    NoDeckPaths(Option<PathBuf>),
+    NoDeckFiles(Vec<PathBuf>),
+    Multiple(Vec<ArgError>),
+}
+
+impl ArgError {
+    fn from_errors(mut errors: Vec<ArgError>) -> Self {
+        if errors.len() == 1 {
+            errors.remove(0)
+        } else {
+            Self::Multiple(errors)
+        }
+    }
 }
 
 impl Display for ArgError {
@@ -345,6 +1206,476 @@ impl Display for ArgError {
                 let faces = faces.join(", ");
                 f.write_fmt(format_args!("Deck \"{deck}\" does not have enough faces for arguments:\nNeeds at least one of: {faces}"))
             }
+            Self::NotEnoughAnswers {
+                face,
+                available,
+                choices,
+            } => f.write_fmt(format_args!(
+                "Not enough distinct \"{face}\" faces for {choices} choices. Only {available} available"
+            )),
+            Self::NoUsableFacePair { deck, card } => f.write_fmt(format_args!(
+                "Card \"{card}\" in deck \"{deck}\" has no question and answer faces matching the selected faces"
+            )),
+            Self::ImpossibleMatchProblem {
+                deck,
+                card,
+                direction,
+                available,
+                needed,
+            } => f.write_fmt(format_args!(
+                "Card \"{card}\" in deck \"{deck}\" cannot be shown as a {direction} match problem. Needs {needed} other answers, only {available} available"
+            )),
+            Self::DeckNoSelectedCards(deck) => f.write_fmt(format_args!(
+                "Deck \"{deck}\" has no cards with the selected faces"
+            )),
+            Self::UnknownFace { face, suggestion } => {
+                f.write_fmt(format_args!("No loaded deck has a \"{face}\" face"))?;
+                match suggestion {
+                    Some(suggestion) => {
+                        f.write_fmt(format_args!(", did you mean \"{suggestion}\"?"))
+                    }
+                    None => Ok(()),
+                }
+            }
+            Self::UnknownTag { tag, suggestion } => {
+                f.write_fmt(format_args!("No card of any loaded deck is tagged \"{tag}\""))?;
+                match suggestion {
+                    Some(suggestion) => {
+                        f.write_fmt(format_args!(", did you mean \"{suggestion}\"?"))
+                    }
+                    None => Ok(()),
+                }
+            }
+            Self::InfoFace(face) => f.write_fmt(format_args!(
+                "\"{face}\" is an info face, which is only shown after answering and is never quizzed"
+            )),
+            Self::NoTaggedCards => f.write_str("No cards have tags matching the tag filters"),
+            Self::NoOrderableFaces => f.write_str(
+                "No selected card has an answer face with more than one distinct subface to put in order",
+            ),
+            Self::UnknownDeck {
+                pattern,
+                suggestion,
+            } => {
+                f.write_fmt(format_args!("No loaded deck's name matches \"{pattern}\""))?;
+                match suggestion {
+                    Some(suggestion) => {
+                        f.write_fmt(format_args!(", did you mean \"{suggestion}\"?"))
+                    }
+                    None => Ok(()),
+                }
+            }
+            Self::NoMatchingDecks => f.write_str("Every loaded deck is excluded by the deck filters"),
+            Self::NoDeckPaths(Some(deck_dir)) => f.write_fmt(format_args!(
+                "No deck paths given, and the deck directory doesn't exist: {}",
+                deck_dir.to_str().unwrap_or("unknown")
+            )),
+            Self::NoDeckPaths(None) => f.write_str(
+                "No deck paths given, and unable to find the user home directory for the deck directory",
+            ),
+            Self::NoDeckFiles(paths) => f.write_fmt(format_args!(
+                "No deck files found in: {}",
+                paths
+                    .iter()
+                    .map(|path| path.to_str().unwrap_or("unknown"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            Self::Multiple(errors) => {
+                let count = errors.len();
+                f.write_fmt(format_args!("{count} errors found:"))?;
+                for err in errors {
+                    f.write_fmt(format_args!("\n{err}"))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+///Finds the most similar of the candidates to the given name, as long as it's
+///close enough to plausibly be a typo
+fn suggest<'a, S: AsRef<str>>(name: &str, candidates: &[&'a S]) -> Option<&'a S> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    candidates
+        .iter()
+        .map(|candidate| {
+            let distance = edit_distance(&name, &candidate.as_ref().to_lowercase());
+            (*candidate, distance)
+        })
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
+
+///Levenshtein distance between the two strings, in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, char_a) in a.chars().enumerate() {
+        let mut current = Vec::with_capacity(prev.len());
+        current.push(i + 1);
+
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution = prev[j] + (char_a != *char_b) as usize;
+            let insertion = current[j] + 1;
+            let deletion = prev[j + 1] + 1;
+            current.push(substitution.min(insertion).min(deletion));
         }
+
+        prev = current;
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use clap::Parser;
+    use glob::Pattern;
+
+    use crate::{
+        cli::{DeckCommand, FlashrCli, FlashrCommand, DEFAULT_CHOICES},
+        deck::{load_decks, Face},
+        deck_paths, load_selected_decks, run_command, suggest,
+        tags::TagFilter,
+        ArgError, DeckFilter, FaceDirection, FaceSelection, FlashrError, ModeArguments,
+    };
+
+    #[test]
+    fn parse_face_direction() {
+        let direction: FaceDirection = "Kanji -> English"
+            .parse()
+            .expect("Unable to parse direction");
+        assert_eq!(direction.question, "Kanji");
+        assert_eq!(direction.answer, "English");
+
+        assert!("Kanji".parse::<FaceDirection>().is_err());
+        assert!("Kanji->".parse::<FaceDirection>().is_err());
+        assert!("Kanji->Kanji".parse::<FaceDirection>().is_err());
+    }
+
+    #[test]
+    fn suggests_similar_faces() {
+        let kanji = "Kanji".to_owned();
+        let english = "English".to_owned();
+        let candidates = [&kanji, &english];
+
+        assert_eq!(suggest("kanj", &candidates), Some(&kanji));
+        assert_eq!(suggest("Englsh", &candidates), Some(&english));
+        assert_eq!(suggest("Definition", &candidates), None);
+    }
+
+    #[test]
+    fn fails_if_unknown_face() {
+        let decks = load_decks(vec!["./tests/deck1.json"]).expect("Unable to load test deck");
+        let faces = FaceSelection::new(Some(vec!["Kanjii".to_owned()]), None, vec![]);
+
+        assert!(
+            ModeArguments::new(&decks, None, faces, &TagFilter::default(), DEFAULT_CHOICES, false).is_err_and(|err| {
+                matches!(err, ArgError::UnknownFace { suggestion: Some(suggestion), .. } if suggestion == "Kanji")
+            })
+        );
+    }
+
+    #[test]
+    fn never_quizzes_info_faces() {
+        let decks = load_decks(vec!["./tests/info_faces.json"]).expect("Unable to load test deck");
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+
+        for deck_card in args.deck_cards.iter() {
+            let faces = deck_card.possible_faces();
+            assert!(faces
+                .iter()
+                .all(|(_, face, _)| !decks[0].is_info_face(face)));
+            assert_eq!(faces.len(), 3);
+        }
+        let info_faces = args.deck_cards[0].info_faces();
+        assert_eq!(info_faces[0].1, "Example");
+        assert_eq!(info_faces[1].1, "Notes");
+        assert_eq!(args.deck_cards[0].join_quizzed(", "), "水, みず, Water");
+
+        let faces = FaceSelection::new(Some(vec!["Example".to_owned()]), None, vec![]);
+        assert!(ModeArguments::new(
+            &decks,
+            None,
+            faces,
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false
+        )
+        .is_err_and(|err| matches!(err, ArgError::InfoFace(face) if face == "Example")));
+    }
+
+    #[test]
+    fn selects_each_cloze() {
+        let decks = load_decks(vec!["./tests/cloze.json"]).expect("Unable to load test deck");
+        let new_args = |faces| {
+            ModeArguments::new(
+                &decks,
+                None,
+                faces,
+                &TagFilter::default(),
+                DEFAULT_CHOICES,
+                false,
+            )
+        };
+
+        let args = new_args(FaceSelection::default()).expect("Unable to create mode arguments");
+        let clozes = args
+            .deck_cards
+            .iter()
+            .map(|deck_card| deck_card.cloze.map(|cloze| (cloze.face, cloze.number)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            clozes,
+            [
+                Some((0, 1)),
+                Some((0, 2)),
+                Some((0, 1)),
+                Some((0, 2)),
+                Some((0, 1)),
+                Some((0, 1)),
+                Some((0, 2)),
+                Some((0, 1)),
+            ]
+        );
+        assert_eq!(args.cards().len(), 5);
+
+        let (_, answer) = args.deck_cards[1].cloze_entry().expect("Card has no cloze");
+        assert_eq!(answer, Face::Single("mat".to_owned()));
+        let (_, answer) = args.deck_cards[4].cloze_entry().expect("Card has no cloze");
+        assert_eq!(answer.join(), "Birds, morning");
+
+        //NOTE: Clozes are only quizzed when any face may be the answer
+        let faces = FaceSelection::new(None, Some(vec!["Sentence".to_owned()]), vec![]);
+        assert!(new_args(faces).is_err_and(|err| matches!(err, ArgError::Multiple(..))));
+    }
+
+    #[test]
+    fn filters_cards_by_tag() {
+        let decks = load_decks(vec!["./tests/tags.json", "./tests/deck1.json"])
+            .expect("Unable to load test decks");
+        let tag = |expr: &str| expr.parse().expect("Unable to parse tag expression");
+        let fronts = |tags: TagFilter| {
+            ModeArguments::new(
+                &decks,
+                None,
+                FaceSelection::default(),
+                &tags,
+                DEFAULT_CHOICES,
+                false,
+            )
+            .expect("Unable to create mode arguments")
+            .deck_cards
+            .iter()
+            .map(|deck_card| deck_card.card.front_string())
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            fronts(TagFilter::new(vec![tag("nature | verb")], vec![])),
+            vec!["水", "火", "食べる"]
+        );
+        assert_eq!(
+            fronts(TagFilter::new(vec![tag("'jlpt n5'")], vec![tag("nature")])),
+            vec!["食べる", "日本"]
+        );
+    }
+
+    #[test]
+    fn fails_if_unknown_tag() {
+        let decks = load_decks(vec!["./tests/tags.json"]).expect("Unable to load test deck");
+        let tags = TagFilter::new(vec!["natur".parse().expect("Unable to parse tag")], vec![]);
+
+        assert!(ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &tags,
+            DEFAULT_CHOICES,
+            false
+        )
+        .is_err_and(|err| {
+            matches!(err, ArgError::UnknownTag { suggestion: Some(suggestion), .. } if suggestion == "nature")
+        }));
+
+        let tags = TagFilter::new(
+            vec!["nature & verb".parse().expect("Unable to parse tag")],
+            vec![],
+        );
+        assert!(ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &tags,
+            DEFAULT_CHOICES,
+            false
+        )
+        .is_err_and(|err| matches!(err, ArgError::NoTaggedCards)));
+    }
+
+    #[test]
+    fn filters_decks_by_name() {
+        let decks = || {
+            load_decks(vec!["./tests/deck1.json", "./tests/dir"])
+                .expect("Unable to load test decks")
+        };
+        let pattern = |pattern: &str| Pattern::new(pattern).expect("Unable to parse pattern");
+        let names = |filter: DeckFilter| {
+            filter
+                .apply(decks())
+                .map(|decks| decks.into_iter().map(|deck| deck.name).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            names(DeckFilter::new(
+                vec![pattern("*kanji*")],
+                vec![pattern("more*")]
+            ))
+            .expect("Unable to filter decks"),
+            vec!["Kanji Words", "Basicest Kanji"]
+        );
+        assert!(names(DeckFilter::new(vec![pattern("Kanji Word")], vec![]))
+            .is_err_and(|err| matches!(err, ArgError::UnknownDeck { suggestion: Some(suggestion), .. } if suggestion == "Kanji Words")));
+        assert!(names(DeckFilter::new(vec![], vec![pattern("*")]))
+            .is_err_and(|err| matches!(err, ArgError::NoMatchingDecks)));
+    }
+
+    #[test]
+    fn uses_deck_dir_without_paths() {
+        assert_eq!(
+            deck_paths(vec!["a.json".to_owned()], Some("./tests/dir".to_owned()))
+                .expect("Unable to get deck paths"),
+            vec![PathBuf::from("a.json")]
+        );
+        assert_eq!(
+            deck_paths(vec![], Some("./tests/dir".to_owned())).expect("Unable to get deck paths"),
+            vec![PathBuf::from("./tests/dir")]
+        );
+        assert!(deck_paths(vec![], Some("./tests/missing".to_owned()))
+            .is_err_and(|err| matches!(err, ArgError::NoDeckPaths(Some(_)))));
+    }
+
+    fn parse_args(args: &[&str]) -> FlashrCli {
+        FlashrCli::try_parse_from(std::iter::once("flashr").chain(args.iter().copied()))
+            .expect("Unable to parse args")
+    }
+
+    #[test]
+    fn validate_fails_without_deck_files() {
+        let validate = |deck_dir: &str| {
+            let cli = parse_args(&["validate", "--deck-dir", deck_dir]);
+            run_command(cli.command.expect("No command parsed"))
+        };
+
+        assert!(matches!(
+            validate("./tests/no_decks"),
+            Err(FlashrError::Arg(ArgError::NoDeckFiles(_)))
+        ));
+        assert!(matches!(
+            validate("./tests/missing"),
+            Err(FlashrError::Arg(ArgError::NoDeckPaths(Some(_))))
+        ));
+    }
+
+    #[test]
+    fn subcommands_select_decks() {
+        let cli = parse_args(&[
+            "deck",
+            "list",
+            "--deck-dir",
+            "./tests/dir",
+            "--exclude-deck",
+            "More*",
+        ]);
+        let Some(FlashrCommand::Deck {
+            command: DeckCommand::List { selection, paths },
+        }) = cli.command
+        else {
+            panic!("Deck list not parsed");
+        };
+
+        let decks = load_selected_decks(paths, selection).expect("Unable to load decks");
+        assert_eq!(
+            decks
+                .iter()
+                .map(|deck| deck.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Basicest Kanji"]
+        );
+    }
+
+    #[test]
+    fn fails_if_deck_not_enough_faces() {
+        let decks = load_decks(vec!["./tests/deck1.json", "./tests/example.json"])
+            .expect("Unable to load test decks");
+        let faces = FaceSelection::new(Some(vec!["Kanji".to_owned()]), None, vec![]);
+
+        assert!(ModeArguments::new(
+            &decks,
+            None,
+            faces,
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false
+        )
+        .is_err_and(
+            |err| matches!(err, ArgError::DeckNotEnoughFaces(_, deck) if deck == "Example")
+        ));
+    }
+
+    #[test]
+    fn fails_if_not_enough_answers() {
+        let decks = load_decks(vec!["./tests/duplicate_cards"])
+            .expect("Unable to load duplicate cards test decks");
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+
+        assert!(args.validate_choices().is_err());
+    }
+
+    #[test]
+    fn fails_if_no_usable_face_pair() {
+        let decks = load_decks(vec!["./tests/deck1.json"]).expect("Unable to load test deck");
+        let faces = FaceSelection::new(
+            Some(vec!["Kanji".to_owned()]),
+            Some(vec!["Kanji".to_owned()]),
+            vec![],
+        );
+
+        assert!(ModeArguments::new(
+            &decks,
+            None,
+            faces,
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false
+        )
+        .is_err_and(|err| {
+            matches!(err, ArgError::Multiple(errors) if errors
+                    .iter()
+                    .any(|err| matches!(err, ArgError::NoUsableFacePair { .. })))
+        }));
     }
 }
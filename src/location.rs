@@ -0,0 +1,592 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{fmt::Display, ops::Range};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SourceLocation {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourceLocation {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    ///Columns are counted in chars
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}:{}", self.line, self.column))
+    }
+}
+
+///Byte ranges of the parts of a deck within its source file
+#[derive(Default, Debug)]
+pub(crate) struct DeckSpans {
+    ///Values of each key in the root object
+    pub keys: Vec<(String, Range<usize>)>,
+    ///Each card in the cards array
+    pub cards: Vec<Range<usize>>,
+    ///Each face of each card, empty for cards which are not arrays
+    pub card_faces: Vec<Vec<Range<usize>>>,
+    ///Each entry of each card, along with its key, empty for cards which are not objects
+    pub card_keys: Vec<Vec<(String, Range<usize>)>>,
+    ///Each face name in the faces array
+    pub faces: Vec<Range<usize>>,
+}
+
+impl DeckSpans {
+    pub fn key(&self, key: &str) -> Option<Range<usize>> {
+        self.keys
+            .iter()
+            .find_map(|(name, span)| (name == key).then(|| span.clone()))
+    }
+}
+
+///Finds the spans of the root keys and each card in a JSON deck, ie. the elements
+///of the root object's "cards" array. Malformed JSON results in as many spans as
+///could be found before the malformed part.
+pub(crate) fn json_deck_spans(json: &str) -> DeckSpans {
+    let mut spans = DeckSpans::default();
+    JsonScanner::new(json).scan_deck(&mut spans);
+    spans.card_faces = spans
+        .cards
+        .iter()
+        .map(|card| {
+            let mut faces = vec![];
+            JsonScanner::at(json, card.start).scan_array(&mut faces);
+            faces
+        })
+        .collect();
+    spans.card_keys = spans
+        .cards
+        .iter()
+        .map(|card| {
+            let mut entries = vec![];
+            JsonScanner::at(json, card.start).scan_object(&mut entries);
+            entries
+        })
+        .collect();
+    if let Some(faces) = spans.key("faces") {
+        JsonScanner::at(json, faces.start).scan_array(&mut spans.faces);
+    }
+    spans
+}
+
+///Finds the span of the value of an object entry, such as those of [DeckSpans::card_keys]
+pub(crate) fn entry_value_span(json: &str, entry: &Range<usize>) -> Option<Range<usize>> {
+    let mut scanner = JsonScanner::at(json, entry.start);
+    scanner.skip_string()?;
+    scanner.expect(b':')?;
+    scanner.skip_whitespace();
+
+    let start = scanner.pos;
+    scanner.skip_value()?;
+    Some(start..scanner.pos)
+}
+
+///Finds the spans of the root keys and each card in a YAML deck. Only cards written as
+///block sequence items, ie. starting with "- " on their own line, are found.
+pub(crate) fn yaml_deck_spans(yaml: &str) -> DeckSpans {
+    let lines = lines_with_offsets(yaml);
+    let is_root_key =
+        |line: &str| !line.is_empty() && !line.starts_with([' ', '\t', '#', '-', '[', '{']);
+
+    line_sections(
+        yaml,
+        &lines,
+        |line| {
+            let (key, _) = line.split_once(':').filter(|_| is_root_key(line))?;
+            Some(key.trim_matches(['"', '\'']))
+        },
+        |section| {
+            let items = section
+                .iter()
+                .filter(|(_, line)| {
+                    let item = line.trim_start();
+                    item.starts_with("- ") || item == "-"
+                })
+                .collect::<Vec<_>>();
+            let indent = items
+                .iter()
+                .map(|(_, line)| line.len() - line.trim_start().len())
+                .min();
+
+            section
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, line))| {
+                    let item = line.trim_start();
+                    Some(line.len() - item.len()) == indent && item.starts_with('-')
+                })
+                .map(|(i, (offset, line))| (i, offset + line.len() - line.trim_start().len()))
+                .collect()
+        },
+    )
+}
+
+///Finds the spans of the root keys and each card in a TOML deck. Only cards written as
+///an array of tables, ie. each starting with a "[[cards]]" header, are found.
+pub(crate) fn toml_deck_spans(toml: &str) -> DeckSpans {
+    let lines = lines_with_offsets(toml);
+    let is_header = |line: &str| {
+        let line = line.trim();
+        line.starts_with('[') && line.ends_with(']') && !line.contains([',', '"', '\''])
+    };
+    let first_header = lines
+        .iter()
+        .position(|(_, line)| is_header(line))
+        .unwrap_or(lines.len());
+
+    let mut spans = line_sections(
+        toml,
+        &lines[..first_header],
+        |line| {
+            let (key, _) = line.split_once('=')?;
+            let key = key.trim();
+            (!key.is_empty()
+                && key
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-'))
+            .then_some(key)
+        },
+        |_| vec![],
+    );
+
+    let headers = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, line))| is_header(line))
+        .collect::<Vec<_>>();
+    for (i, (line, (offset, header))) in headers.iter().enumerate() {
+        if header.trim() != "[[cards]]" {
+            continue;
+        }
+        let end = headers.get(i + 1).map_or(lines.len(), |(line, _)| *line);
+        spans.cards.push(trimmed_span(
+            toml,
+            *offset + header.len() - header.trim_start().len(),
+            &lines[*line..end],
+        ));
+    }
+
+    spans
+}
+
+///Each line of the source along with the offset it starts at
+fn lines_with_offsets(source: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    source
+        .split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            (start, line.trim_end_matches(['\n', '\r']))
+        })
+        .collect()
+}
+
+///Span from the start to the end of the last non-blank, non-comment line
+fn trimmed_span(source: &str, start: usize, lines: &[(usize, &str)]) -> Range<usize> {
+    let end = lines
+        .iter()
+        .rev()
+        .find(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map_or(start, |(offset, line)| offset + line.trim_end().len());
+
+    start..end.max(start).min(source.len())
+}
+
+///Splits the lines into sections, each starting at a line which is a root key, and finds
+///the spans of each key and of the cards, given the start of each card within the cards section
+fn line_sections<'a>(
+    source: &str,
+    lines: &[(usize, &'a str)],
+    root_key: impl Fn(&'a str) -> Option<&'a str>,
+    card_starts: impl Fn(&[(usize, &'a str)]) -> Vec<(usize, usize)>,
+) -> DeckSpans {
+    let mut spans = DeckSpans::default();
+    let roots = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (_, line))| root_key(line).map(|key| (i, key)))
+        .collect::<Vec<_>>();
+
+    for (j, (i, key)) in roots.iter().enumerate() {
+        let end = roots.get(j + 1).map_or(lines.len(), |(line, _)| *line);
+        let (offset, _) = lines[*i];
+        spans.keys.push((
+            key.to_string(),
+            trimmed_span(source, offset, &lines[*i..end]),
+        ));
+
+        if *key == "cards" {
+            let section = &lines[*i + 1..end];
+            let starts = card_starts(section);
+            for (k, (line, start)) in starts.iter().enumerate() {
+                let card_end = starts.get(k + 1).map_or(section.len(), |(line, _)| *line);
+                spans
+                    .cards
+                    .push(trimmed_span(source, *start, &section[*line..card_end]));
+            }
+        }
+    }
+
+    spans
+}
+
+///Lines of the source covering the byte range, along with their line numbers.
+///Limited to the given number of lines.
+pub(crate) fn snippet(source: &str, span: Range<usize>, max_lines: usize) -> Vec<(usize, String)> {
+    let SourceLocation { line: first, .. } = SourceLocation::from_offset(source, span.start);
+    let SourceLocation { line: last, .. } = SourceLocation::from_offset(source, span.end);
+
+    source
+        .lines()
+        .enumerate()
+        .skip(first - 1)
+        .take((last + 1 - first).min(max_lines))
+        .map(|(i, line)| (i + 1, line.to_owned()))
+        .collect()
+}
+
+///Minimal JSON scanner which only understands enough structure to find offsets
+struct JsonScanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonScanner<'a> {
+    fn new(json: &'a str) -> Self {
+        Self::at(json, 0)
+    }
+
+    fn at(json: &'a str, pos: usize) -> Self {
+        Self {
+            bytes: json.as_bytes(),
+            pos,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        (self.peek() == Some(byte)).then(|| self.pos += 1)
+    }
+
+    fn skip_string(&mut self) -> Option<&'a [u8]> {
+        self.expect(b'"')?;
+        let start = self.pos;
+
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Some(&self.bytes[start..self.pos - 1]);
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_whitespace();
+
+        match self.peek()? {
+            b'"' => self.skip_string().map(|_| ()),
+            b'{' | b'[' => {
+                let mut depth = 0;
+                loop {
+                    match self.peek()? {
+                        b'"' => {
+                            self.skip_string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                self.pos += 1;
+                                return Some(());
+                            }
+                        }
+                        _ => {}
+                    }
+                    self.pos += 1;
+                }
+            }
+            _ => {
+                while self.peek().is_some_and(|byte| {
+                    !matches!(byte, b',' | b'}' | b']') && !byte.is_ascii_whitespace()
+                }) {
+                    self.pos += 1;
+                }
+                Some(())
+            }
+        }
+    }
+
+    fn scan_deck(&mut self, spans: &mut DeckSpans) -> Option<()> {
+        self.expect(b'{')?;
+
+        loop {
+            let key = String::from_utf8_lossy(self.skip_string()?).into_owned();
+            self.expect(b':')?;
+            self.skip_whitespace();
+
+            let start = self.pos;
+            if key == "cards" {
+                self.scan_array(&mut spans.cards)?;
+            } else {
+                self.skip_value()?;
+            }
+            spans.keys.push((key, start..self.pos));
+
+            self.expect(b',')?;
+        }
+    }
+
+    ///Scans an object, finding the span of each entry from its key to the end of its value
+    fn scan_object(&mut self, entries: &mut Vec<(String, Range<usize>)>) -> Option<()> {
+        self.expect(b'{')?;
+        self.skip_whitespace();
+
+        if self.peek()? == b'}' {
+            self.pos += 1;
+            return Some(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            let key = String::from_utf8_lossy(self.skip_string()?).into_owned();
+            self.expect(b':')?;
+            self.skip_value()?;
+            entries.push((key, start..self.pos));
+
+            match self.expect(b',') {
+                Some(_) => continue,
+                None => return self.expect(b'}'),
+            }
+        }
+    }
+
+    fn scan_array(&mut self, spans: &mut Vec<Range<usize>>) -> Option<()> {
+        self.expect(b'[')?;
+        self.skip_whitespace();
+
+        if self.peek()? == b']' {
+            self.pos += 1;
+            return Some(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            self.skip_value()?;
+            spans.push(start..self.pos);
+
+            match self.expect(b',') {
+                Some(_) => continue,
+                None => return self.expect(b']'),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        entry_value_span, json_deck_spans, snippet, toml_deck_spans, yaml_deck_spans,
+        SourceLocation,
+    };
+
+    #[test]
+    fn finds_card_locations() {
+        let json = r#"{
+    "name": "Test [with] {brackets} and \"quotes\"",
+    "faces": ["Front", "Back"],
+    "cards": [
+        ["Front 1", "Back 1"],
+        [["Front, 2", "]"], null], ["Front 3", "Back 3"]
+    ]
+}"#;
+
+        assert_eq!(
+            json_deck_spans(json)
+                .cards
+                .into_iter()
+                .map(|span| SourceLocation::from_offset(json, span.start))
+                .collect::<Vec<_>>(),
+            vec![
+                SourceLocation::new(5, 9),
+                SourceLocation::new(6, 9),
+                SourceLocation::new(6, 36),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_key_spans() {
+        let json = r#"{ "name": "Test", "faces": ["Front", "Back"], "cards": [] }"#;
+        let spans = json_deck_spans(json);
+
+        assert_eq!(
+            spans.key("faces").map(|span| &json[span]),
+            Some(r#"["Front", "Back"]"#)
+        );
+        assert_eq!(spans.key("cards").map(|span| &json[span]), Some("[]"));
+        assert_eq!(
+            spans
+                .faces
+                .iter()
+                .map(|span| &json[span.clone()])
+                .collect::<Vec<_>>(),
+            vec![r#""Front""#, r#""Back""#]
+        );
+    }
+
+    #[test]
+    fn finds_card_face_spans() {
+        let json = r#"{ "cards": [["Front", ["Back", "]"], null], {}] }"#;
+        let spans = json_deck_spans(json);
+
+        assert_eq!(
+            spans.card_faces[0]
+                .iter()
+                .map(|span| &json[span.clone()])
+                .collect::<Vec<_>>(),
+            vec![r#""Front""#, r#"["Back", "]"]"#, "null"]
+        );
+        assert!(spans.card_faces[1].is_empty());
+        assert!(spans.card_keys[0].is_empty());
+    }
+
+    #[test]
+    fn finds_card_key_spans() {
+        let json = r#"{ "cards": [{"Front": "}", "Back": ["A", "B"]}] }"#;
+        let spans = json_deck_spans(json);
+
+        assert_eq!(
+            spans.card_keys[0]
+                .iter()
+                .map(|(key, span)| (key.as_str(), &json[span.clone()]))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Front", r#""Front": "}""#),
+                ("Back", r#""Back": ["A", "B"]"#)
+            ]
+        );
+        assert!(spans.card_faces[0].is_empty());
+        assert_eq!(
+            entry_value_span(json, &spans.card_keys[0][1].1).map(|span| &json[span]),
+            Some(r#"["A", "B"]"#)
+        );
+    }
+
+    #[test]
+    fn finds_yaml_spans() {
+        let yaml = "name: Test\nfaces:\n  - Front\n  - Back\ncards:\n- [a, b]\n- Front: c\n  Back: [d, \"- e\"]\n\n# Comment\n";
+        let spans = yaml_deck_spans(yaml);
+
+        assert_eq!(
+            spans.key("faces").map(|span| &yaml[span]),
+            Some("faces:\n  - Front\n  - Back")
+        );
+        assert_eq!(
+            spans
+                .cards
+                .iter()
+                .map(|span| &yaml[span.clone()])
+                .collect::<Vec<_>>(),
+            vec!["- [a, b]", "- Front: c\n  Back: [d, \"- e\"]"]
+        );
+    }
+
+    #[test]
+    fn finds_toml_spans() {
+        let toml = "name = \"Test\"\nfaces = [\n  \"Front\",\n  \"Back\",\n]\n\n[[cards]]\nFront = \"a\"\nBack = [\"b\"]\n\n[[cards]]\nFront = \"c\"\n";
+        let spans = toml_deck_spans(toml);
+
+        assert_eq!(
+            spans.key("faces").map(|span| &toml[span]),
+            Some("faces = [\n  \"Front\",\n  \"Back\",\n]")
+        );
+        assert_eq!(
+            spans
+                .cards
+                .iter()
+                .map(|span| &toml[span.clone()])
+                .collect::<Vec<_>>(),
+            vec![
+                "[[cards]]\nFront = \"a\"\nBack = [\"b\"]",
+                "[[cards]]\nFront = \"c\""
+            ]
+        );
+    }
+
+    #[test]
+    fn snippet_covers_span() {
+        let source = "{\n  \"cards\": [\n    [\n      \"Front\"\n    ]\n  ]\n}";
+        let start = source.find("    [").expect("Unable to find card") + 4;
+        let end = source.find("    ]").expect("Unable to find card end") + 5;
+
+        assert_eq!(
+            snippet(source, start..end, 10),
+            vec![
+                (3, "    [".to_owned()),
+                (4, "      \"Front\"".to_owned()),
+                (5, "    ]".to_owned()),
+            ]
+        );
+        assert_eq!(snippet(source, start..end, 1).len(), 1);
+    }
+
+    #[test]
+    fn columns_count_chars() {
+        let source = "[\"日本\", \"にほん\"]";
+        let offset = source.find("\"に").expect("Unable to find offset");
+        assert_eq!(
+            SourceLocation::from_offset(source, offset),
+            SourceLocation::new(1, 8)
+        );
+    }
+}
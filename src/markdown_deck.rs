@@ -0,0 +1,331 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{fmt::Display, ops::Range, path::Path};
+
+use crate::{
+    deck::{Card, Deck, DeckMetadata, Face},
+    location::DeckSpans,
+};
+
+///Separates faces of a card, and the names in the faces line
+const FACE_SEPARATOR: char = '|';
+///Separates subfaces within a face
+const SUBFACE_SEPARATOR: char = ';';
+const ESCAPE: char = '\\';
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MarkdownErrorKind {
+    UnexpectedLine,
+    DuplicateName(usize),
+    DuplicateFaces(usize),
+    CardBeforeFaces,
+    MissingFaces,
+}
+
+///Error in the layout of a Markdown deck, at a 1-based line and column
+#[derive(Debug)]
+pub struct MarkdownError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: MarkdownErrorKind,
+}
+
+impl Display for MarkdownError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        //NOTE: The location is left out, as it's shown along with the source
+        match self.kind {
+            MarkdownErrorKind::UnexpectedLine => f.write_str(
+                "Expected a \"# Deck name\" heading, a \"faces:\" line, or a \"- \" card list item",
+            ),
+            MarkdownErrorKind::DuplicateName(line) => f.write_fmt(format_args!(
+                "Deck already has a name, given by the heading on line {line}"
+            )),
+            MarkdownErrorKind::DuplicateFaces(line) => {
+                f.write_fmt(format_args!("Deck already has faces, given on line {line}"))
+            }
+            MarkdownErrorKind::CardBeforeFaces => {
+                f.write_str("Card is listed before the \"faces:\" line")
+            }
+            MarkdownErrorKind::MissingFaces => {
+                f.write_str("Deck has no \"faces:\" line, eg. \"faces: Front | Back\"")
+            }
+        }
+    }
+}
+
+///A line of a Markdown deck, along with the byte range of its content within the line
+enum Line<'a> {
+    Ignored,
+    Name(Range<usize>, &'a str),
+    Faces(Range<usize>, &'a str),
+    Card(Range<usize>, &'a str),
+    Unexpected(usize),
+}
+
+impl<'a> Line<'a> {
+    fn new(line: &'a str) -> Self {
+        let trimmed = line.trim();
+        let start = line.len() - line.trim_start().len();
+        let content = |content: &'a str| {
+            let content = content.trim();
+            let offset = content.as_ptr() as usize - line.as_ptr() as usize;
+            (offset..offset + content.len(), content)
+        };
+
+        if trimmed.is_empty()
+            || trimmed.starts_with("##")
+            || (trimmed.starts_with("<!--") && trimmed.ends_with("-->"))
+        {
+            Self::Ignored
+        } else if let Some(name) = trimmed.strip_prefix("# ") {
+            let (range, name) = content(name);
+            Self::Name(range, name)
+        } else if let Some(faces) = trimmed
+            .split_once(':')
+            .filter(|(key, _)| key.trim().eq_ignore_ascii_case("faces"))
+            .map(|(_, faces)| faces)
+        {
+            let (range, faces) = content(faces);
+            Self::Faces(range, faces)
+        } else if let Some(card) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker))
+        {
+            let (range, card) = content(card);
+            Self::Card(range, card)
+        } else {
+            Self::Unexpected(start)
+        }
+    }
+}
+
+fn lines(source: &str) -> impl Iterator<Item = (usize, usize, Line<'_>)> {
+    let mut offset = 0;
+    source
+        .split_inclusive('\n')
+        .enumerate()
+        .map(move |(i, line)| {
+            let start = offset;
+            offset += line.len();
+            (i + 1, start, Line::new(line.trim_end_matches(['\n', '\r'])))
+        })
+}
+
+///Ranges of the parts of the text between unescaped separators
+fn split_unescaped(text: &str, separator: char) -> Vec<Range<usize>> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == ESCAPE {
+            escaped = true;
+        } else if c == separator {
+            parts.push(start..i);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(start..text.len());
+
+    parts
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            (ESCAPE, Some(next @ (FACE_SEPARATOR | SUBFACE_SEPARATOR | ESCAPE))) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+fn card_face(face: &str) -> Option<Face> {
+    let subfaces = split_unescaped(face, SUBFACE_SEPARATOR)
+        .into_iter()
+        .map(|range| unescape(face[range].trim()))
+        .filter(|subface| !subface.is_empty())
+        .collect::<Vec<_>>();
+
+    match subfaces.len() {
+        0 => None,
+        1 => subfaces.into_iter().next().map(Face::Single),
+        _ => Some(Face::Multi(subfaces)),
+    }
+}
+
+///Parses a Markdown deck, named by its "# Deck name" heading, or otherwise the file name.
+///Its "faces:" line and each "- " card list item separate faces with "|" and subfaces
+///with ";", which may be escaped with "\". Empty faces are null.
+pub(crate) fn parse_markdown_deck(path: &Path, source: &str) -> Result<Deck, MarkdownError> {
+    let mut name: Option<(usize, &str)> = None;
+    let mut faces: Option<(usize, Vec<String>)> = None;
+    let mut cards = vec![];
+
+    for (line_number, _, line) in lines(source) {
+        let error = |column: usize, kind| MarkdownError {
+            line: line_number,
+            column: column + 1,
+            kind,
+        };
+
+        match line {
+            Line::Ignored => {}
+            Line::Name(range, text) => match name {
+                Some((line, _)) => {
+                    return Err(error(range.start, MarkdownErrorKind::DuplicateName(line)))
+                }
+                None => name = Some((line_number, text)),
+            },
+            Line::Faces(range, text) => match faces {
+                Some((line, _)) => {
+                    return Err(error(range.start, MarkdownErrorKind::DuplicateFaces(line)))
+                }
+                None => {
+                    let names = split_unescaped(text, FACE_SEPARATOR)
+                        .into_iter()
+                        .map(|range| unescape(text[range].trim()))
+                        .collect();
+                    faces = Some((line_number, names));
+                }
+            },
+            Line::Card(range, text) => {
+                if faces.is_none() {
+                    return Err(error(range.start, MarkdownErrorKind::CardBeforeFaces));
+                }
+                cards.push(Card::from_faces(
+                    split_unescaped(text, FACE_SEPARATOR)
+                        .into_iter()
+                        .map(|range| card_face(&text[range]))
+                        .collect(),
+                ));
+            }
+            Line::Unexpected(column) => {
+                return Err(error(column, MarkdownErrorKind::UnexpectedLine))
+            }
+        }
+    }
+
+    let Some((_, faces)) = faces else {
+        return Err(MarkdownError {
+            line: 1,
+            column: 1,
+            kind: MarkdownErrorKind::MissingFaces,
+        });
+    };
+    let name = name
+        .map(|(_, name)| name.to_owned())
+        .or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+
+    Ok(Deck {
+        name,
+        metadata: DeckMetadata::default(),
+        faces,
+        cards,
+    })
+}
+
+///Finds the spans of the heading, as the "name" key, of the "faces:" line, and of each card
+pub(crate) fn markdown_deck_spans(source: &str) -> DeckSpans {
+    let mut spans = DeckSpans::default();
+
+    for (_, start, line) in lines(source) {
+        match line {
+            Line::Name(range, _) if spans.key("name").is_none() => spans
+                .keys
+                .push(("name".to_owned(), start + range.start..start + range.end)),
+            Line::Faces(range, text) if spans.key("faces").is_none() => {
+                spans
+                    .keys
+                    .push(("faces".to_owned(), start + range.start..start + range.end));
+                spans.faces = split_unescaped(text, FACE_SEPARATOR)
+                    .into_iter()
+                    .map(|face| start + range.start + face.start..start + range.start + face.end)
+                    .collect();
+            }
+            Line::Card(range, _) => spans.cards.push(start + range.start..start + range.end),
+            _ => {}
+        }
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{parse_markdown_deck, split_unescaped, unescape, MarkdownErrorKind};
+
+    #[test]
+    fn splits_on_unescaped_separators() {
+        let text = r"a \| b | c; d \\| e";
+        let parts = split_unescaped(text, '|')
+            .into_iter()
+            .map(|range| unescape(text[range].trim()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(parts, vec!["a | b", "c; d \\", "e"]);
+    }
+
+    #[test]
+    fn names_deck_after_file_without_heading() {
+        let deck = parse_markdown_deck(Path::new("decks/Kanji.md"), "faces: A | B\n- 1 | 2\n")
+            .expect("Unable to parse deck");
+        assert_eq!(deck.name, "Kanji");
+    }
+
+    #[test]
+    fn reports_layout_errors() {
+        let kind = |source: &str| {
+            parse_markdown_deck(Path::new("deck.md"), source)
+                .map(|_| ())
+                .map_err(|err| (err.line, err.kind))
+        };
+
+        assert_eq!(
+            kind("# A\n- 1 | 2\n"),
+            Err((2, MarkdownErrorKind::CardBeforeFaces))
+        );
+        assert_eq!(
+            kind("# A\n\n# B\nfaces: A | B\n"),
+            Err((3, MarkdownErrorKind::DuplicateName(1)))
+        );
+        assert_eq!(
+            kind("faces: A | B\nFaces: C | D\n"),
+            Err((2, MarkdownErrorKind::DuplicateFaces(1)))
+        );
+        assert_eq!(kind("# A\n"), Err((1, MarkdownErrorKind::MissingFaces)));
+        assert_eq!(kind("faces: A | B\n\n## Section\n- 1 | 2\n"), Ok(()));
+    }
+}
@@ -24,7 +24,10 @@ use crossterm::event::{
 };
 use widget::{FlashcardWidget, FlashcardWidgetState};
 
-use crate::{event::clear_and_match_event, terminal::TerminalWrapper, DeckCard, FlashrError};
+use crate::{
+    deck::plain_text, event::clear_and_match_event, render_utils::Scroll,
+    terminal::TerminalWrapper, DeckCard, FaceSelection, FlashrError,
+};
 
 mod widget;
 
@@ -102,6 +105,7 @@ impl<'a, T> Deref for WrappingIndex<'a, T> {
 pub fn show_flashcards(
     term: &mut TerminalWrapper,
     deck_cards: Vec<DeckCard>,
+    faces: &FaceSelection,
 ) -> Result<(), FlashrError> {
     if deck_cards.is_empty() {
         return Ok(());
@@ -116,7 +120,7 @@ pub fn show_flashcards(
             next: deck_cards[index.next_index()],
         };
 
-        let action = show_flashcard(term, problem)?;
+        let action = show_flashcard(term, problem, faces)?;
 
         match action {
             Action::Prev => index.decrement(),
@@ -137,8 +141,11 @@ struct FlashcardProblem<'a> {
 fn show_flashcard(
     term: &mut TerminalWrapper,
     problem: FlashcardProblem,
+    faces: &FaceSelection,
 ) -> Result<Action, FlashrError> {
-    let faces = problem.current.possible_faces();
+    let mut faces = problem.current.selected_faces(faces);
+    //NOTE: Info faces are never quizzed, but are still worth reading when studying
+    faces.extend(problem.current.info_faces());
     let mut index = WrappingIndex::new(&faces);
     let state = &mut FlashcardWidgetState::default();
 
@@ -147,8 +154,8 @@ fn show_flashcard(
         term.render_stateful_widget(
             FlashcardWidget::new(
                 (deck_face, card_face),
-                problem.prev.front_string(),
-                problem.next.front_string(),
+                plain_text(&problem.prev.front_string()),
+                plain_text(&problem.next.front_string()),
             ),
             state,
         )?;
@@ -159,11 +166,20 @@ fn show_flashcard(
             UserInput::NextFace => index.increment(),
             UserInput::PrevFace => index.decrement(),
             UserInput::ExactFace(exact_index) => index.set(exact_index),
+            UserInput::ScrollUp => {
+                state.scroll.up();
+                continue;
+            }
+            UserInput::ScrollDown => {
+                state.scroll.down();
+                continue;
+            }
             UserInput::NextCard => return Ok(Action::Next),
             UserInput::PrevCard => return Ok(Action::Prev),
             UserInput::Quit => return Ok(Action::Quit),
             UserInput::Resize => continue,
         };
+        state.scroll = Scroll::default();
     }
 }
 
@@ -171,6 +187,8 @@ enum UserInput {
     NextFace,
     PrevFace,
     ExactFace(usize),
+    ScrollUp,
+    ScrollDown,
     NextCard,
     PrevCard,
     Resize,
@@ -192,6 +210,8 @@ fn match_user_input(event: Event, state: &FlashcardWidgetState) -> Option<UserIn
             KeyCode::Right | KeyCode::Char(' ') | KeyCode::Char('l') | KeyCode::Char('d') => {
                 Some(UserInput::NextFace)
             }
+            KeyCode::PageUp => Some(UserInput::ScrollUp),
+            KeyCode::PageDown => Some(UserInput::ScrollDown),
             KeyCode::Esc | KeyCode::Char('q') => Some(UserInput::Quit),
             KeyCode::Char(char) => char.to_digit(10).map(|index| {
                 UserInput::ExactFace(
@@ -205,6 +225,14 @@ fn match_user_input(event: Event, state: &FlashcardWidgetState) -> Option<UserIn
             _ => None,
         },
         Event::Resize(_, _) => Some(UserInput::Resize),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::ScrollUp,
+            ..
+        }) => Some(UserInput::ScrollUp),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::ScrollDown,
+            ..
+        }) => Some(UserInput::ScrollDown),
         Event::Mouse(MouseEvent {
             kind: MouseEventKind::Up(button),
             column,
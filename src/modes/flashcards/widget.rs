@@ -19,7 +19,7 @@
 
 use crate::{
     deck::Face,
-    render_utils::{horizontally_centered_area_for_string, BoxOffsets},
+    render_utils::{horizontally_centered_area_for_string, ruby_text, BoxOffsets, Scroll},
 };
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
@@ -30,6 +30,8 @@ use ratatui::{
 pub(super) struct FlashcardWidgetState {
     pub left: Rect,
     pub right: Rect,
+    ///Scroll of the face, for faces too long to fit on screen
+    pub scroll: Scroll,
 }
 
 pub(super) struct FlashcardWidget<'a> {
@@ -55,9 +57,7 @@ impl<'a> StatefulWidget for FlashcardWidget<'a> {
     ) where
         Self: Sized,
     {
-        let face_string = self.face.1.join();
-
-        let (title_area, face_area, sides) = {
+        let (face_text, title_area, face_area, sides) = {
             let (left, middle, right) = {
                 let layout = Layout::new(
                     Direction::Horizontal,
@@ -72,18 +72,23 @@ impl<'a> StatefulWidget for FlashcardWidget<'a> {
                 (split[0], split[1], split[2])
             };
 
-            let title_area = {
+            let (title_area, body_area) = {
                 let layout = Layout::new(
                     Direction::Vertical,
                     [Constraint::Length(3), Constraint::Fill(1)],
                 );
-                layout.split(middle)[0]
+                let split = layout.split(middle);
+                (split[0], split[1])
             };
 
-            let face_area =
-                horizontally_centered_area_for_string(middle, &face_string, BoxOffsets::default());
+            let face_text = ruby_text(&self.face.1.join(), body_area.width, true);
+            let face_area = horizontally_centered_area_for_string(
+                body_area,
+                &face_text.to_string(),
+                BoxOffsets::default(),
+            );
 
-            (title_area, face_area, (left, right))
+            (face_text, title_area, face_area, (left, right))
         };
 
         {
@@ -115,9 +120,11 @@ impl<'a> StatefulWidget for FlashcardWidget<'a> {
         }
 
         {
-            Paragraph::new(face_string)
+            let scroll = state.scroll.clamp(&face_text, face_area);
+            Paragraph::new(face_text)
                 .wrap(Wrap { trim: false })
                 .centered()
+                .scroll(scroll)
                 .render(face_area, buf);
         }
 
@@ -20,18 +20,22 @@
 use rand::prelude::{SliceRandom, ThreadRng};
 
 use crate::{
+    cloze,
+    deck::Face,
+    hint::hints,
     random::{GetRandom, IntoIterShuffled},
     stats::Stats,
     weighted_list::WeightedList,
-    AndThen, DeckCard, FlashrError, OptionTuple, PromptCard,
+    AndThen, ArgError, DeckCard, FaceSelection, FlashrError, PromptCard,
 };
 
-use super::{MatchProblem, ANSWERS_PER_PROBLEM};
+use super::MatchProblem;
 
-pub(super) struct MatchProblemIterator<'a> {
+pub(crate) struct MatchProblemIterator<'a> {
     rng: &'a mut ThreadRng,
     weighted_deck_cards: WeightedList<DeckCard<'a>>,
-    faces: Option<Vec<String>>,
+    faces: FaceSelection,
+    choices: usize,
     line: bool,
 }
 
@@ -39,13 +43,15 @@ impl<'a> MatchProblemIterator<'a> {
     pub fn new(
         deck_cards: Vec<DeckCard<'a>>,
         stats: &mut Stats,
-        faces: Option<Vec<String>>,
+        faces: FaceSelection,
+        choices: usize,
         line: bool,
         rng: &'a mut ThreadRng,
     ) -> Self {
         Self {
             rng,
             faces,
+            choices,
             line,
             weighted_deck_cards: {
                 let mut buf = WeightedList::with_capacity(deck_cards.len());
@@ -61,6 +67,84 @@ impl<'a> MatchProblemIterator<'a> {
     pub fn change_weight(&mut self, index: usize, weight: f64) {
         self.weighted_deck_cards.change_weight(index, weight)
     }
+
+    ///Problem showing the card's face with the text of its cloze deleted, answered by that
+    ///text among the text deleted by clozes of the same face of other cards
+    fn next_cloze(
+        &mut self,
+        problem_deck_card: DeckCard<'a>,
+        problem_index: usize,
+    ) -> Result<MatchProblem<'a>, FlashrError> {
+        let ((_, question_face, problem_question_face), problem_answer) = problem_deck_card
+            .cloze_entry()
+            .expect("Unable to find cloze of cloze card");
+
+        let mut answer_cards = Vec::with_capacity(self.choices);
+        answer_cards.push((
+            (problem_answer.clone(), problem_deck_card, problem_index),
+            true,
+        ));
+
+        for ((deck_card, _), card_index) in self
+            .weighted_deck_cards
+            .clone()
+            .into_iter_shuffled(self.rng)
+        {
+            if answer_cards.len() >= self.choices {
+                break;
+            }
+            let Some(card_answer) = cloze_distractor(&deck_card, &problem_deck_card) else {
+                continue;
+            };
+            if !answer_cards
+                .iter()
+                .any(|((answer, _, _), _)| *answer == card_answer)
+            {
+                answer_cards.push(((card_answer, deck_card, card_index), false));
+            }
+        }
+
+        if answer_cards.len() < self.choices {
+            let deck_name = &problem_deck_card.deck.name;
+            return Err(FlashrError::DeckMismatch(format!("Cannot find enough answers for cloze {problem_question_face}, which is a \"{question_face}\" face, from deck {deck_name}")));
+        }
+
+        answer_cards.shuffle(self.rng);
+
+        let answer_index = answer_cards
+            .iter()
+            .enumerate()
+            .find_map(|(i, (_, correct))| correct.then_some(i))
+            .expect("Unable to find answer index after shuffling");
+
+        let number = problem_deck_card.cloze.map_or(0, |cloze| cloze.number);
+        Ok(MatchProblem {
+            question: PromptCard {
+                prompt: cloze::blank(&problem_question_face.join_random(self.rng), number),
+                deck_card: problem_deck_card,
+                index: problem_index,
+            },
+            answers: answer_cards
+                .into_iter()
+                .map(|((answer, deck_card, index), correct)| {
+                    let prompt = answer.join_random(self.rng);
+                    (
+                        PromptCard {
+                            prompt,
+                            deck_card,
+                            index,
+                        },
+                        correct,
+                    )
+                })
+                .collect(),
+            answer_index,
+            hints: hints(problem_deck_card.card, &problem_answer),
+            //NOTE: The deleted text isn't shown, so its readings can't give it away
+            readings: true,
+            weights: self.line.then(|| self.weighted_deck_cards.weights()),
+        })
+    }
 }
 
 impl<'a> Iterator for MatchProblemIterator<'a> {
@@ -68,40 +152,24 @@ impl<'a> Iterator for MatchProblemIterator<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let (problem_deck_card, problem_index) = self.weighted_deck_cards.get_random(self.rng)?;
+        if problem_deck_card.cloze.is_some() {
+            let problem_deck_card = *problem_deck_card;
+            return Some(self.next_cloze(problem_deck_card, problem_index));
+        }
 
-        let possible_faces = problem_deck_card.possible_faces();
-
+        let pair = *problem_deck_card
+            .possible_pairs(&self.faces)
+            .get_random(self.rng)
+            .expect("Unable to find valid question and answer faces");
         let (
             (_question_index, question_face, problem_question_face),
             (_answer_index, answer_face, problem_answer_face),
-        ) = match self.faces.as_ref() {
-            Some(faces) => {
-                let question = possible_faces
-                    .clone()
-                    .into_iter_shuffled(self.rng)
-                    .find(|(_, face, _)| faces.iter().any(|specified| face == &specified))
-                    .expect("Unable to find a valid question face");
-
-                let (question_index, _, _) = question;
-
-                //TODO: Abilitiy to specify answer faces as well?
-                let answer = possible_faces
-                    .into_iter_shuffled(self.rng)
-                    .find(|(i, _, _)| *i != question_index)
-                    .expect("Unable to find a valid answer face");
-
-                (question, answer)
-            }
-            None => possible_faces
-                .into_iter_shuffled(self.rng)
-                .collect::<OptionTuple<_>>()
-                .expect("Unable to find valid question and answer faces"),
-        };
+        ) = pair;
 
-        let mut seen_faces = Vec::with_capacity(ANSWERS_PER_PROBLEM);
+        let mut seen_faces = Vec::with_capacity(self.choices);
         seen_faces.push(problem_answer_face);
 
-        let mut answer_cards = Vec::with_capacity(ANSWERS_PER_PROBLEM);
+        let mut answer_cards = Vec::with_capacity(self.choices);
         answer_cards.push((
             (problem_answer_face, *problem_deck_card, problem_index),
             true,
@@ -111,15 +179,11 @@ impl<'a> Iterator for MatchProblemIterator<'a> {
             .clone()
             .into_iter_shuffled(self.rng)
             .filter_map(|((deck_card, _), card_index)| {
-                let card_answer_face =
-                    deck_card
-                        .deck
-                        .faces
-                        .iter()
-                        .enumerate()
-                        .find_map(|(i, face)| {
-                            (face == answer_face).and_then(|| deck_card.card[i].as_ref())
-                        })?;
+                let card_answer_face = distractor_face(
+                    &deck_card,
+                    (question_face, problem_question_face),
+                    answer_face,
+                )?;
 
                 if seen_faces.contains(&card_answer_face) {
                     return None;
@@ -127,32 +191,12 @@ impl<'a> Iterator for MatchProblemIterator<'a> {
                     seen_faces.push(card_answer_face);
                 }
 
-                let card_question_face_matches_problem = {
-                    let card_question_face =
-                        deck_card
-                            .deck
-                            .faces
-                            .iter()
-                            .enumerate()
-                            .find_map(|(i, face)| {
-                                (face == question_face).and_then(|| deck_card[i].as_ref())
-                            });
-
-                    card_question_face
-                        .map(|card_question_face| card_question_face == problem_question_face)
-                        .unwrap_or(false)
-                };
-
-                if card_question_face_matches_problem {
-                    return None;
-                }
-
                 Some(((card_answer_face, deck_card, card_index), false))
             })
-            .take(ANSWERS_PER_PROBLEM - 1)
+            .take(self.choices - 1)
             .for_each(|answer_card| answer_cards.push(answer_card));
 
-        if answer_cards.len() < ANSWERS_PER_PROBLEM {
+        if answer_cards.len() < self.choices {
             let deck_name = &problem_deck_card.deck.name;
             return Some(Err(FlashrError::DeckMismatch(format!("Cannot find enough answers for question {problem_question_face}, which is a \"{question_face}\" face, from deck {deck_name}, given answer face \"{answer_face}\""))));
         }
@@ -172,7 +216,7 @@ impl<'a> Iterator for MatchProblemIterator<'a> {
                 index: problem_index,
             },
             answers: {
-                let mut buf = Vec::with_capacity(ANSWERS_PER_PROBLEM);
+                let mut buf = Vec::with_capacity(self.choices);
                 for ((answer_face, answer_deck_card, answer_index), correct) in answer_cards {
                     buf.push((
                         PromptCard {
@@ -186,25 +230,169 @@ impl<'a> Iterator for MatchProblemIterator<'a> {
                 buf
             },
             answer_index,
+            hints: hints(problem_deck_card.card, problem_answer_face),
+            readings: problem_deck_card.shows_readings(&pair),
             weights: self.line.then(|| self.weighted_deck_cards.weights()),
         }))
     }
 }
 
+///Gets the answer face of the card if it can be used as a distractor for a problem
+///with the given question, which is the case when the card has the answer face,
+///and doesn't share the problem's question face (eg 海 and 皆 both being カイ)
+fn distractor_face<'a>(
+    deck_card: &DeckCard<'a>,
+    (question_face, problem_question_face): (&String, &Face),
+    answer_face: &String,
+) -> Option<&'a Face> {
+    let face_for = |name: &String| {
+        deck_card
+            .deck
+            .faces
+            .iter()
+            .enumerate()
+            .find_map(|(i, face)| (face == name).and_then(|| deck_card.card[i].as_ref()))
+    };
+
+    let card_answer_face = face_for(answer_face)?;
+
+    if face_for(question_face).is_some_and(|face| face == problem_question_face) {
+        return None;
+    }
+
+    Some(card_answer_face)
+}
+
+///Gets the text deleted by the card's cloze if it can be used as a distractor for the
+///problem's cloze, which is the case when it's a cloze of the same face of another card
+fn cloze_distractor(deck_card: &DeckCard, problem_deck_card: &DeckCard) -> Option<Face> {
+    let ((_, face, _), answer) = deck_card.cloze_entry()?;
+    let ((_, problem_face, _), _) = problem_deck_card.cloze_entry()?;
+
+    (face == problem_face && !std::ptr::eq(deck_card.card, problem_deck_card.card))
+        .then_some(answer)
+}
+
+///Simulates problem generation for every question and answer face pair of every card,
+///to ensure that each could find enough distinct distractors, reporting all that can't.
+pub(super) fn validate_match_problems(
+    deck_cards: &[DeckCard],
+    faces: &FaceSelection,
+    choices: usize,
+) -> Result<(), ArgError> {
+    let needed = choices - 1;
+    let mut errors = vec![];
+
+    for problem_deck_card in deck_cards {
+        if let Some(((_, question_face, _), problem_answer)) = problem_deck_card.cloze_entry() {
+            let mut seen_answers = Vec::with_capacity(choices);
+            seen_answers.push(problem_answer);
+
+            for deck_card in deck_cards {
+                if seen_answers.len() > needed {
+                    break;
+                }
+
+                if let Some(card_answer) = cloze_distractor(deck_card, problem_deck_card) {
+                    if !seen_answers.contains(&card_answer) {
+                        seen_answers.push(card_answer);
+                    }
+                }
+            }
+
+            let available = seen_answers.len() - 1;
+            if available < needed {
+                let number = problem_deck_card.cloze.map_or(0, |cloze| cloze.number);
+                errors.push(ArgError::ImpossibleMatchProblem {
+                    deck: problem_deck_card.deck.name.clone(),
+                    card: problem_deck_card.front_string(),
+                    direction: format!("{question_face} (cloze {number})"),
+                    available,
+                    needed,
+                });
+            }
+            continue;
+        }
+
+        for ((_, question_face, problem_question_face), (_, answer_face, problem_answer_face)) in
+            problem_deck_card.possible_pairs(faces)
+        {
+            let mut seen_faces = Vec::with_capacity(choices);
+            seen_faces.push(problem_answer_face);
+
+            for deck_card in deck_cards {
+                if seen_faces.len() > needed {
+                    break;
+                }
+
+                if let Some(card_answer_face) = distractor_face(
+                    deck_card,
+                    (question_face, problem_question_face),
+                    answer_face,
+                ) {
+                    if !seen_faces.contains(&card_answer_face) {
+                        seen_faces.push(card_answer_face);
+                    }
+                }
+            }
+
+            let available = seen_faces.len() - 1;
+
+            if available < needed {
+                errors.push(ArgError::ImpossibleMatchProblem {
+                    deck: problem_deck_card.deck.name.clone(),
+                    card: problem_deck_card.front_string(),
+                    direction: format!("{question_face}->{answer_face}"),
+                    available,
+                    needed,
+                });
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ArgError::from_errors(errors))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{deck::load_decks, stats::Stats, ModeArguments};
+    use std::path::Path;
 
-    use super::MatchProblemIterator;
+    use crate::{
+        cli::DEFAULT_CHOICES,
+        deck::{load_decks, parse_deck, plain_text},
+        stats::Stats,
+        tags::TagFilter,
+        ArgError, FaceSelection, ModeArguments,
+    };
+
+    use super::{validate_match_problems, MatchProblemIterator};
 
     #[test]
     fn ensure_unique_question_answers() {
         let decks = load_decks(vec!["./tests/deck1.json"]).expect("Unable to load test deck");
-        let args = ModeArguments::new(&decks, None, None, false);
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
         let rng = &mut rand::thread_rng();
         let stats = &mut Stats::new("");
-        let problems =
-            MatchProblemIterator::new(args.deck_cards, stats, args.faces, args.line, rng);
+        let problems = MatchProblemIterator::new(
+            args.deck_cards,
+            stats,
+            args.faces,
+            args.choices,
+            args.line,
+            rng,
+        );
 
         for problem in problems.take(1000) {
             let problem = problem.expect("Unable to get problem");
@@ -233,19 +421,233 @@ mod test {
         }
     }
 
+    #[test]
+    fn generates_requested_number_of_choices() {
+        let decks = load_decks(vec!["./tests/deck1.json"]).expect("Unable to load test deck");
+        let rng = &mut rand::thread_rng();
+        let stats = &mut Stats::new("");
+
+        for choices in 2..=3 {
+            let args = ModeArguments::new(
+                &decks,
+                None,
+                FaceSelection::default(),
+                &TagFilter::default(),
+                choices,
+                false,
+            )
+            .expect("Unable to create mode arguments");
+            let problems = MatchProblemIterator::new(
+                args.deck_cards,
+                stats,
+                args.faces,
+                args.choices,
+                args.line,
+                rng,
+            );
+
+            for problem in problems.take(100) {
+                let problem = problem.expect("Unable to get problem");
+                assert_eq!(problem.answers.len(), choices);
+                assert!(problem.answer_index < choices);
+            }
+        }
+    }
+
+    #[test]
+    fn uses_selected_direction() {
+        let decks = load_decks(vec!["./tests/deck1.json"]).expect("Unable to load test deck");
+        let faces = FaceSelection::new(
+            None,
+            None,
+            vec!["Kanji->English".parse().expect("Unable to parse direction")],
+        );
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            faces,
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+        let rng = &mut rand::thread_rng();
+        let stats = &mut Stats::new("");
+        let problems = MatchProblemIterator::new(
+            args.deck_cards,
+            stats,
+            args.faces,
+            args.choices,
+            args.line,
+            rng,
+        );
+
+        for problem in problems.take(100) {
+            let problem = problem.expect("Unable to get problem");
+            let card = problem.question.deck_card;
+            assert!(card[0]
+                .as_ref()
+                .is_some_and(|kanji| kanji.join() == problem.question.prompt));
+            assert!(problem.answers.iter().all(|(answer, _)| answer.deck_card[2]
+                .as_ref()
+                .is_some_and(|english| english.join() == answer.prompt)));
+        }
+    }
+
+    #[test]
+    fn validates_all_match_problems() {
+        let decks = load_decks(vec!["./tests/deck1.json"]).expect("Unable to load test deck");
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+        assert!(validate_match_problems(&args.deck_cards, &args.faces, args.choices).is_ok());
+
+        let decks = load_decks(vec!["./tests/duplicate_cards"])
+            .expect("Unable to load duplicate cards test deck");
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+        assert!(
+            validate_match_problems(&args.deck_cards, &args.faces, args.choices).is_err_and(
+                //4 cards, each with 6 question and answer face pairs
+                |err| matches!(err, ArgError::Multiple(errors) if errors.len() == 4 * 6)
+            )
+        );
+    }
+
     #[test]
     fn fails_if_not_enough_unique_answers() {
         let decks = load_decks(vec!["./tests/duplicate_cards"])
             .expect("Unable to load duplicate cards test deck");
-        let args = ModeArguments::new(&decks, None, None, false);
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
         let rng = &mut rand::thread_rng();
         let stats = &mut Stats::new("");
-        let mut problems =
-            MatchProblemIterator::new(args.deck_cards, stats, args.faces, args.line, rng);
+        let mut problems = MatchProblemIterator::new(
+            args.deck_cards,
+            stats,
+            args.faces,
+            args.choices,
+            args.line,
+            rng,
+        );
 
         assert!(problems
             .next()
             .is_some_and(|problem| problem
                 .is_err_and(|err| matches!(err, crate::FlashrError::DeckMismatch(_)))));
     }
+
+    #[test]
+    fn styled_answers_are_not_both_choices() {
+        let json = r#"{"name": "Styled", "faces": ["Front", "Back"], "cards": [
+            ["One", "**dog**"], ["Two", "dog"], ["Three", "_cat_"], ["Four", "bird"]]}"#;
+        let decks =
+            vec![parse_deck(Path::new("styled.json"), json).expect("Unable to parse test deck")];
+        let faces = FaceSelection::new(
+            Some(vec!["Front".to_owned()]),
+            Some(vec!["Back".to_owned()]),
+            vec![],
+        );
+        let args = ModeArguments::new(&decks, None, faces, &TagFilter::default(), 3, false)
+            .expect("Unable to create mode arguments");
+        let rng = &mut rand::thread_rng();
+        let stats = &mut Stats::new("");
+        let problems = MatchProblemIterator::new(
+            args.deck_cards,
+            stats,
+            args.faces,
+            args.choices,
+            args.line,
+            rng,
+        );
+
+        for problem in problems.take(20) {
+            let problem = problem.expect("Unable to get problem");
+            let mut answers = problem
+                .answers
+                .iter()
+                .map(|(answer, _)| plain_text(&answer.prompt))
+                .collect::<Vec<_>>();
+            answers.sort();
+            assert_eq!(answers, vec!["bird", "cat", "dog"]);
+        }
+    }
+
+    #[test]
+    fn quizzes_clozes() {
+        let decks = load_decks(vec!["./tests/cloze.json"]).expect("Unable to load test deck");
+        let new_args = |choices| {
+            ModeArguments::new(
+                &decks,
+                None,
+                FaceSelection::default(),
+                &TagFilter::default(),
+                choices,
+                false,
+            )
+            .expect("Unable to create mode arguments")
+        };
+
+        let args = new_args(DEFAULT_CHOICES);
+        assert!(validate_match_problems(&args.deck_cards, &args.faces, args.choices).is_ok());
+
+        let rng = &mut rand::thread_rng();
+        let stats = &mut Stats::new("");
+        let problems = MatchProblemIterator::new(
+            args.deck_cards,
+            stats,
+            args.faces,
+            args.choices,
+            args.line,
+            rng,
+        );
+
+        for problem in problems.take(100) {
+            let problem = problem.expect("Unable to get problem");
+            let question = problem.question.deck_card;
+            let (_, answer) = question.cloze_entry().expect("Problem isn't a cloze");
+
+            assert!(
+                problem.question.prompt.contains('[') && !problem.question.prompt.contains("{{")
+            );
+            assert!(problem.answers[problem.answer_index]
+                .0
+                .prompt
+                .split(", ")
+                .all(|part| answer.contains(&plain_text(part))));
+            //NOTE: Distractors are clozes of other cards
+            assert!(problem
+                .answers
+                .iter()
+                .all(|(answer, correct)| *correct
+                    || !std::ptr::eq(answer.deck_card.card, question.card)));
+        }
+
+        //NOTE: Only the birds and water cards have 7 other distinct cloze answers
+        let args = new_args(8);
+        assert!(
+            validate_match_problems(&args.deck_cards, &args.faces, args.choices)
+                .is_err_and(|err| matches!(err, ArgError::Multiple(errors) if errors.len() == 6))
+        );
+    }
 }
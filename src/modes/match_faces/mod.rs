@@ -19,12 +19,12 @@
 
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind};
 
-use iter::MatchProblemIterator;
+pub(crate) use iter::MatchProblemIterator;
 use widget::{MatchProblemWidget, MatchProblemWidgetState};
 
 use crate::{
-    event::clear_and_match_event, stats::Stats, terminal::TerminalWrapper, FlashrError,
-    ModeArguments, Progress, PromptCard,
+    event::clear_and_match_event, stats::Stats, terminal::TerminalWrapper, ArgError, FaceSelection,
+    FlashrError, ModeArguments, Progress, PromptCard,
 };
 
 use super::flashcards::show_flashcards;
@@ -32,12 +32,16 @@ use super::flashcards::show_flashcards;
 mod iter;
 mod widget;
 
-const ANSWERS_PER_PROBLEM: usize = 4;
+///Keys which, when pressed with shift, map to the number keys 1 through 9
+const SHIFTED_NUMBER_KEYS: [char; 9] = ['!', '@', '#', '$', '%', '^', '&', '*', '('];
 
-struct MatchProblem<'a> {
-    question: PromptCard<'a>,
-    answers: Vec<(PromptCard<'a>, bool)>,
+pub(crate) struct MatchProblem<'a> {
+    pub(crate) question: PromptCard<'a>,
+    pub(crate) answers: Vec<(PromptCard<'a>, bool)>,
     answer_index: usize,
+    hints: Vec<String>,
+    ///Whether readings are shown before answering, toggled by the user
+    pub(crate) readings: bool,
     weights: Option<Vec<f64>>,
 }
 
@@ -45,20 +49,34 @@ struct Quit;
 
 enum MatchResult<'a, 'b> {
     Correct(&'b PromptCard<'a>),
+    ///Correct, but only after revealing hints
+    Hinted(&'b PromptCard<'a>),
     Incorrect {
         q: &'b PromptCard<'a>,
         a: &'b PromptCard<'a>,
     },
 }
 
+///Ensures that match problems can be generated for every selected card before starting
+pub fn validate_match_faces(args: &ModeArguments) -> Result<(), ArgError> {
+    args.validate_choices()?;
+    iter::validate_match_problems(&args.deck_cards, &args.faces, args.choices)
+}
+
 pub fn match_faces(
     term: &mut TerminalWrapper,
     args: ModeArguments,
 ) -> Result<Progress, FlashrError> {
     let rng = &mut rand::thread_rng();
     let mut stats = Stats::load_from_user_home()?;
-    let mut problems =
-        MatchProblemIterator::new(args.deck_cards, &mut stats, args.faces, args.line, rng);
+    let mut problems = MatchProblemIterator::new(
+        args.deck_cards,
+        &mut stats,
+        args.faces,
+        args.choices,
+        args.line,
+        rng,
+    );
 
     fn update_correct(card: &PromptCard, stats: &mut Stats, problems: &mut MatchProblemIterator) {
         let stats = stats.for_card_mut(card);
@@ -66,6 +84,13 @@ pub fn match_faces(
         problems.change_weight(card.index, stats.weight());
     }
 
+    fn update_hinted(card: &PromptCard, stats: &mut Stats, problems: &mut MatchProblemIterator) {
+        let stats = stats.for_card_mut(card);
+        stats.correct += 1;
+        stats.hinted += 1;
+        problems.change_weight(card.index, stats.weight());
+    }
+
     fn update_incorrect(card: &PromptCard, stats: &mut Stats, problems: &mut MatchProblemIterator) {
         let stats = stats.for_card_mut(card);
         stats.incorrect += 1;
@@ -86,6 +111,10 @@ pub fn match_faces(
                         update_correct(card, &mut stats, &mut problems);
                         progress.add_correct();
                     }
+                    MatchResult::Hinted(card) => {
+                        update_hinted(card, &mut stats, &mut problems);
+                        progress.add_correct();
+                    }
                     MatchResult::Incorrect { q, a } => {
                         update_incorrect(q, &mut stats, &mut problems);
                         update_incorrect(a, &mut stats, &mut problems);
@@ -111,16 +140,32 @@ fn show_match_problem<'a, 'b>(
     problem: &'b MatchProblem<'a>,
     progress: Progress,
 ) -> Result<MatchProblemResult<'a, 'b>, FlashrError> {
-    let widget_state = &mut MatchProblemWidgetState::default();
+    let widget_state = &mut MatchProblemWidgetState::new(problem.answers.len());
+    let mut hints_revealed = 0;
+    let mut readings = problem.readings;
 
     loop {
-        term.render_stateful_widget(MatchProblemWidget::new(problem, &progress), widget_state)?;
+        term.render_stateful_widget(
+            MatchProblemWidget::new(problem, &progress)
+                .hints(&problem.hints[..hints_revealed])
+                .readings(readings),
+            widget_state,
+        )?;
 
         let input = clear_and_match_event(|event| match_user_input(event, widget_state))?;
         match input {
             UserInput::Answer(index_answered) => {
-                return show_match_problem_result(term, problem, progress, index_answered)
+                return show_match_problem_result(
+                    term,
+                    problem,
+                    progress,
+                    (index_answered, hints_revealed),
+                )
             }
+            UserInput::Hint => hints_revealed = (hints_revealed + 1).min(problem.hints.len()),
+            UserInput::ToggleReadings => readings = !readings,
+            UserInput::ScrollUp => widget_state.scroll.up(),
+            UserInput::ScrollDown => widget_state.scroll.down(),
             UserInput::Resize | UserInput::EnterFlashcard(_) => continue,
             UserInput::Quit => return Ok(Err(Quit)),
         }
@@ -131,21 +176,25 @@ fn show_match_problem_result<'a, 'b>(
     term: &mut TerminalWrapper,
     problem: &'b MatchProblem<'a>,
     progress: Progress,
-    index_answered: usize,
+    (index_answered, hints_revealed): (usize, usize),
 ) -> Result<MatchProblemResult<'a, 'b>, FlashrError> {
     let correct = index_answered == problem.answer_index;
-    let widget_state = &mut MatchProblemWidgetState::default();
+    let widget_state = &mut MatchProblemWidgetState::new(problem.answers.len());
 
     loop {
         term.render_stateful_widget(
-            MatchProblemWidget::new(problem, &progress).answered((index_answered, correct)),
+            MatchProblemWidget::new(problem, &progress)
+                .answered((index_answered, correct))
+                .hints(&problem.hints[..hints_revealed]),
             widget_state,
         )?;
 
         let input = clear_and_match_event(|event| match_user_input(event, widget_state))?;
         match input {
             UserInput::Answer(answer) if answer == problem.answer_index => {
-                return Ok(Ok(if correct {
+                return Ok(Ok(if correct && hints_revealed > 0 {
+                    MatchResult::Hinted(&problem.question)
+                } else if correct {
                     MatchResult::Correct(&problem.question)
                 } else {
                     MatchResult::Incorrect {
@@ -168,6 +217,7 @@ fn show_match_problem_result<'a, 'b>(
                             .iter()
                             .map(|(card, _)| card.deck_card)
                             .collect(),
+                        &FaceSelection::default(),
                     )?;
                 }
                 Some(specific_index) => {
@@ -181,10 +231,16 @@ fn show_match_problem_result<'a, 'b>(
                                 (specific_index == i).then_some(card.deck_card)
                             })
                             .collect(),
+                        &FaceSelection::default(),
                     )?;
                 }
             },
-            UserInput::Answer(_) | UserInput::Resize => continue,
+            UserInput::ScrollUp => widget_state.scroll.up(),
+            UserInput::ScrollDown => widget_state.scroll.down(),
+            UserInput::Answer(_)
+            | UserInput::Hint
+            | UserInput::ToggleReadings
+            | UserInput::Resize => continue,
             UserInput::Quit => return Ok(Err(Quit)),
         }
     }
@@ -193,6 +249,10 @@ fn show_match_problem_result<'a, 'b>(
 enum UserInput {
     Answer(usize),
     EnterFlashcard(Option<usize>),
+    Hint,
+    ToggleReadings,
+    ScrollUp,
+    ScrollDown,
     Resize,
     Quit,
 }
@@ -204,19 +264,36 @@ fn match_user_input(event: Event, state: &MatchProblemWidgetState) -> Option<Use
             code,
             ..
         }) => match code {
-            KeyCode::Char('1') => Some(UserInput::Answer(0)),
-            KeyCode::Char('2') => Some(UserInput::Answer(1)),
-            KeyCode::Char('3') => Some(UserInput::Answer(2)),
-            KeyCode::Char('4') => Some(UserInput::Answer(3)),
             KeyCode::Enter => Some(UserInput::EnterFlashcard(None)),
-            KeyCode::Char('!') => Some(UserInput::EnterFlashcard(Some(0))),
-            KeyCode::Char('@') => Some(UserInput::EnterFlashcard(Some(1))),
-            KeyCode::Char('#') => Some(UserInput::EnterFlashcard(Some(2))),
-            KeyCode::Char('$') => Some(UserInput::EnterFlashcard(Some(3))),
             KeyCode::Esc | KeyCode::Char('q') => Some(UserInput::Quit),
+            KeyCode::Tab | KeyCode::Char('h') => Some(UserInput::Hint),
+            KeyCode::Char('r') => Some(UserInput::ToggleReadings),
+            KeyCode::Up | KeyCode::PageUp => Some(UserInput::ScrollUp),
+            KeyCode::Down | KeyCode::PageDown => Some(UserInput::ScrollDown),
+            KeyCode::Char(char) => {
+                let choices = state.answer_areas.len();
+
+                if let Some(index) = SHIFTED_NUMBER_KEYS.iter().position(|key| *key == char) {
+                    (index < choices).then_some(UserInput::EnterFlashcard(Some(index)))
+                } else {
+                    char.to_digit(10).and_then(|digit| {
+                        //NOTE: Subbing one so that '1' is the first answer
+                        let index = (digit as usize).checked_sub(1)?;
+                        (index < choices).then_some(UserInput::Answer(index))
+                    })
+                }
+            }
             _ => None,
         },
         Event::Resize(_, _) => Some(UserInput::Resize),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::ScrollUp,
+            ..
+        }) => Some(UserInput::ScrollUp),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::ScrollDown,
+            ..
+        }) => Some(UserInput::ScrollDown),
         Event::Mouse(MouseEvent {
             kind: MouseEventKind::Up(_),
             column,
@@ -21,19 +21,27 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style, Stylize},
     symbols::{border, line},
-    widgets::{
-        Bar, BarChart, BarGroup, Block, Borders, Gauge, Paragraph, StatefulWidget, Widget, Wrap,
-    },
+    text::Line,
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Paragraph, StatefulWidget, Widget, Wrap},
 };
 
-use crate::{color::LinearGradient, Progress};
+use crate::{
+    color::LinearGradient,
+    render_utils::{
+        info_lines, progress_gauge, prompt_with_hints, ruby_text, Scroll, COLOR_CORRECT,
+        COLOR_INCORRECT,
+    },
+    Progress,
+};
 
-use super::{MatchProblem, ANSWERS_PER_PROBLEM};
+use super::MatchProblem;
 
 pub(super) struct MatchProblemWidget<'a> {
     problem: &'a MatchProblem<'a>,
     progress: &'a Progress,
     answer: Option<(usize, bool)>,
+    hints: &'a [String],
+    readings: bool,
 }
 
 impl<'a> MatchProblemWidget<'a> {
@@ -42,6 +50,8 @@ impl<'a> MatchProblemWidget<'a> {
             problem,
             progress,
             answer: None,
+            hints: &[],
+            readings: true,
         }
     }
 
@@ -49,23 +59,42 @@ impl<'a> MatchProblemWidget<'a> {
         self.answer = Some(answer);
         self
     }
+
+    ///Whether to show the readings of annotated text before the problem is answered
+    pub(super) fn readings(mut self, readings: bool) -> Self {
+        self.readings = readings;
+        self
+    }
+
+    ///Shows the hints revealed so far beneath the question
+    pub(super) fn hints(mut self, hints: &'a [String]) -> Self {
+        self.hints = hints;
+        self
+    }
 }
 
-#[repr(transparent)]
 pub(super) struct MatchProblemWidgetState {
     pub(super) answer_areas: Vec<Rect>,
+    ///Scroll of the question, which may be followed by long info faces once answered
+    pub(super) scroll: Scroll,
 }
 
-impl Default for MatchProblemWidgetState {
-    fn default() -> Self {
+impl MatchProblemWidgetState {
+    pub(super) fn new(choices: usize) -> Self {
         Self {
-            answer_areas: [Rect::default()].repeat(ANSWERS_PER_PROBLEM),
+            answer_areas: [Rect::default()].repeat(choices),
+            scroll: Scroll::default(),
         }
     }
 }
 
-const COLOR_CORRECT: Color = Color::Green;
-const COLOR_INCORRECT: Color = Color::Red;
+///Returns the (columns, rows) of the answer grid for the given number of choices.
+///Columns are preferred over rows so that 4 answers fit in a 2x2 grid, 6 in 3x2, etc.
+fn grid_size(choices: usize) -> (usize, usize) {
+    let columns = (choices as f64).sqrt().ceil() as usize;
+    let rows = choices.div_ceil(columns);
+    (columns, rows)
+}
 
 impl StatefulWidget for MatchProblemWidget<'_> {
     type State = MatchProblemWidgetState;
@@ -112,34 +141,41 @@ impl StatefulWidget for MatchProblemWidget<'_> {
                 };
 
             let (answer_areas, divider_areas) = {
-                let (answer_top, answer_bot) = {
-                    let layout = Layout::new(Direction::Vertical, [Constraint::Ratio(1, 2); 2]);
-                    let split = layout.split(answer_area);
-                    (split[0], split[1])
+                let (columns, rows) = grid_size(self.problem.answers.len());
+
+                let row_areas = {
+                    let layout = Layout::new(
+                        Direction::Vertical,
+                        (0..rows).map(|_| Constraint::Ratio(1, rows as u32)),
+                    );
+                    layout.split(answer_area)
                 };
 
                 let layout = Layout::new(
                     Direction::Horizontal,
-                    [
-                        Constraint::Ratio(1, 2),
-                        Constraint::Min(1),
-                        Constraint::Ratio(1, 2),
-                    ],
+                    (0..columns).flat_map(|column| {
+                        (column != 0)
+                            .then_some(Constraint::Length(1))
+                            .into_iter()
+                            .chain([Constraint::Fill(1)])
+                    }),
                 );
 
-                let (top_left, divider_top, top_right) = {
-                    let split = layout.split(answer_top);
-                    (split[0], split[1], split[2])
-                };
-                let (bot_left, divider_bot, bot_right) = {
-                    let split = layout.split(answer_bot);
-                    (split[0], split[1], split[2])
-                };
+                let mut answer_areas = Vec::with_capacity(columns * rows);
+                let mut divider_areas = Vec::with_capacity((columns - 1) * rows);
 
-                (
-                    [top_left, top_right, bot_left, bot_right],
-                    (divider_top, divider_bot),
-                )
+                for row_area in row_areas.iter() {
+                    let split = layout.split(*row_area);
+                    for (i, area) in split.iter().enumerate() {
+                        if i % 2 == 0 {
+                            answer_areas.push(*area);
+                        } else {
+                            divider_areas.push(*area);
+                        }
+                    }
+                }
+
+                (answer_areas, (columns, divider_areas))
             };
 
             (
@@ -151,22 +187,50 @@ impl StatefulWidget for MatchProblemWidget<'_> {
             )
         };
 
-        let question = Paragraph::new(self.problem.question.prompt.to_owned())
-            .wrap(Wrap { trim: false })
-            .centered();
-
-        let divider_top = Block::new()
-            .borders(Borders::RIGHT | Borders::TOP)
-            .border_set(border::Set {
-                top_right: line::DOUBLE_HORIZONTAL_DOWN,
-                ..border::DOUBLE
-            });
-        let divider_bot = Block::new()
-            .borders(Borders::RIGHT | Borders::TOP)
-            .border_set(border::Set {
-                top_right: line::DOUBLE_CROSS,
-                ..border::DOUBLE
-            });
+        //NOTE: Readings are always shown once answered, as there's nothing left to give away
+        let readings = self.readings || self.answer.is_some();
+
+        let question = {
+            let width = question_area.width;
+            let prompt = ruby_text(&self.problem.question.prompt, width, readings);
+            let mut text = prompt_with_hints(prompt, self.hints);
+            let info_faces = self.problem.question.deck_card.info_faces();
+            if self.answer.is_some() && !info_faces.is_empty() {
+                text.push_line(Line::default());
+                text.extend(info_lines(&info_faces, width));
+            }
+            let scroll = state.scroll.clamp(&text, question_area);
+
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .centered()
+                .scroll(scroll)
+        };
+
+        let (columns, divider_areas) = divider_areas;
+        let dividers = divider_areas.iter().enumerate().map(|(i, area)| {
+            let row = i / (columns - 1);
+            let column = i % (columns - 1);
+
+            let divider = Block::new()
+                .borders(Borders::RIGHT | Borders::TOP)
+                .border_set(border::Set {
+                    top_right: if row == 0 {
+                        line::DOUBLE_HORIZONTAL_DOWN
+                    } else {
+                        line::DOUBLE_CROSS
+                    },
+                    ..border::DOUBLE
+                });
+
+            //Answer indices either side of the divider
+            let left = row * columns + column;
+            (divider, (left, left + 1), *area)
+        });
+        let empty_answer_areas = answer_areas.iter().skip(self.problem.answers.len());
+        let empty_answer = Block::bordered()
+            .borders(Borders::TOP)
+            .border_set(border::DOUBLE);
 
         if let Some((weights, line_area)) = weights_area {
             WeightLineWidget::new(
@@ -191,11 +255,13 @@ impl StatefulWidget for MatchProblemWidget<'_> {
                     state.answer_areas[answer_index] = answer_area;
 
                     MatchAnswerWidget::new(answer.prompt.to_owned(), answer_index)
+                        .readings(readings)
                         .render(answer_area, buf)
                 }
 
-                divider_top.render(divider_areas.0, buf);
-                divider_bot.render(divider_areas.1, buf);
+                for (divider, _, area) in dividers {
+                    divider.render(area, buf);
+                }
             }
             Some((answered_index, correct)) => {
                 {
@@ -214,12 +280,14 @@ impl StatefulWidget for MatchProblemWidget<'_> {
                     let answer_area = answer_areas[answer_index];
                     state.answer_areas[answer_index] = answer_area;
 
-                    MatchAnswerWidget::new(answer.deck_card.join("\n"), answer_index)
+                    MatchAnswerWidget::new(answer.deck_card.join_quizzed("\n"), answer_index)
                         .answered((*is_correct, is_answered))
                         .render(answer_area, buf)
                 }
 
-                let color_for_divider = |index_test: fn(usize) -> bool| -> Color {
+                let color_for_divider = |(left, right): (usize, usize)| -> Color {
+                    let index_test = |index: usize| index == left || index == right;
+
                     if index_test(answered_index) {
                         if correct {
                             COLOR_CORRECT
@@ -233,25 +301,17 @@ impl StatefulWidget for MatchProblemWidget<'_> {
                     }
                 };
 
-                divider_top
-                    .fg(color_for_divider(|index| index < 2))
-                    .render(divider_areas.0, buf);
-                divider_bot
-                    .fg(color_for_divider(|index| index >= 2))
-                    .render(divider_areas.1, buf);
+                for (divider, adjacent, area) in dividers {
+                    divider.fg(color_for_divider(adjacent)).render(area, buf);
+                }
             }
         }
 
-        {
-            let (ratio, percent) = self.progress.ratio_percent();
-            let Progress { correct, total } = self.progress;
-            Gauge::default()
-                .ratio(ratio)
-                .label(format!("{percent:05.2}% ({correct}/{total})"))
-                .gauge_style(Style::default().fg(COLOR_CORRECT).bg(COLOR_INCORRECT))
-                .use_unicode(true)
-                .render(progress_area, buf);
+        for area in empty_answer_areas {
+            empty_answer.clone().render(*area, buf);
         }
+
+        progress_gauge(self.progress).render(progress_area, buf);
     }
 }
 
@@ -259,6 +319,7 @@ struct MatchAnswerWidget {
     answer: String,
     answer_index: usize,
     outcome: Option<(bool, bool)>,
+    readings: bool,
 }
 
 impl MatchAnswerWidget {
@@ -267,9 +328,15 @@ impl MatchAnswerWidget {
             answer,
             answer_index,
             outcome: None,
+            readings: true,
         }
     }
 
+    fn readings(mut self, readings: bool) -> Self {
+        self.readings = readings;
+        self
+    }
+
     fn answered(mut self, outcome: (bool, bool)) -> Self {
         self.outcome = Some(outcome);
         self
@@ -278,7 +345,8 @@ impl MatchAnswerWidget {
 
 impl Widget for MatchAnswerWidget {
     fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
-        Paragraph::new(format!("{}: {}", self.answer_index + 1, self.answer))
+        let answer = format!("{}: {}", self.answer_index + 1, self.answer);
+        Paragraph::new(ruby_text(&answer, area.width, self.readings))
             .wrap(Wrap { trim: false })
             .centered()
             .block(
@@ -19,4 +19,6 @@
 
 pub mod flashcards;
 pub mod match_faces;
+pub mod order_faces;
+pub mod true_false;
 pub mod type_faces;
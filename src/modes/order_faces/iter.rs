@@ -0,0 +1,192 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use rand::{rngs::ThreadRng, seq::SliceRandom};
+
+use crate::{
+    deck::plain_text, random::GetRandom, stats::Stats, weighted_list::WeightedList, ArgError,
+    DeckCard, FacePair, FaceSelection, ModeArguments, PromptCard,
+};
+
+use super::{in_place, OrderProblem};
+
+pub(super) struct OrderProblemIterator<'a> {
+    rng: &'a mut ThreadRng,
+    weighted_deck_cards: WeightedList<DeckCard<'a>>,
+    faces: FaceSelection,
+}
+
+impl<'a> OrderProblemIterator<'a> {
+    ///Iterates problems of the cards with subfaces to put in order, leaving out the rest
+    pub fn new(
+        deck_cards: Vec<DeckCard<'a>>,
+        stats: &mut Stats,
+        faces: FaceSelection,
+        rng: &'a mut ThreadRng,
+    ) -> Self {
+        Self {
+            rng,
+            weighted_deck_cards: {
+                let mut buf = WeightedList::with_capacity(deck_cards.len());
+                deck_cards
+                    .into_iter()
+                    .filter(|deck_card| !orderable_pairs(deck_card, &faces).is_empty())
+                    .for_each(|deck_card| {
+                        let weight = stats.for_card(&deck_card).weight();
+                        buf.add((deck_card, weight));
+                    });
+                buf
+            },
+            faces,
+        }
+    }
+
+    pub fn change_weight(&mut self, index: usize, weight: f64) {
+        self.weighted_deck_cards.change_weight(index, weight)
+    }
+}
+
+impl<'a> Iterator for OrderProblemIterator<'a> {
+    type Item = OrderProblem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (deck_card, index) = self.weighted_deck_cards.get_random(self.rng)?;
+
+        let pair = *orderable_pairs(deck_card, &self.faces)
+            .get_random(self.rng)
+            .expect("Unable to find question and answer faces to order");
+        let ((_, question_face, question), (_, answer_face, answer)) = pair;
+        let subfaces = answer.subfaces();
+
+        //NOTE: Reshuffled until out of order, which ends as the subfaces aren't all the same
+        let mut shuffled = (0..subfaces.len()).collect::<Vec<_>>();
+        while in_place(subfaces, &shuffled) == subfaces.len() {
+            shuffled.shuffle(self.rng);
+        }
+
+        Some(OrderProblem {
+            question: PromptCard {
+                prompt: question.join_random(self.rng),
+                deck_card: *deck_card,
+                index,
+            },
+            question_face,
+            answer_face,
+            subfaces,
+            shuffled,
+            readings: deck_card.shows_readings(&pair),
+        })
+    }
+}
+
+///Question and answer face pairs of the card allowed by the selection, whose answer has
+///more than one distinct subface, so that there's an order to put them in.
+///Clozes are left out, as they're answered by the text they delete.
+pub(super) fn orderable_pairs<'a>(
+    deck_card: &DeckCard<'a>,
+    faces: &FaceSelection,
+) -> Vec<FacePair<'a>> {
+    if deck_card.cloze.is_some() {
+        return vec![];
+    }
+
+    deck_card
+        .possible_pairs(faces)
+        .into_iter()
+        .filter(|(_, (_, _, answer))| {
+            answer.is_multi_and(|subfaces| {
+                subfaces
+                    .iter()
+                    .any(|subface| plain_text(subface) != plain_text(&subfaces[0]))
+            })
+        })
+        .collect()
+}
+
+///Ensures that at least one selected card has subfaces to put in order
+pub(super) fn validate_order_problems(args: &ModeArguments) -> Result<(), ArgError> {
+    if args
+        .deck_cards
+        .iter()
+        .any(|deck_card| !orderable_pairs(deck_card, &args.faces).is_empty())
+    {
+        Ok(())
+    } else {
+        Err(ArgError::NoOrderableFaces)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cli::DEFAULT_CHOICES, deck::load_decks, modes::order_faces::in_place, stats::Stats,
+        tags::TagFilter, ArgError, FaceSelection, ModeArguments,
+    };
+
+    use super::{validate_order_problems, OrderProblemIterator};
+
+    #[test]
+    fn shuffles_orderable_subfaces() {
+        let decks = load_decks(vec!["./tests/order.json"]).expect("Unable to load test deck");
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+        assert!(validate_order_problems(&args).is_ok());
+
+        let rng = &mut rand::thread_rng();
+        let stats = &mut Stats::new("");
+        let problems = OrderProblemIterator::new(args.deck_cards, stats, args.faces, rng);
+
+        for problem in problems.take(100) {
+            assert_eq!(problem.question_face, "Task");
+            assert_eq!(problem.answer_face, "Steps");
+            //NOTE: Cards with a single step, or only repeated steps, have nothing to order
+            assert!(problem.subfaces.len() >= 3);
+            assert!(in_place(problem.subfaces, &problem.shuffled) < problem.subfaces.len());
+
+            let mut shuffled = problem.shuffled.clone();
+            shuffled.sort_unstable();
+            assert!(shuffled.into_iter().eq(0..problem.subfaces.len()));
+        }
+    }
+
+    #[test]
+    fn fails_without_orderable_faces() {
+        let decks = load_decks(vec!["./tests/order.json"]).expect("Unable to load test deck");
+        let faces = FaceSelection::new(None, Some(vec!["Task".to_owned()]), vec![]);
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            faces,
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+
+        assert!(validate_order_problems(&args)
+            .is_err_and(|err| matches!(err, ArgError::NoOrderableFaces)));
+    }
+}
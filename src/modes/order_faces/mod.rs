@@ -0,0 +1,314 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind};
+
+use iter::OrderProblemIterator;
+use widget::{OrderProblemWidget, OrderProblemWidgetState};
+
+use crate::{
+    deck::plain_text, event::clear_and_match_event, stats::Stats, terminal::TerminalWrapper,
+    ArgError, FlashrError, ModeArguments, Progress, PromptCard,
+};
+
+mod iter;
+mod widget;
+
+struct OrderProblem<'a> {
+    question: PromptCard<'a>,
+    question_face: &'a String,
+    answer_face: &'a String,
+    ///Subfaces of the answer face, in their right order
+    subfaces: &'a [String],
+    ///Order the subfaces are first shown in, as indices into `subfaces`
+    shuffled: Vec<usize>,
+    ///Whether readings are shown before answering, toggled by the user
+    readings: bool,
+}
+
+struct Quit;
+
+///Number of subfaces put in their right place, given as indices into the subfaces. Subfaces
+///are compared by their text, so that repeated subfaces may be put in either place.
+fn in_place(subfaces: &[String], order: &[usize]) -> usize {
+    order
+        .iter()
+        .enumerate()
+        .filter(|(place, subface)| {
+            plain_text(&subfaces[**subface]) == plain_text(&subfaces[*place])
+        })
+        .count()
+}
+
+///Order the subfaces have been put in so far, along with which is highlighted,
+///and whether it's been grabbed to be moved
+struct Ordering {
+    order: Vec<usize>,
+    cursor: usize,
+    grabbed: bool,
+}
+
+impl Ordering {
+    fn new(order: Vec<usize>) -> Self {
+        Self {
+            order,
+            cursor: 0,
+            grabbed: false,
+        }
+    }
+
+    ///Moves the cursor up, along with the subface if grabbed
+    fn up(&mut self) {
+        if let Some(above) = self.cursor.checked_sub(1) {
+            self.move_to(above);
+        }
+    }
+
+    ///Moves the cursor down, along with the subface if grabbed
+    fn down(&mut self) {
+        if self.cursor + 1 < self.order.len() {
+            self.move_to(self.cursor + 1);
+        }
+    }
+
+    fn toggle_grab(&mut self) {
+        self.grabbed = !self.grabbed;
+    }
+
+    ///Grabs the subface at the place, as when pressing the mouse on it
+    fn grab(&mut self, place: usize) {
+        self.cursor = place;
+        self.grabbed = true;
+    }
+
+    fn release(&mut self) {
+        self.grabbed = false;
+    }
+
+    ///Moves the cursor to the place, moving the grabbed subface there too,
+    ///shifting those in between
+    fn move_to(&mut self, place: usize) {
+        if self.grabbed {
+            let subface = self.order.remove(self.cursor);
+            self.order.insert(place, subface);
+        }
+        self.cursor = place;
+    }
+}
+
+///Ensures that order problems can be generated for at least one selected card before starting
+pub fn validate_order_faces(args: &ModeArguments) -> Result<(), ArgError> {
+    iter::validate_order_problems(args)
+}
+
+pub fn order_faces(
+    term: &mut TerminalWrapper,
+    args: ModeArguments,
+) -> Result<Progress, FlashrError> {
+    let rng = &mut rand::thread_rng();
+    let mut stats = Stats::load_from_user_home()?;
+    let mut problems = OrderProblemIterator::new(args.deck_cards, &mut stats, args.faces, rng);
+
+    let mut progress = Progress::default();
+    let range = args.problem_count.map_or(0..usize::MAX, |count| 0..count);
+
+    for _ in range {
+        if let Some(problem) = problems.next() {
+            match show_order_problem(term, &problem, progress)? {
+                Ok(placed) => {
+                    let total = problem.subfaces.len();
+                    let card_stats = stats.for_card_mut(&problem.question);
+
+                    //NOTE: Only fully ordered subfaces count as correct for the card,
+                    //while every subface in its right place counts towards the score
+                    if placed == total {
+                        card_stats.correct += 1;
+                    } else {
+                        card_stats.incorrect += 1;
+                    }
+                    progress.add_partial(placed, total);
+
+                    problems.change_weight(problem.question.index, card_stats.weight());
+                }
+                Err(Quit) => break,
+            }
+        } else {
+            break;
+        }
+    }
+
+    stats.save_to_file()?;
+
+    Ok(progress)
+}
+
+///Number of subfaces put in their right place
+type OrderProblemResult = Result<usize, Quit>;
+
+fn show_order_problem(
+    term: &mut TerminalWrapper,
+    problem: &OrderProblem,
+    progress: Progress,
+) -> Result<OrderProblemResult, FlashrError> {
+    let ordering = &mut Ordering::new(problem.shuffled.clone());
+    let widget_state = &mut OrderProblemWidgetState::new(problem.subfaces.len());
+    let mut readings = problem.readings;
+
+    loop {
+        term.render_stateful_widget(
+            OrderProblemWidget::new(problem, ordering, &progress).readings(readings),
+            widget_state,
+        )?;
+
+        match clear_and_match_event(|event| match_order_input(event, widget_state))? {
+            OrderInput::Up => ordering.up(),
+            OrderInput::Down => ordering.down(),
+            OrderInput::Grab => ordering.toggle_grab(),
+            OrderInput::Press(place) => ordering.grab(place),
+            OrderInput::Drag(place) if ordering.grabbed => ordering.move_to(place),
+            OrderInput::Release => ordering.release(),
+            OrderInput::ToggleReadings => readings = !readings,
+            OrderInput::Submit => break,
+            OrderInput::Drag(_) | OrderInput::Resize => continue,
+            OrderInput::Quit => return Ok(Err(Quit)),
+        }
+    }
+
+    ordering.release();
+    let placed = in_place(problem.subfaces, &ordering.order);
+
+    loop {
+        term.render_stateful_widget(
+            OrderProblemWidget::new(problem, ordering, &progress).answered(),
+            widget_state,
+        )?;
+
+        match clear_and_match_event(|event| match_order_input(event, widget_state))? {
+            OrderInput::Submit | OrderInput::Grab => return Ok(Ok(placed)),
+            OrderInput::Quit => return Ok(Err(Quit)),
+            _ => continue,
+        }
+    }
+}
+
+enum OrderInput {
+    Up,
+    Down,
+    ///Grabs or releases the highlighted subface
+    Grab,
+    ///Mouse pressed on the subface at the place
+    Press(usize),
+    ///Mouse dragged over the subface at the place
+    Drag(usize),
+    Release,
+    ToggleReadings,
+    Submit,
+    Resize,
+    Quit,
+}
+
+fn match_order_input(event: Event, state: &OrderProblemWidgetState) -> Option<OrderInput> {
+    let place_at = |column, row| {
+        state
+            .subface_areas
+            .iter()
+            .position(|area| area.contains((column, row).into()))
+    };
+
+    match event {
+        Event::Key(KeyEvent {
+            kind: KeyEventKind::Press,
+            code,
+            ..
+        }) => match code {
+            KeyCode::Up | KeyCode::Char('k') => Some(OrderInput::Up),
+            KeyCode::Down | KeyCode::Char('j') => Some(OrderInput::Down),
+            KeyCode::Char(' ') => Some(OrderInput::Grab),
+            KeyCode::Char('r') => Some(OrderInput::ToggleReadings),
+            KeyCode::Enter => Some(OrderInput::Submit),
+            KeyCode::Esc | KeyCode::Char('q') => Some(OrderInput::Quit),
+            _ => None,
+        },
+        Event::Resize(_, _) => Some(OrderInput::Resize),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(_),
+            column,
+            row,
+            ..
+        }) => place_at(column, row).map(OrderInput::Press),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Drag(_),
+            column,
+            row,
+            ..
+        }) => place_at(column, row).map(OrderInput::Drag),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Up(_),
+            ..
+        }) => Some(OrderInput::Release),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{in_place, Ordering};
+
+    #[test]
+    fn scores_subfaces_in_place() {
+        let subfaces = ["Boil", "Steep", "Pour"].map(str::to_owned);
+        assert_eq!(in_place(&subfaces, &[0, 1, 2]), 3);
+        assert_eq!(in_place(&subfaces, &[0, 2, 1]), 1);
+        assert_eq!(in_place(&subfaces, &[2, 0, 1]), 0);
+
+        //NOTE: Repeated subfaces may be swapped, and markup is ignored
+        let subfaces = ["Stir", "**Wait**", "Stir", "Wait"].map(str::to_owned);
+        assert_eq!(in_place(&subfaces, &[2, 3, 0, 1]), 4);
+    }
+
+    #[test]
+    fn moves_grabbed_subfaces() {
+        let mut ordering = Ordering::new(vec![0, 1, 2, 3]);
+        ordering.down();
+        assert_eq!(
+            (ordering.order.as_slice(), ordering.cursor),
+            (&[0, 1, 2, 3][..], 1)
+        );
+
+        ordering.toggle_grab();
+        ordering.down();
+        ordering.down();
+        assert_eq!(
+            (ordering.order.as_slice(), ordering.cursor),
+            (&[0, 2, 3, 1][..], 3)
+        );
+        ordering.down();
+        assert_eq!(ordering.cursor, 3);
+
+        //NOTE: Dragging with the mouse shifts the subfaces in between
+        ordering.grab(3);
+        ordering.move_to(0);
+        ordering.release();
+        ordering.up();
+        assert_eq!(
+            (ordering.order.as_slice(), ordering.cursor),
+            (&[1, 0, 2, 3][..], 0)
+        );
+    }
+}
@@ -0,0 +1,236 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Stylize},
+    text::{Line, Text},
+    widgets::{block::Title, Block, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::{
+    deck::plain_text,
+    render_utils::{
+        info_lines, progress_gauge, ruby_text, COLOR_CORRECT, COLOR_HINT, COLOR_INCORRECT,
+    },
+    Progress,
+};
+
+use super::{in_place, OrderProblem, Ordering};
+
+pub(super) struct OrderProblemWidget<'a> {
+    problem: &'a OrderProblem<'a>,
+    ordering: &'a Ordering,
+    progress: &'a Progress,
+    answered: bool,
+    readings: bool,
+}
+
+impl<'a> OrderProblemWidget<'a> {
+    pub(super) fn new(
+        problem: &'a OrderProblem<'a>,
+        ordering: &'a Ordering,
+        progress: &'a Progress,
+    ) -> Self {
+        Self {
+            problem,
+            ordering,
+            progress,
+            answered: false,
+            readings: true,
+        }
+    }
+
+    ///Whether to show the readings of annotated text before the problem is answered
+    pub(super) fn readings(mut self, readings: bool) -> Self {
+        self.readings = readings;
+        self
+    }
+
+    ///Shows which subfaces are in their right place, next to the right order
+    pub(super) fn answered(mut self) -> Self {
+        self.answered = true;
+        self
+    }
+}
+
+pub(super) struct OrderProblemWidgetState {
+    ///Areas of the subfaces in the order they've been put in, for mouse input
+    pub(super) subface_areas: Vec<Rect>,
+}
+
+impl OrderProblemWidgetState {
+    pub(super) fn new(subfaces: usize) -> Self {
+        Self {
+            subface_areas: [Rect::default()].repeat(subfaces),
+        }
+    }
+}
+
+impl StatefulWidget for OrderProblemWidget<'_> {
+    type State = OrderProblemWidgetState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) where
+        Self: Sized,
+    {
+        let (question_area, order_area, progress_area) = {
+            let layout = Layout::new(
+                Direction::Vertical,
+                [
+                    Constraint::Ratio(1, 3),
+                    Constraint::Fill(1),
+                    Constraint::Length(1),
+                ],
+            );
+            let split = layout.split(area);
+
+            (split[0], split[1], split[2])
+        };
+
+        let subfaces = self.problem.subfaces;
+        let order = &self.ordering.order;
+        let placed = in_place(subfaces, order);
+
+        //NOTE: Readings are always shown once answered, as there's nothing left to give away
+        let readings = self.readings || self.answered;
+
+        {
+            let mut text = ruby_text(&self.problem.question.prompt, question_area.width, readings);
+            let info_faces = self.problem.question.deck_card.info_faces();
+            if self.answered && !info_faces.is_empty() {
+                text.push_line(Line::default());
+                text.extend(info_lines(&info_faces, question_area.width));
+            }
+
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .centered()
+                .block(Block::new().title(format!("{}:", self.problem.question_face)))
+                .fg(match (self.answered, placed == subfaces.len()) {
+                    (false, _) => Color::default(),
+                    (true, true) => COLOR_CORRECT,
+                    (true, false) => COLOR_INCORRECT,
+                })
+                .render(question_area, buf);
+        }
+
+        let mut block = Block::bordered();
+        block = if self.answered {
+            block.title(format!(
+                "{}: {placed}/{} in place",
+                self.problem.answer_face,
+                subfaces.len()
+            ))
+        } else {
+            block
+                .title(format!("{} in order:", self.problem.answer_face))
+                .title(
+                    Title::from("Space: move, Enter: done".fg(COLOR_HINT))
+                        .alignment(Alignment::Right),
+                )
+        };
+        let inner = block.inner(order_area);
+        block.render(order_area, buf);
+
+        let (order_area, right_order_area) = match self.answered {
+            true => {
+                let layout = Layout::new(
+                    Direction::Horizontal,
+                    [Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)],
+                );
+                let split = layout.split(inner);
+
+                (split[0], Some(split[1]))
+            }
+            false => (inner, None),
+        };
+
+        let subface_text = |place: usize, subface: &str, width: u16| {
+            ruby_text(&format!("{}. {subface}", place + 1), width, readings)
+        };
+
+        let ordered = order
+            .iter()
+            .enumerate()
+            .map(|(place, subface)| {
+                let text = subface_text(place, &subfaces[*subface], order_area.width);
+                if self.answered {
+                    let is_placed = plain_text(&subfaces[*subface]) == plain_text(&subfaces[place]);
+                    text.fg(if is_placed {
+                        COLOR_CORRECT
+                    } else {
+                        COLOR_INCORRECT
+                    })
+                } else if place == self.ordering.cursor && self.ordering.grabbed {
+                    text.fg(COLOR_HINT).bold()
+                } else if place == self.ordering.cursor {
+                    text.reversed()
+                } else {
+                    text
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for (place, area) in render_rows(ordered, order_area, buf)
+            .into_iter()
+            .enumerate()
+        {
+            state.subface_areas[place] = area;
+        }
+
+        if let Some(right_order_area) = right_order_area {
+            let right_order = subfaces
+                .iter()
+                .enumerate()
+                .map(|(place, subface)| {
+                    subface_text(place, subface, right_order_area.width).fg(COLOR_CORRECT)
+                })
+                .collect();
+            render_rows(right_order, right_order_area, buf);
+        }
+
+        progress_gauge(self.progress).render(progress_area, buf);
+    }
+}
+
+///Renders each text on its own rows, top to bottom, returning the area of each
+fn render_rows(texts: Vec<Text>, area: Rect, buf: &mut ratatui::prelude::Buffer) -> Vec<Rect> {
+    let layout = Layout::new(
+        Direction::Vertical,
+        texts
+            .iter()
+            .map(|text| Constraint::Length(text.height() as u16))
+            .chain([Constraint::Fill(1)]),
+    );
+    let split = layout.split(area);
+
+    texts
+        .into_iter()
+        .zip(split.iter())
+        .map(|(text, area)| {
+            Paragraph::new(text).render(*area, buf);
+            *area
+        })
+        .collect()
+}
@@ -0,0 +1,148 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    cli::MIN_CHOICES,
+    modes::match_faces::{MatchProblem, MatchProblemIterator},
+    stats::Stats,
+    DeckCard, FaceSelection, FlashrError,
+};
+
+use super::TrueFalseProblem;
+
+///Iterates match problems with a single distractor, showing either the answer or the
+///distractor with the question
+pub(super) struct TrueFalseProblemIterator<'a> {
+    rng: ThreadRng,
+    problems: MatchProblemIterator<'a>,
+}
+
+impl<'a> TrueFalseProblemIterator<'a> {
+    pub fn new(
+        deck_cards: Vec<DeckCard<'a>>,
+        stats: &mut Stats,
+        faces: FaceSelection,
+        rng: &'a mut ThreadRng,
+    ) -> Self {
+        Self {
+            rng: rng.clone(),
+            problems: MatchProblemIterator::new(
+                deck_cards,
+                stats,
+                faces,
+                MIN_CHOICES as usize,
+                false,
+                rng,
+            ),
+        }
+    }
+
+    pub fn change_weight(&mut self, index: usize, weight: f64) {
+        self.problems.change_weight(index, weight)
+    }
+}
+
+impl<'a> Iterator for TrueFalseProblemIterator<'a> {
+    type Item = Result<TrueFalseProblem<'a>, FlashrError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let MatchProblem {
+            question,
+            answers,
+            readings,
+            ..
+        } = match self.problems.next()? {
+            Ok(problem) => problem,
+            Err(err) => return Some(Err(err)),
+        };
+
+        let (mut answers, mut distractors): (Vec<_>, Vec<_>) =
+            answers.into_iter().partition(|(_, correct)| *correct);
+        let (answer, _) = answers
+            .pop()
+            .expect("Unable to find answer of match problem");
+        let distractor = self
+            .rng
+            .gen_bool(0.5)
+            .then(|| distractors.pop().map(|(distractor, _)| distractor))
+            .flatten();
+
+        Some(Ok(TrueFalseProblem {
+            question,
+            answer,
+            distractor,
+            readings,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cli::MIN_CHOICES, deck::load_decks, stats::Stats, tags::TagFilter, FaceSelection,
+        ModeArguments,
+    };
+
+    use super::TrueFalseProblemIterator;
+
+    #[test]
+    fn shows_answers_and_distractors() {
+        let decks = load_decks(vec!["./tests/deck1.json"]).expect("Unable to load test deck");
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &TagFilter::default(),
+            MIN_CHOICES as usize,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+        let rng = &mut rand::thread_rng();
+        let stats = &mut Stats::new("");
+        let problems = TrueFalseProblemIterator::new(args.deck_cards, stats, args.faces, rng);
+
+        let (mut trues, mut falses) = (0, 0);
+        for problem in problems.take(200) {
+            let problem = problem.expect("Unable to get problem");
+            assert!(std::ptr::eq(
+                problem.answer.deck_card.card,
+                problem.question.deck_card.card
+            ));
+
+            match problem.distractor.as_ref() {
+                Some(distractor) => {
+                    assert_ne!(distractor.prompt, problem.answer.prompt);
+                    assert!(!std::ptr::eq(
+                        distractor.deck_card.card,
+                        problem.question.deck_card.card
+                    ));
+                    assert!(!problem.is_true());
+                    falses += 1;
+                }
+                None => {
+                    assert_eq!(problem.shown().prompt, problem.answer.prompt);
+                    trues += 1;
+                }
+            }
+        }
+        assert!(trues > 0 && falses > 0);
+    }
+}
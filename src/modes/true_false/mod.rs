@@ -0,0 +1,250 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+
+use iter::TrueFalseProblemIterator;
+use widget::TrueFalseProblemWidget;
+
+use crate::{
+    event::{clear_and_match_event, clear_event_loop, match_event_within},
+    stats::Stats,
+    terminal::TerminalWrapper,
+    FlashrError, ModeArguments, Progress, PromptCard,
+};
+
+mod iter;
+mod widget;
+
+///How often the countdown is redrawn while waiting for an answer
+const COUNTDOWN_TICK: Duration = Duration::from_millis(100);
+///How long a correct answer is shown before moving on, keeping up the pace
+const CORRECT_DELAY: Duration = Duration::from_millis(500);
+
+struct TrueFalseProblem<'a> {
+    question: PromptCard<'a>,
+    answer: PromptCard<'a>,
+    ///Distractor shown in place of the answer, making the problem false
+    distractor: Option<PromptCard<'a>>,
+    ///Whether readings are shown before answering, toggled by the user
+    readings: bool,
+}
+
+impl<'a> TrueFalseProblem<'a> {
+    ///Answer shown along with the question, which is either its own or a distractor
+    fn shown(&self) -> &PromptCard<'a> {
+        self.distractor.as_ref().unwrap_or(&self.answer)
+    }
+
+    fn is_true(&self) -> bool {
+        self.distractor.is_none()
+    }
+}
+
+///Problems answered correctly in a row, along with the most in a row so far
+#[derive(Clone, Copy, Default)]
+struct Streak {
+    current: usize,
+    best: usize,
+}
+
+impl Streak {
+    fn add(&mut self, correct: bool) {
+        self.current = if correct { self.current + 1 } else { 0 };
+        self.best = self.best.max(self.current);
+    }
+}
+
+struct Quit;
+
+pub fn true_false(
+    term: &mut TerminalWrapper,
+    args: ModeArguments,
+    countdown: Duration,
+) -> Result<Progress, FlashrError> {
+    let rng = &mut rand::thread_rng();
+    let mut stats = Stats::load_from_user_home()?;
+    let mut problems = TrueFalseProblemIterator::new(args.deck_cards, &mut stats, args.faces, rng);
+
+    fn update(
+        card: &PromptCard,
+        correct: bool,
+        stats: &mut Stats,
+        problems: &mut TrueFalseProblemIterator,
+    ) {
+        let stats = stats.for_card_mut(card);
+        if correct {
+            stats.correct += 1;
+        } else {
+            stats.incorrect += 1;
+        }
+        problems.change_weight(card.index, stats.weight());
+    }
+
+    let mut progress = Progress::default();
+    let mut streak = Streak::default();
+    let range = args.problem_count.map_or(0..usize::MAX, |count| 0..count);
+
+    for _ in range {
+        let Some(problem) = problems.next() else {
+            break;
+        };
+        let problem = &problem?;
+
+        let Ok(answer) = show_true_false_problem(term, problem, (progress, streak), countdown)?
+        else {
+            break;
+        };
+        let correct = answer == Some(problem.is_true());
+
+        update(&problem.question, correct, &mut stats, &mut problems);
+        //NOTE: As in match mode, a distractor taken for the answer is known less well too
+        if let (Some(true), Some(distractor)) = (answer, problem.distractor.as_ref()) {
+            update(distractor, false, &mut stats, &mut problems);
+        }
+
+        if correct {
+            progress.add_correct();
+        } else {
+            progress.add_incorrect();
+        }
+        streak.add(correct);
+
+        if let Err(Quit) = show_true_false_result(term, problem, (progress, streak), answer)? {
+            break;
+        }
+    }
+
+    stats.save_to_file()?;
+
+    Ok(progress)
+}
+
+///Whether the problem was answered as true or false, or None if time ran out
+type TrueFalseProblemResult = Result<Option<bool>, Quit>;
+
+fn show_true_false_problem(
+    term: &mut TerminalWrapper,
+    problem: &TrueFalseProblem,
+    status: (Progress, Streak),
+    countdown: Duration,
+) -> Result<TrueFalseProblemResult, FlashrError> {
+    //NOTE: Cleared once, as the countdown is redrawn without waiting for input
+    clear_event_loop()?;
+    let start = Instant::now();
+    let mut readings = problem.readings;
+
+    loop {
+        let remaining = countdown.saturating_sub(start.elapsed());
+        if remaining.is_zero() {
+            return Ok(Ok(None));
+        }
+
+        term.render_widget(
+            TrueFalseProblemWidget::new(problem, status)
+                .countdown(remaining, countdown)
+                .readings(readings),
+        )?;
+
+        match match_event_within(match_true_false_input, remaining.min(COUNTDOWN_TICK))? {
+            Some(TrueFalseInput::Answer(answer)) => return Ok(Ok(Some(answer))),
+            Some(TrueFalseInput::ToggleReadings) => readings = !readings,
+            Some(TrueFalseInput::Quit) => return Ok(Err(Quit)),
+            Some(TrueFalseInput::Continue | TrueFalseInput::Resize) | None => continue,
+        }
+    }
+}
+
+///Shows whether the problem was answered correctly, briefly when it was, otherwise
+///until the user continues
+fn show_true_false_result(
+    term: &mut TerminalWrapper,
+    problem: &TrueFalseProblem,
+    status: (Progress, Streak),
+    answer: Option<bool>,
+) -> Result<Result<(), Quit>, FlashrError> {
+    let widget = || TrueFalseProblemWidget::new(problem, status).answered(answer);
+
+    if answer == Some(problem.is_true()) {
+        term.render_widget(widget())?;
+        return match match_event_within(match_true_false_input, CORRECT_DELAY)? {
+            Some(TrueFalseInput::Quit) => Ok(Err(Quit)),
+            _ => Ok(Ok(())),
+        };
+    }
+
+    loop {
+        term.render_widget(widget())?;
+
+        match clear_and_match_event(match_true_false_input)? {
+            TrueFalseInput::Continue => return Ok(Ok(())),
+            TrueFalseInput::Quit => return Ok(Err(Quit)),
+            _ => continue,
+        }
+    }
+}
+
+enum TrueFalseInput {
+    Answer(bool),
+    Continue,
+    ToggleReadings,
+    Resize,
+    Quit,
+}
+
+fn match_true_false_input(event: Event) -> Option<TrueFalseInput> {
+    match event {
+        Event::Key(KeyEvent {
+            kind: KeyEventKind::Press,
+            code,
+            ..
+        }) => match code {
+            KeyCode::Enter | KeyCode::Char(' ') => Some(TrueFalseInput::Continue),
+            KeyCode::Esc | KeyCode::Char('q') => Some(TrueFalseInput::Quit),
+            KeyCode::Char(char) => match char.to_ascii_lowercase() {
+                'y' | 't' => Some(TrueFalseInput::Answer(true)),
+                'n' | 'f' => Some(TrueFalseInput::Answer(false)),
+                'r' => Some(TrueFalseInput::ToggleReadings),
+                _ => None,
+            },
+            _ => None,
+        },
+        Event::Resize(_, _) => Some(TrueFalseInput::Resize),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Streak;
+
+    #[test]
+    fn tracks_best_streak() {
+        let mut streak = Streak::default();
+        for correct in [true, true, true, false, true] {
+            streak.add(correct);
+        }
+        assert_eq!((streak.current, streak.best), (1, 3));
+
+        streak.add(false);
+        assert_eq!((streak.current, streak.best), (0, 3));
+    }
+}
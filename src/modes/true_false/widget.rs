@@ -0,0 +1,195 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{block::Title, Block, Gauge, Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    render_utils::{
+        info_lines, progress_gauge, ruby_text, COLOR_CORRECT, COLOR_HINT, COLOR_INCORRECT,
+    },
+    Progress,
+};
+
+use super::{Streak, TrueFalseProblem};
+
+pub(super) struct TrueFalseProblemWidget<'a> {
+    problem: &'a TrueFalseProblem<'a>,
+    progress: Progress,
+    streak: Streak,
+    ///Time left to answer, out of the time given
+    countdown: Option<(Duration, Duration)>,
+    ///Whether the problem was answered as true or false, or None if time ran out
+    answer: Option<Option<bool>>,
+    readings: bool,
+}
+
+impl<'a> TrueFalseProblemWidget<'a> {
+    pub(super) fn new(
+        problem: &'a TrueFalseProblem<'a>,
+        (progress, streak): (Progress, Streak),
+    ) -> Self {
+        Self {
+            problem,
+            progress,
+            streak,
+            countdown: None,
+            answer: None,
+            readings: true,
+        }
+    }
+
+    pub(super) fn countdown(mut self, remaining: Duration, total: Duration) -> Self {
+        self.countdown = Some((remaining, total));
+        self
+    }
+
+    ///Whether to show the readings of annotated text before the problem is answered
+    pub(super) fn readings(mut self, readings: bool) -> Self {
+        self.readings = readings;
+        self
+    }
+
+    pub(super) fn answered(mut self, answer: Option<bool>) -> Self {
+        self.answer = Some(answer);
+        self
+    }
+}
+
+impl Widget for TrueFalseProblemWidget<'_> {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        let (countdown_area, question_area, answer_area, streak_area, progress_area) = {
+            let layout = Layout::new(
+                Direction::Vertical,
+                [
+                    Constraint::Length(1),
+                    Constraint::Ratio(1, 3),
+                    Constraint::Fill(1),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                ],
+            );
+            let split = layout.split(area);
+
+            (split[0], split[1], split[2], split[3], split[4])
+        };
+
+        let is_true = self.problem.is_true();
+        let correct = self.answer.map(|answer| answer == Some(is_true));
+        let color = match correct {
+            None => Color::default(),
+            Some(true) => COLOR_CORRECT,
+            Some(false) => COLOR_INCORRECT,
+        };
+
+        //NOTE: Readings are always shown once answered, as there's nothing left to give away
+        let readings = self.readings || self.answer.is_some();
+
+        match (self.countdown, self.answer) {
+            (_, Some(answer)) => {
+                let verdict = match (answer, correct) {
+                    (None, _) => "Out of time!",
+                    (Some(_), Some(true)) => "Correct!",
+                    (Some(_), _) => "Incorrect!",
+                };
+                Paragraph::new(verdict)
+                    .centered()
+                    .fg(color)
+                    .bold()
+                    .render(countdown_area, buf);
+            }
+            (Some((remaining, total)), None) => {
+                Gauge::default()
+                    .ratio(remaining.as_secs_f64() / total.as_secs_f64())
+                    .label(format!("{:.1}s", remaining.as_secs_f64()))
+                    .gauge_style(Style::default().fg(COLOR_HINT))
+                    .use_unicode(true)
+                    .render(countdown_area, buf);
+            }
+            (None, None) => {}
+        }
+
+        {
+            let width = question_area.width;
+            let mut text = ruby_text(&self.problem.question.prompt, width, readings);
+            let info_faces = self.problem.question.deck_card.info_faces();
+            if self.answer.is_some() && !info_faces.is_empty() {
+                text.push_line(Line::default());
+                text.extend(info_lines(&info_faces, width));
+            }
+
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .centered()
+                .fg(color)
+                .render(question_area, buf);
+        }
+
+        {
+            let mut block = Block::bordered();
+            block = match (self.answer, correct) {
+                (None, _) => block.title("True or false?").title(
+                    Title::from("Y: true, N: false".fg(COLOR_HINT)).alignment(Alignment::Right),
+                ),
+                (Some(_), Some(true)) => block.title(if is_true { "True" } else { "False" }),
+                (Some(_), _) => block.title(if is_true { "True" } else { "False" }).title(
+                    Title::from("Enter: continue".fg(COLOR_HINT)).alignment(Alignment::Right),
+                ),
+            };
+            let width = block.inner(answer_area).width;
+
+            let mut text = ruby_text(&self.problem.shown().prompt, width, readings);
+            if self.answer.is_some() {
+                text = text.fg(if is_true {
+                    COLOR_CORRECT
+                } else {
+                    COLOR_INCORRECT
+                });
+                if !is_true {
+                    text.push_line(Line::default());
+                    text.extend(
+                        ruby_text(&self.problem.answer.prompt, width, true).fg(COLOR_CORRECT),
+                    );
+                }
+            }
+
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .centered()
+                .block(block)
+                .render(answer_area, buf);
+        }
+
+        let Streak { current, best } = self.streak;
+        Paragraph::new(format!("Streak: {current} (best {best})"))
+            .centered()
+            .render(streak_area, buf);
+
+        progress_gauge(&self.progress).render(progress_area, buf);
+    }
+}
@@ -0,0 +1,167 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    cloze, hint::hints, random::GetRandom, stats::Stats, weighted_list::WeightedList, DeckCard,
+    FaceSelection, PromptCard,
+};
+
+use super::TypeProblem;
+
+pub(super) struct TypeProblemIterator<'a> {
+    rng: &'a mut ThreadRng,
+    weighted_deck_cards: WeightedList<DeckCard<'a>>,
+    faces: FaceSelection,
+}
+
+impl<'a> TypeProblemIterator<'a> {
+    pub fn new(
+        deck_cards: Vec<DeckCard<'a>>,
+        stats: &mut Stats,
+        faces: FaceSelection,
+        rng: &'a mut ThreadRng,
+    ) -> Self {
+        Self {
+            rng,
+            faces,
+            weighted_deck_cards: {
+                let mut buf = WeightedList::with_capacity(deck_cards.len());
+                deck_cards.into_iter().for_each(|deck_card| {
+                    let weight = stats.for_card(&deck_card).weight();
+                    buf.add((deck_card, weight));
+                });
+                buf
+            },
+        }
+    }
+
+    pub fn change_weight(&mut self, index: usize, weight: f64) {
+        self.weighted_deck_cards.change_weight(index, weight)
+    }
+}
+
+impl<'a> Iterator for TypeProblemIterator<'a> {
+    type Item = TypeProblem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (deck_card, index) = self.weighted_deck_cards.get_random(self.rng)?;
+
+        if let Some(((_, face, question), answer)) = deck_card.cloze_entry() {
+            let number = deck_card.cloze.map_or(0, |cloze| cloze.number);
+            return Some(TypeProblem {
+                question: PromptCard {
+                    prompt: cloze::blank(&question.join_random(self.rng), number),
+                    deck_card: *deck_card,
+                    index,
+                },
+                question_face: face,
+                answer_face: face,
+                hints: hints(deck_card.card, &answer),
+                answer: Cow::Owned(answer),
+                //NOTE: The deleted text isn't shown, so its readings can't give it away
+                readings: true,
+            });
+        }
+
+        let pair = *deck_card
+            .possible_pairs(&self.faces)
+            .get_random(self.rng)
+            .expect("Unable to find valid question and answer faces");
+        let ((_, question_face, question), (_, answer_face, answer)) = pair;
+
+        Some(TypeProblem {
+            question: PromptCard {
+                prompt: question.join_random(self.rng),
+                deck_card: *deck_card,
+                index,
+            },
+            question_face,
+            answer_face,
+            answer: Cow::Borrowed(answer),
+            hints: hints(deck_card.card, answer),
+            readings: deck_card.shows_readings(&pair),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        cli::DEFAULT_CHOICES, deck::load_decks, stats::Stats, tags::TagFilter, FaceSelection,
+        ModeArguments,
+    };
+
+    use super::TypeProblemIterator;
+
+    #[test]
+    fn uses_selected_faces() {
+        let decks = load_decks(vec!["./tests/deck1.json"]).expect("Unable to load test deck");
+        let faces = FaceSelection::new(
+            Some(vec!["Hiragana".to_owned()]),
+            Some(vec!["English".to_owned()]),
+            vec![],
+        );
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            faces,
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+        let rng = &mut rand::thread_rng();
+        let stats = &mut Stats::new("");
+        let problems = TypeProblemIterator::new(args.deck_cards, stats, args.faces, rng);
+
+        for problem in problems.take(100) {
+            assert_eq!(problem.question_face, "Hiragana");
+            assert_eq!(problem.answer_face, "English");
+        }
+    }
+
+    #[test]
+    fn answers_clozes() {
+        let decks = load_decks(vec!["./tests/cloze.json"]).expect("Unable to load test deck");
+        let args = ModeArguments::new(
+            &decks,
+            None,
+            FaceSelection::default(),
+            &TagFilter::default(),
+            DEFAULT_CHOICES,
+            false,
+        )
+        .expect("Unable to create mode arguments");
+        let rng = &mut rand::thread_rng();
+        let stats = &mut Stats::new("");
+        let problems = TypeProblemIterator::new(args.deck_cards, stats, args.faces, rng);
+
+        for problem in problems.take(100) {
+            assert_eq!(problem.question_face, "Sentence");
+            assert!(!problem.question.prompt.contains("{{"));
+            assert!(!problem.question.deck_card[0]
+                .as_ref()
+                .is_some_and(|sentence| sentence.is_match(&problem.answer.join(), None)));
+        }
+    }
+}
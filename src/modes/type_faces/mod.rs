@@ -0,0 +1,203 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind,
+};
+
+use iter::TypeProblemIterator;
+use widget::TypeProblemWidget;
+
+use crate::{
+    deck::Face,
+    event::{clear_and_match_event, match_event},
+    render_utils::Scroll,
+    stats::Stats,
+    terminal::TerminalWrapper,
+    FlashrError, ModeArguments, Progress, PromptCard,
+};
+
+mod iter;
+mod widget;
+
+struct TypeProblem<'a> {
+    question: PromptCard<'a>,
+    question_face: &'a String,
+    answer_face: &'a String,
+    ///Face to answer, or the text deleted by the question's cloze
+    answer: Cow<'a, Face>,
+    hints: Vec<String>,
+    ///Whether readings are shown before answering, toggled by the user
+    readings: bool,
+}
+
+struct Quit;
+
+pub fn type_faces(
+    term: &mut TerminalWrapper,
+    args: ModeArguments,
+) -> Result<Progress, FlashrError> {
+    let rng = &mut rand::thread_rng();
+    let mut stats = Stats::load_from_user_home()?;
+    let mut problems = TypeProblemIterator::new(args.deck_cards, &mut stats, args.faces, rng);
+
+    let mut progress = Progress::default();
+    let range = args.problem_count.map_or(0..usize::MAX, |count| 0..count);
+
+    for _ in range {
+        if let Some(problem) = problems.next() {
+            match show_type_problem(term, &problem, progress)? {
+                Ok((correct, hinted)) => {
+                    let card_stats = stats.for_card_mut(&problem.question);
+
+                    if correct {
+                        card_stats.correct += 1;
+                        if hinted {
+                            card_stats.hinted += 1;
+                        }
+                        progress.add_correct();
+                    } else {
+                        card_stats.incorrect += 1;
+                        progress.add_incorrect();
+                    }
+
+                    problems.change_weight(problem.question.index, card_stats.weight());
+                }
+                Err(Quit) => break,
+            }
+        } else {
+            break;
+        }
+    }
+
+    stats.save_to_file()?;
+
+    Ok(progress)
+}
+
+///Whether the answer was correct, and whether any hints were revealed before answering
+type TypeProblemResult = Result<(bool, bool), Quit>;
+
+fn show_type_problem(
+    term: &mut TerminalWrapper,
+    problem: &TypeProblem,
+    progress: Progress,
+) -> Result<TypeProblemResult, FlashrError> {
+    let mut input = String::new();
+    let mut hints_revealed = 0;
+    let mut readings = problem.readings;
+    let scroll = &mut Scroll::default();
+
+    loop {
+        term.render_stateful_widget(
+            TypeProblemWidget::new(problem, &input, &progress)
+                .hints(hints_revealed)
+                .readings(readings),
+            scroll,
+        )?;
+
+        match match_event(match_typing_input)? {
+            TypingInput::Char(char) => input.push(char),
+            TypingInput::Backspace => {
+                input.pop();
+            }
+            TypingInput::Hint => hints_revealed = (hints_revealed + 1).min(problem.hints.len()),
+            TypingInput::ToggleReadings => readings = !readings,
+            TypingInput::ScrollUp | TypingInput::ScrollDown => continue,
+            TypingInput::Submit if !input.trim().is_empty() => break,
+            TypingInput::Submit | TypingInput::Resize => continue,
+            TypingInput::Quit => return Ok(Err(Quit)),
+        }
+    }
+
+    let language = problem
+        .question
+        .deck_card
+        .deck
+        .metadata
+        .language(problem.answer_face);
+    let correct = problem.answer.is_match(&input, language);
+
+    loop {
+        term.render_stateful_widget(
+            TypeProblemWidget::new(problem, &input, &progress)
+                .hints(hints_revealed)
+                .answered(correct),
+            scroll,
+        )?;
+
+        match clear_and_match_event(match_typing_input)? {
+            TypingInput::Submit => return Ok(Ok((correct, hints_revealed > 0))),
+            TypingInput::ScrollUp => scroll.up(),
+            TypingInput::ScrollDown => scroll.down(),
+            TypingInput::Quit => return Ok(Err(Quit)),
+            _ => continue,
+        }
+    }
+}
+
+enum TypingInput {
+    Char(char),
+    Backspace,
+    Hint,
+    ToggleReadings,
+    ScrollUp,
+    ScrollDown,
+    Submit,
+    Resize,
+    Quit,
+}
+
+fn match_typing_input(event: Event) -> Option<TypingInput> {
+    match event {
+        //NOTE: Ctrl, as every plain character may be part of the answer
+        Event::Key(KeyEvent {
+            kind: KeyEventKind::Press,
+            code: KeyCode::Char('r'),
+            modifiers,
+            ..
+        }) if modifiers.contains(KeyModifiers::CONTROL) => Some(TypingInput::ToggleReadings),
+        Event::Key(KeyEvent {
+            kind: KeyEventKind::Press,
+            code,
+            ..
+        }) => match code {
+            KeyCode::Char(char) => Some(TypingInput::Char(char)),
+            KeyCode::Backspace => Some(TypingInput::Backspace),
+            KeyCode::Tab => Some(TypingInput::Hint),
+            KeyCode::Up | KeyCode::PageUp => Some(TypingInput::ScrollUp),
+            KeyCode::Down | KeyCode::PageDown => Some(TypingInput::ScrollDown),
+            KeyCode::Enter => Some(TypingInput::Submit),
+            KeyCode::Esc => Some(TypingInput::Quit),
+            _ => None,
+        },
+        Event::Resize(_, _) => Some(TypingInput::Resize),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::ScrollUp,
+            ..
+        }) => Some(TypingInput::ScrollUp),
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::ScrollDown,
+            ..
+        }) => Some(TypingInput::ScrollDown),
+        _ => None,
+    }
+}
@@ -0,0 +1,172 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Stylize},
+    text::Line,
+    widgets::{block::Title, Block, Borders, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::{
+    render_utils::{
+        info_lines, progress_gauge, prompt_with_hints, ruby_text, Scroll, COLOR_CORRECT,
+        COLOR_HINT, COLOR_INCORRECT,
+    },
+    Progress,
+};
+
+use super::TypeProblem;
+
+pub(super) struct TypeProblemWidget<'a> {
+    problem: &'a TypeProblem<'a>,
+    input: &'a str,
+    progress: &'a Progress,
+    correct: Option<bool>,
+    hints_revealed: usize,
+    readings: bool,
+}
+
+impl<'a> TypeProblemWidget<'a> {
+    pub(super) fn new(
+        problem: &'a TypeProblem<'a>,
+        input: &'a str,
+        progress: &'a Progress,
+    ) -> Self {
+        Self {
+            problem,
+            input,
+            progress,
+            correct: None,
+            hints_revealed: 0,
+            readings: true,
+        }
+    }
+
+    ///Whether to show the readings of annotated text before the problem is answered
+    pub(super) fn readings(mut self, readings: bool) -> Self {
+        self.readings = readings;
+        self
+    }
+
+    ///Shows the first `revealed` of the problem's hints beneath the question
+    pub(super) fn hints(mut self, revealed: usize) -> Self {
+        self.hints_revealed = revealed;
+        self
+    }
+
+    pub(super) fn answered(mut self, correct: bool) -> Self {
+        self.correct = Some(correct);
+        self
+    }
+}
+
+impl StatefulWidget for TypeProblemWidget<'_> {
+    ///Scroll of the answer, which may be followed by long info faces
+    type State = Scroll;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        scroll: &mut Self::State,
+    ) where
+        Self: Sized,
+    {
+        let (question_area, input_area, answer_area, progress_area) = {
+            let layout = Layout::new(
+                Direction::Vertical,
+                [
+                    Constraint::Ratio(1, 3),
+                    Constraint::Length(3),
+                    Constraint::Fill(1),
+                    Constraint::Length(1),
+                ],
+            );
+            let split = layout.split(area);
+
+            (split[0], split[1], split[2], split[3])
+        };
+
+        let color = match self.correct {
+            None => Color::default(),
+            Some(true) => COLOR_CORRECT,
+            Some(false) => COLOR_INCORRECT,
+        };
+
+        let hints = &self.problem.hints[..self.hints_revealed];
+        let readings = self.readings || self.correct.is_some();
+        let prompt = ruby_text(&self.problem.question.prompt, question_area.width, readings);
+        Paragraph::new(prompt_with_hints(prompt, hints))
+            .wrap(Wrap { trim: false })
+            .centered()
+            .block(Block::new().title(format!("{}:", self.problem.question_face)))
+            .fg(color)
+            .render(question_area, buf);
+
+        {
+            let input = match self.correct {
+                //NOTE: Fake cursor, since the terminal cursor is hidden
+                None => format!("{}_", self.input),
+                Some(_) => self.input.to_owned(),
+            };
+
+            let title = match self.problem.question.deck_card.cloze {
+                Some(cloze) => format!("{} (cloze {}):", self.problem.answer_face, cloze.number),
+                None => format!("{}:", self.problem.answer_face),
+            };
+            let mut block = Block::bordered().title(title);
+            let hints_left = self.problem.hints.len() - self.hints_revealed;
+            if self.correct.is_none() && hints_left > 0 {
+                block = block.title(
+                    Title::from(format!("Tab: hint ({hints_left} left)").fg(COLOR_HINT))
+                        .alignment(Alignment::Right),
+                );
+            }
+
+            Paragraph::new(input)
+                .block(block)
+                .fg(color)
+                .render(input_area, buf);
+        }
+
+        if self.correct.is_some() {
+            let deck_card = &self.problem.question.deck_card;
+            let block = Block::new().borders(Borders::TOP);
+            let width = block.inner(answer_area).width;
+            let mut text = ruby_text(&deck_card.join_quizzed("\n"), width, true);
+            let info_faces = deck_card.info_faces();
+            if !info_faces.is_empty() {
+                text.push_line(Line::default());
+                text.extend(info_lines(&info_faces, width));
+            }
+
+            let scroll = scroll.clamp(&text, block.inner(answer_area));
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .centered()
+                .block(block)
+                .fg(COLOR_CORRECT)
+                .scroll(scroll)
+                .render(answer_area, buf);
+        }
+
+        progress_gauge(self.progress).render(progress_area, buf);
+    }
+}
@@ -17,9 +17,178 @@
  * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
  */
 
-use ratatui::layout::Rect;
+use std::borrow::Cow;
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::Gauge,
+};
 use unicode_width::UnicodeWidthStr;
 
+use crate::{
+    cloze, rich,
+    ruby::{self, Segment},
+    FaceEntry, Progress,
+};
+
+pub(crate) const COLOR_CORRECT: Color = Color::Green;
+pub(crate) const COLOR_INCORRECT: Color = Color::Red;
+pub(crate) const COLOR_HINT: Color = Color::Yellow;
+
+pub(crate) fn progress_gauge(progress: &Progress) -> Gauge<'static> {
+    let (ratio, percent) = progress.ratio_percent();
+    let Progress { correct, total } = progress;
+    Gauge::default()
+        .ratio(ratio)
+        .label(format!("{percent:05.2}% ({correct}/{total})"))
+        .gauge_style(Style::default().fg(COLOR_CORRECT).bg(COLOR_INCORRECT))
+        .use_unicode(true)
+}
+
+///Text of a problem's prompt, followed by any hints revealed so far
+pub(crate) fn prompt_with_hints<'a>(mut prompt: Text<'a>, hints: &[String]) -> Text<'a> {
+    if !hints.is_empty() {
+        prompt.push_line(Line::default());
+        for hint in hints {
+            let hint = ruby::inline(&rich::strip(hint)).into_owned();
+            prompt.push_line(Line::from(format!("Hint: {hint}")).fg(COLOR_HINT).italic());
+        }
+    }
+    prompt
+}
+
+///Lines of a line of text styled by its markup, eg. `**bold**`, with its reading annotations,
+///eg. `{日本|にほん}`, shown above the annotated text, or in parentheses after it if they'd
+///be wider than the width allows. Without `readings`, only the annotated text is shown.
+///Cloze deletions, eg. `{{c1::word}}`, are shown with their text.
+pub(crate) fn ruby_lines(line: &str, width: u16, readings: bool) -> Vec<Line<'static>> {
+    let line = cloze::reveal(line);
+    let pieces = rich::pieces(&line);
+    let styled = |text: fn(&str) -> Cow<'_, str>| {
+        Line::from(
+            pieces
+                .iter()
+                .map(|piece| Span::styled(text(piece.text).into_owned(), piece.style()))
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    if !readings {
+        return vec![styled(ruby::plain)];
+    } else if !pieces.iter().any(|piece| ruby::has_ruby(piece.text)) {
+        return vec![styled(|text| Cow::Borrowed(text))];
+    }
+
+    let pad = |text: &str, width: usize| {
+        let padding = width.saturating_sub(text.width());
+        let left = padding / 2;
+        format!("{}{text}{}", " ".repeat(left), " ".repeat(padding - left))
+    };
+
+    let (mut above, mut below) = (Line::default(), Line::default());
+    for piece in pieces.iter() {
+        let style = piece.style();
+        for segment in ruby::segments(piece.text) {
+            match segment {
+                Segment::Text(text) => {
+                    above.push_span(" ".repeat(text.width()));
+                    below.push_span(Span::styled(text.to_owned(), style));
+                }
+                Segment::Ruby { base, reading } => {
+                    let width = base.width().max(reading.width());
+                    above.push_span(Span::styled(pad(reading, width), style));
+                    below.push_span(Span::styled(pad(base, width), style));
+                }
+            }
+        }
+    }
+
+    if below.width() > width as usize {
+        return vec![styled(ruby::inline)];
+    }
+    vec![above.dim(), below]
+}
+
+///Text with the reading annotations of each of its lines shown as by [ruby_lines]
+pub(crate) fn ruby_text(text: &str, width: u16, readings: bool) -> Text<'static> {
+    Text::from(
+        text.lines()
+            .flat_map(|line| ruby_lines(line, width, readings))
+            .collect::<Vec<_>>(),
+    )
+}
+
+///Lines for each of a card's info faces, starting with the face's name
+pub(crate) fn info_lines(info_faces: &[FaceEntry], width: u16) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    for (_, deck_face, card_face) in info_faces {
+        let name = format!("{deck_face}: ");
+        let name_width = name.width() as u16;
+        let mut name = Some(name);
+
+        for line in card_face.join().lines() {
+            let mut face_lines = ruby_lines(line, width.saturating_sub(name_width), true);
+            if let Some(name) = name.take() {
+                let last = face_lines.len() - 1;
+                for (i, face_line) in face_lines.iter_mut().enumerate() {
+                    let prefix = match i == last {
+                        true => Span::from(name.clone()).bold(),
+                        false => Span::from(" ".repeat(name_width as usize)),
+                    };
+                    face_line.spans.insert(0, prefix);
+                }
+            }
+            lines.extend(face_lines);
+        }
+    }
+
+    //NOTE: Reset so that info isn't colored along with the answer it's shown beneath
+    lines
+        .into_iter()
+        .map(|line| {
+            let style = Style::reset().patch(line.style);
+            line.style(style)
+        })
+        .collect()
+}
+
+///Lines scrolled past of text which may be too long for its area
+#[derive(Default, Clone, Copy)]
+#[repr(transparent)]
+pub(crate) struct Scroll(u16);
+
+impl Scroll {
+    pub fn up(&mut self) {
+        self.0 = self.0.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.0 = self.0.saturating_add(1);
+    }
+
+    ///Clamps the scroll so that the end of the wrapped text is never scrolled past the
+    ///bottom of the area, returning the offset to render it at
+    pub fn clamp(&mut self, text: &Text, area: Rect) -> (u16, u16) {
+        let lines = wrapped_line_count(text, area.width);
+        self.0 = self.0.min(lines.saturating_sub(area.height));
+        (self.0, 0)
+    }
+}
+
+///Lines the text takes up when wrapped to the width, assuming lines wrap at any character
+fn wrapped_line_count(text: &Text, width: u16) -> u16 {
+    if width == 0 {
+        return 0;
+    }
+
+    text.lines
+        .iter()
+        .map(|line| (line.width() as f64 / width as f64).ceil().max(1.0) as u16)
+        .fold(0, u16::saturating_add)
+}
+
 /// Offsets to account for boxes.
 /// Eg, if Text is boxed on all sides, would use 2 for both
 #[derive(Default)]
@@ -0,0 +1,287 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    deck::{Card, CardId, Deck},
+    stats::{Stats, Strength},
+};
+
+///Describes the deck along with its metadata, eg.
+///```text
+///Kanji Words v1.2 by Clownvin, 20 cards
+///    Common kanji and their readings
+///    Faces: Kanji (ja), Hiragana (ja), Definition (en), Example (ja, info)
+///    License: CC-BY-4.0
+///```
+fn deck_header(deck: &Deck) -> String {
+    let metadata = &deck.metadata;
+    let mut header = deck.name.clone();
+    if let Some(version) = metadata.version.as_ref() {
+        header.push_str(&format!(" v{}", version.trim_start_matches(['v', 'V'])));
+    }
+    if let Some(author) = metadata.author.as_ref() {
+        header.push_str(&format!(" by {author}"));
+    }
+    header.push_str(&format!(", {} cards\n", deck.cards.len()));
+
+    if let Some(description) = metadata.description.as_ref() {
+        header.push_str(&format!("    {description}\n"));
+    }
+    let faces = deck
+        .faces
+        .iter()
+        .map(|face| {
+            let notes = metadata
+                .language(face)
+                .into_iter()
+                .chain(deck.is_info_face(face).then_some("info"))
+                .collect::<Vec<_>>();
+            match notes.is_empty() {
+                true => face.clone(),
+                false => format!("{face} ({})", notes.join(", ")),
+            }
+        })
+        .collect::<Vec<_>>();
+    header.push_str(&format!("    Faces: {}\n", faces.join(", ")));
+    if !metadata.tags.is_empty() {
+        header.push_str(&format!("    Tags: {}\n", metadata.tags.join(", ")));
+    }
+    if let Some(license) = metadata.license.as_ref() {
+        header.push_str(&format!("    License: {license}\n"));
+    }
+    if let Some(source) = metadata.source.as_ref() {
+        header.push_str(&format!("    Source: {source}\n"));
+    }
+
+    header
+}
+
+pub fn deck_list(decks: &[Deck]) -> String {
+    decks.iter().map(deck_header).collect()
+}
+
+#[derive(Default)]
+struct StatsTotals {
+    correct: usize,
+    incorrect: usize,
+    hinted: usize,
+    strengths: [usize; Strength::ALL.len()],
+}
+
+impl StatsTotals {
+    fn add(&mut self, other: &Self) {
+        self.correct += other.correct;
+        self.incorrect += other.incorrect;
+        self.hinted += other.hinted;
+        for (total, count) in self.strengths.iter_mut().zip(other.strengths) {
+            *total += count;
+        }
+    }
+
+    fn cards(&self) -> usize {
+        self.strengths.iter().sum()
+    }
+
+    fn lines(&self, indent: &str) -> String {
+        let answered = self.correct + self.incorrect;
+        let percent = match answered {
+            0 => 0,
+            _ => self.correct * 100 / answered,
+        };
+        let strengths = Strength::ALL
+            .iter()
+            .zip(self.strengths)
+            .map(|(strength, count)| format!("{count} {strength}"))
+            .collect::<Vec<_>>();
+
+        let hinted = match self.hinted {
+            0 => String::new(),
+            hinted => format!(" ({hinted} with hints)"),
+        };
+
+        format!(
+            "{indent}Answered {}{hinted} correctly and {} incorrectly ({percent}% correct)\n{indent}{}\n",
+            self.correct,
+            self.incorrect,
+            strengths.join(", ")
+        )
+    }
+}
+
+fn deck_totals(deck: &Deck, stats: &Stats, filter: impl Fn(&Card) -> bool) -> StatsTotals {
+    let mut totals = StatsTotals::default();
+
+    for card in deck.iter().filter(|card| filter(card)) {
+        //NOTE: A card's clozes are known as part of the card
+        let card_stats = stats.total(&CardId::with_clozes(deck, card));
+        let card_stats = card_stats.as_ref();
+        if let Some(card_stats) = card_stats {
+            totals.correct += card_stats.correct;
+            totals.incorrect += card_stats.incorrect;
+            totals.hinted += card_stats.hinted;
+        }
+        let strength = Strength::of(card_stats);
+        if let Some(i) = Strength::ALL.iter().position(|other| *other == strength) {
+            totals.strengths[i] += 1;
+        }
+    }
+
+    totals
+}
+
+const UNTAGGED: &str = "(untagged)";
+
+///Tags are in the order they're first seen, and cards with many tags are in each group
+fn tag_totals(deck: &Deck, stats: &Stats) -> Vec<(String, StatsTotals)> {
+    let mut names: Vec<&String> = vec![];
+    let mut untagged = false;
+    for card in deck.iter() {
+        let tags = deck.tags_of(card);
+        untagged |= tags.is_empty();
+        for tag in tags {
+            if !names.contains(&tag) {
+                names.push(tag);
+            }
+        }
+    }
+
+    let mut totals = names
+        .into_iter()
+        .map(|tag| {
+            let totals = deck_totals(deck, stats, |card| deck.tags_of(card).contains(&tag));
+            (tag.clone(), totals)
+        })
+        .collect::<Vec<_>>();
+    if untagged {
+        let untagged = deck_totals(deck, stats, |card| deck.tags_of(card).is_empty());
+        totals.push((UNTAGGED.to_owned(), untagged));
+    }
+
+    totals
+}
+
+fn push_tag_lines(report: &mut String, tag_totals: &[(String, StatsTotals)]) {
+    for (tag, totals) in tag_totals {
+        report.push_str(&format!("    {tag}, {} cards\n", totals.cards()));
+        report.push_str(&totals.lines("        "));
+    }
+}
+
+///With `by_tag`, the stats of each deck, and of all decks, are also grouped by tag
+pub fn stats_report(decks: &[Deck], stats: &Stats, by_tag: bool) -> String {
+    let mut report = String::new();
+    let mut totals = StatsTotals::default();
+    let mut all_tag_totals: Vec<(String, StatsTotals)> = vec![];
+
+    for deck in decks {
+        let deck_totals = deck_totals(deck, stats, |_| true);
+        report.push_str(&deck_header(deck));
+        report.push_str(&deck_totals.lines("    "));
+        totals.add(&deck_totals);
+
+        if by_tag {
+            let tag_totals = tag_totals(deck, stats);
+            //NOTE: Decks without any tags would only repeat their totals as untagged
+            if deck.iter().any(|card| !deck.tags_of(card).is_empty()) {
+                push_tag_lines(&mut report, &tag_totals);
+            }
+
+            for (tag, totals) in tag_totals {
+                match all_tag_totals.iter_mut().find(|(other, _)| *other == tag) {
+                    Some((_, all_totals)) => all_totals.add(&totals),
+                    None => all_tag_totals.push((tag, totals)),
+                }
+            }
+        }
+    }
+
+    if decks.len() > 1 {
+        report.push_str(&format!(
+            "Total of {} decks, {} cards\n",
+            decks.len(),
+            decks.iter().map(|deck| deck.cards.len()).sum::<usize>()
+        ));
+        report.push_str(&totals.lines("    "));
+        push_tag_lines(&mut report, &all_tag_totals);
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        deck::{load_decks, CardId},
+        stats::Stats,
+    };
+
+    use super::{deck_list, stats_report};
+
+    #[test]
+    fn lists_decks_with_metadata() {
+        let decks = load_decks(vec!["./tests/metadata.json"]).expect("Unable to load test deck");
+
+        assert_eq!(
+            deck_list(&decks),
+            "Elements v1.2 by Clownvin, 3 cards\n    Kanji of the five elements\n    Faces: Kanji (ja), Hiragana (ja), English (en)\n    License: CC-BY-4.0\n    Source: https://example.com/elements\n"
+        );
+    }
+
+    #[test]
+    fn reports_deck_stats() {
+        let decks = load_decks(vec!["./tests/metadata.json", "./tests/deck1.json"])
+            .expect("Unable to load test decks");
+        let mut stats = Stats::new("./tests/report_stats.json");
+        for (card, (correct, incorrect)) in decks[0].iter().zip([(1, 2), (4, 0)]) {
+            let card_stats = stats.for_card_mut(CardId::get(&decks[0], card));
+            card_stats.correct = correct;
+            card_stats.incorrect = incorrect;
+        }
+
+        let report = stats_report(&decks, &stats, false);
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[5],
+            "    Answered 5 correctly and 2 incorrectly (71% correct)"
+        );
+        assert_eq!(lines[6], "    1 new, 1 weak, 0 learning, 1 strong");
+        assert!(report.contains("Total of 2 decks"));
+    }
+
+    #[test]
+    fn reports_stats_by_tag() {
+        let decks = load_decks(vec!["./tests/tags.json"]).expect("Unable to load test deck");
+        let mut stats = Stats::new("./tests/report_tag_stats.json");
+        let card_stats = stats.for_card_mut(CardId::get(&decks[0], &decks[0][0]));
+        card_stats.correct = 2;
+        card_stats.hinted = 1;
+
+        let report = stats_report(&decks, &stats, true);
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(lines[2], "    Tags: JLPT N5");
+        assert_eq!(lines[5], "    JLPT N5, 4 cards");
+        assert_eq!(
+            lines[6],
+            "        Answered 2 (1 with hints) correctly and 0 incorrectly (100% correct)"
+        );
+        assert_eq!(lines[8], "    nature, 2 cards");
+        assert_eq!(lines[11], "    verb, 1 cards");
+        assert_eq!(lines[13], "        1 new, 0 weak, 0 learning, 0 strong");
+    }
+}
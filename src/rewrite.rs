@@ -0,0 +1,190 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{fmt::Display, fs, ops::Range, path::PathBuf};
+
+use crate::{deck::DeckError, location::SourceLocation};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub text: String,
+    ///Offset of the source the edit is reported at
+    pub offset: usize,
+    pub description: String,
+}
+
+impl Edit {
+    pub(crate) fn new(
+        range: Range<usize>,
+        text: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            offset: range.start,
+            range,
+            text: text.into(),
+            description: description.into(),
+        }
+    }
+
+    pub(crate) fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+///Edits are sorted and never overlap
+#[derive(Debug)]
+pub struct DeckRewrite {
+    pub path: PathBuf,
+    pub source: String,
+    pub edits: Vec<Edit>,
+}
+
+impl DeckRewrite {
+    pub fn fixed(&self) -> String {
+        apply_edits(&self.source, &self.edits, 0..self.source.len())
+    }
+
+    pub fn write(&self) -> Result<(), DeckError> {
+        fs::write(&self.path, self.fixed())
+            .map_err(|err| DeckError::IoError(self.path.clone(), err))
+    }
+
+    pub fn diff(&self) -> String {
+        let path = self.path.to_str().unwrap_or("unknown");
+        let mut diff = format!("--- {path}\n+++ {path}\n");
+        let mut line_delta = 0isize;
+
+        for hunk in self.hunks() {
+            let first = hunk.first().expect("Hunks are never empty");
+            let last = hunk.last().expect("Hunks are never empty");
+            let start = line_start(&self.source, first.range.start);
+            let end = line_end(&self.source, last.range.end);
+
+            let old = self.source[start..end].split('\n').collect::<Vec<_>>();
+            let new = apply_edits(&self.source, hunk, start..end);
+            let new = new.split('\n').collect::<Vec<_>>();
+
+            let old_line = SourceLocation::from_offset(&self.source, start).line;
+            let new_line = old_line as isize + line_delta;
+            line_delta += new.len() as isize - old.len() as isize;
+
+            diff.push_str(&format!(
+                "@@ -{old_line},{} +{new_line},{} @@\n",
+                old.len(),
+                new.len()
+            ));
+            old.iter()
+                .for_each(|line| diff.push_str(&format!("-{line}\n")));
+            new.iter()
+                .for_each(|line| diff.push_str(&format!("+{line}\n")));
+        }
+
+        diff
+    }
+
+    ///Groups of edits which touch the same lines
+    fn hunks(&self) -> Vec<&[Edit]> {
+        let mut hunks = vec![];
+        let mut start = 0;
+
+        for i in 1..=self.edits.len() {
+            let touches_previous = self.edits.get(i).is_some_and(|edit| {
+                line_start(&self.source, edit.range.start)
+                    <= line_end(&self.source, self.edits[i - 1].range.end)
+            });
+            if !touches_previous {
+                hunks.push(&self.edits[start..i]);
+                start = i;
+            }
+        }
+
+        hunks
+    }
+}
+
+impl Display for DeckRewrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self.path.to_str().unwrap_or("unknown");
+        for edit in self.edits.iter() {
+            let location = SourceLocation::from_offset(&self.source, edit.offset);
+            f.write_fmt(format_args!(
+                "fixed: {path}:{location}: {}\n",
+                edit.description
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(source: &str, offset: usize) -> usize {
+    source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i)
+}
+
+///Applies the sorted edits which fall within the region of the source
+pub(crate) fn apply_edits(source: &str, edits: &[Edit], region: Range<usize>) -> String {
+    let mut fixed = String::with_capacity(region.len());
+    let mut cursor = region.start;
+
+    for edit in edits
+        .iter()
+        .filter(|edit| edit.range.start >= region.start && edit.range.end <= region.end)
+    {
+        fixed.push_str(&source[cursor..edit.range.start]);
+        fixed.push_str(&edit.text);
+        cursor = edit.range.end;
+    }
+    fixed.push_str(&source[cursor..region.end]);
+
+    fixed
+}
+
+pub(crate) fn insert_element(
+    array: &Range<usize>,
+    elements: &[Range<usize>],
+    index: usize,
+    text: &str,
+) -> Edit {
+    match (
+        elements.get(index),
+        index.checked_sub(1).map(|i| &elements[i]),
+    ) {
+        (Some(next), _) => Edit::new(next.start..next.start, format!("{text}, "), ""),
+        (None, Some(previous)) => Edit::new(previous.end..previous.end, format!(", {text}"), ""),
+        (None, None) => Edit::new(array.start + 1..array.start + 1, text, ""),
+    }
+}
+
+pub(crate) fn remove_element(elements: &[Range<usize>], index: usize) -> Edit {
+    let range = match (index, elements.get(1)) {
+        (0, Some(next)) => elements[0].start..next.start,
+        (0, None) => elements[0].clone(),
+        _ => elements[index - 1].end..elements[index].end,
+    };
+
+    Edit::new(range, "", "")
+}
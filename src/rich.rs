@@ -0,0 +1,244 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+
+use ratatui::style::{Color, Modifier, Style};
+
+///Part of a line of text, along with the style given to it by inline markup:
+///`**bold**`, `_italic_` and `[red]colored[/]`
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) struct Piece<'a> {
+    pub text: &'a str,
+    pub bold: bool,
+    pub italic: bool,
+    pub color: Option<Color>,
+}
+
+impl Piece<'_> {
+    pub fn style(&self) -> Style {
+        let mut style = Style::default();
+        if self.bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        if self.italic {
+            style = style.add_modifier(Modifier::ITALIC);
+        }
+        if let Some(color) = self.color {
+            style = style.fg(color);
+        }
+        style
+    }
+}
+
+const BOLD: &str = "**";
+const ITALIC: &str = "_";
+const COLOR_END: &str = "[/]";
+
+///Splits the line into pieces by its markup. Markup is only used when it's closed later on
+///in the line, and `_` only around whole words, so that eg. snake_case is left as it is.
+pub(crate) fn pieces(line: &str) -> Vec<Piece<'_>> {
+    let mut pieces = vec![];
+    let mut current = Piece {
+        text: "",
+        bold: false,
+        italic: false,
+        color: None,
+    };
+    let mut colors: Vec<Color> = vec![];
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < line.len() {
+        let rest = &line[i..];
+        let before = line[..i].chars().next_back();
+
+        let marker = if rest.starts_with(BOLD) && (current.bold || opens(rest, BOLD)) {
+            Some((BOLD.len(), Marker::Bold))
+        } else if rest.starts_with(ITALIC)
+            && (if current.italic {
+                closes_italic(&rest[1..])
+            } else {
+                !before.is_some_and(char::is_alphanumeric) && opens_italic(&rest[1..])
+            })
+        {
+            Some((ITALIC.len(), Marker::Italic))
+        } else if rest.starts_with(COLOR_END) && !colors.is_empty() {
+            Some((COLOR_END.len(), Marker::ColorEnd))
+        } else if rest.starts_with('[') {
+            color_start(rest).map(|(len, color)| (len, Marker::Color(color)))
+        } else {
+            None
+        };
+
+        let Some((len, marker)) = marker else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+
+        if start < i {
+            pieces.push(Piece {
+                text: &line[start..i],
+                ..current
+            });
+        }
+        match marker {
+            Marker::Bold => current.bold = !current.bold,
+            Marker::Italic => current.italic = !current.italic,
+            Marker::Color(color) => colors.push(color),
+            Marker::ColorEnd => {
+                colors.pop();
+            }
+        }
+        current.color = colors.last().copied();
+        i += len;
+        start = i;
+    }
+
+    if start < line.len() {
+        pieces.push(Piece {
+            text: &line[start..],
+            ..current
+        });
+    }
+
+    pieces
+}
+
+enum Marker {
+    Bold,
+    Italic,
+    Color(Color),
+    ColorEnd,
+}
+
+///Whether the marker at the start of the text opens a styled piece, closed later on
+fn opens(text: &str, marker: &str) -> bool {
+    let inner = &text[marker.len()..];
+    inner.find(marker).is_some_and(|end| end > 0) && !inner.starts_with(char::is_whitespace)
+}
+
+fn opens_italic(inner: &str) -> bool {
+    !inner.starts_with(char::is_whitespace)
+        && inner
+            .match_indices(ITALIC)
+            .any(|(end, _)| end > 0 && closes_italic(&inner[end + 1..]))
+}
+
+fn closes_italic(after: &str) -> bool {
+    !after.starts_with(char::is_alphanumeric)
+}
+
+///Length and color of a `[color]` marker at the start of the text, if it's closed later on.
+///Colors are named like "red" or "light-blue", or given as hex like "#ff8800".
+fn color_start(text: &str) -> Option<(usize, Color)> {
+    let end = text.find(']')?;
+    let name = &text[1..end];
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '#') {
+        return None;
+    }
+    let color = name.parse::<Color>().ok()?;
+    text[end..].contains(COLOR_END).then_some((end + 1, color))
+}
+
+///Whether the line has any markup
+pub(crate) fn has_markup(line: &str) -> bool {
+    pieces(line)
+        .iter()
+        .map(|piece| piece.text.len())
+        .sum::<usize>()
+        != line.len()
+}
+
+///Replaces each piece of each line of the text with the text given for it
+pub(crate) fn replace<'a>(text: &'a str, piece: impl Fn(&Piece) -> String) -> Cow<'a, str> {
+    if !text.lines().any(has_markup) {
+        return Cow::Borrowed(text);
+    }
+
+    text.split('\n')
+        .map(|line| pieces(line).iter().map(&piece).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .into()
+}
+
+///Text without any of its markup, eg. "Eat it" for "**Eat** [red]it[/]"
+pub(crate) fn strip(text: &str) -> Cow<'_, str> {
+    replace(text, |piece| piece.text.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use super::{pieces, strip, Piece};
+
+    #[test]
+    fn splits_styled_text() {
+        let plain = |text| Piece {
+            text,
+            bold: false,
+            italic: false,
+            color: None,
+        };
+
+        assert_eq!(
+            pieces("I **ate** _the_ [red]**apple**[/]!"),
+            [
+                plain("I "),
+                Piece {
+                    bold: true,
+                    ..plain("ate")
+                },
+                plain(" "),
+                Piece {
+                    italic: true,
+                    ..plain("the")
+                },
+                plain(" "),
+                Piece {
+                    bold: true,
+                    color: Some(Color::Red),
+                    ..plain("apple")
+                },
+                plain("!"),
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_markup() {
+        assert_eq!(strip("**Eat** [light-blue]it[/]\n_now_"), "Eat it\nnow");
+        assert_eq!(strip("[#ff8800]Orange[/]"), "Orange");
+
+        //NOTE: Unclosed or unknown markup, and underscores within words, are left as they are
+        for text in [
+            "2 ** 3",
+            "snake_case_name",
+            "_ spaced _",
+            "[1] Footnote [/]",
+            "[nocolor]x[/]",
+            "[red] unclosed",
+            "**unclosed",
+        ] {
+            assert_eq!(strip(text), text);
+        }
+    }
+}
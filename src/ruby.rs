@@ -0,0 +1,178 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+
+use crate::{cloze, deck::Face, rich};
+
+///Part of a face's text, either plain text or text annotated with its reading (furigana),
+///written as `{日本|にほん}`
+#[derive(PartialEq, Eq, Debug)]
+pub(crate) enum Segment<'a> {
+    Text(&'a str),
+    Ruby { base: &'a str, reading: &'a str },
+}
+
+///Splits the text into plain and annotated segments. Braces which don't form an annotation,
+///with a non-empty base and reading separated by a single `|`, are left as plain text.
+pub(crate) fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = vec![];
+    let mut plain_start = 0;
+    let mut search_start = 0;
+
+    while let Some(open) = text[search_start..].find('{').map(|i| i + search_start) {
+        search_start = open + 1;
+
+        let Some(close) = text[open + 1..]
+            .find(['{', '}'])
+            .map(|i| i + open + 1)
+            .filter(|close| text[*close..].starts_with('}'))
+        else {
+            continue;
+        };
+        let Some((base, reading)) = text[open + 1..close].split_once('|') else {
+            continue;
+        };
+        if base.is_empty() || reading.is_empty() || reading.contains('|') {
+            continue;
+        }
+
+        if plain_start < open {
+            segments.push(Segment::Text(&text[plain_start..open]));
+        }
+        segments.push(Segment::Ruby { base, reading });
+        plain_start = close + 1;
+        search_start = close + 1;
+    }
+
+    if plain_start < text.len() {
+        segments.push(Segment::Text(&text[plain_start..]));
+    }
+
+    segments
+}
+
+///Whether the text has any annotations
+pub(crate) fn has_ruby(text: &str) -> bool {
+    segments(text)
+        .iter()
+        .any(|segment| matches!(segment, Segment::Ruby { .. }))
+}
+
+///Replaces each annotation with the text given for its base and reading
+pub(crate) fn replace<'a>(text: &'a str, ruby: impl Fn(&str, &str) -> String) -> Cow<'a, str> {
+    if !has_ruby(text) {
+        return Cow::Borrowed(text);
+    }
+
+    segments(text)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.to_owned(),
+            Segment::Ruby { base, reading } => ruby(base, reading),
+        })
+        .collect::<String>()
+        .into()
+}
+
+///Text as it's read without its readings, eg. "日本語" for "{日本|にほん}語"
+pub(crate) fn plain(text: &str) -> Cow<'_, str> {
+    replace(text, |base, _| base.to_owned())
+}
+
+///Text with its readings in place of the annotated text, eg. "にほん語" for "{日本|にほん}語"
+pub(crate) fn readings(text: &str) -> Cow<'_, str> {
+    replace(text, |_, reading| reading.to_owned())
+}
+
+///Text with each reading in parentheses after the annotated text, eg. "日本(にほん)語"
+pub(crate) fn inline(text: &str) -> Cow<'_, str> {
+    replace(text, |base, reading| format!("{base}({reading})"))
+}
+
+///Whether showing the readings of either face would give away the other, such as when
+///the readings are what's being asked for
+pub(crate) fn gives_away(
+    (question, question_language): (&Face, Option<&str>),
+    (answer, answer_language): (&Face, Option<&str>),
+) -> bool {
+    let read_subface = |face: &str| readings(&rich::strip(&cloze::reveal(face))).into_owned();
+    let read = |face: &Face| match face {
+        Face::Single(face) => vec![read_subface(face)],
+        Face::Multi(faces) => faces.iter().map(|face| read_subface(face)).collect(),
+    };
+
+    (question.has_ruby()
+        && read(question)
+            .iter()
+            .any(|question| answer.is_match(question, answer_language)))
+        || (answer.has_ruby()
+            && read(answer)
+                .iter()
+                .any(|answer| question.is_match(answer, question_language)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deck::Face;
+
+    use super::{gives_away, inline, plain, readings, segments, Segment};
+
+    #[test]
+    fn splits_annotated_text() {
+        assert_eq!(
+            segments("私は{日本|にほん}{人|じん}です"),
+            [
+                Segment::Text("私は"),
+                Segment::Ruby {
+                    base: "日本",
+                    reading: "にほん"
+                },
+                Segment::Ruby {
+                    base: "人",
+                    reading: "じん"
+                },
+                Segment::Text("です"),
+            ]
+        );
+        assert_eq!(segments("{a|b|c} {|x} {{y|z}}").len(), 3);
+        assert_eq!(plain("{a|b|c} {|x} {{y|z}}"), "{a|b|c} {|x} {y}");
+    }
+
+    #[test]
+    fn replaces_annotations() {
+        let text = "{日本|にほん}語";
+        assert_eq!(plain(text), "日本語");
+        assert_eq!(readings(text), "にほん語");
+        assert_eq!(inline(text), "日本(にほん)語");
+        assert_eq!(plain("No {markup}"), "No {markup}");
+    }
+
+    #[test]
+    fn finds_readings_giving_answer_away() {
+        let kanji = Face::Single("{日本|にほん}".to_owned());
+        let hiragana = Face::Single("にほん".to_owned());
+        let english = Face::Single("Japan".to_owned());
+
+        let ja = Some("ja");
+        assert!(gives_away((&kanji, ja), (&hiragana, ja)));
+        assert!(gives_away((&hiragana, ja), (&kanji, ja)));
+        assert!(!gives_away((&kanji, ja), (&english, Some("en"))));
+    }
+}
@@ -0,0 +1,432 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{fmt::Display, path::PathBuf};
+
+use crate::{
+    deck::{find_deck_files, is_card_file, parse_deck, CardId, Deck, DeckError, DeckFormat},
+    location::json_deck_spans,
+    rewrite::{apply_edits, insert_element, remove_element, DeckRewrite, Edit},
+    stats::Stats,
+    suggest,
+};
+
+#[derive(Debug, Clone)]
+pub enum FacePosition {
+    End,
+    Before(String),
+    After(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum FaceChange {
+    Add(String, FacePosition),
+    Remove(String),
+    Reorder(Vec<String>),
+}
+
+impl Display for FaceChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add(face, _) => f.write_fmt(format_args!("Added \"{face}\" face")),
+            Self::Remove(face) => f.write_fmt(format_args!("Removed \"{face}\" face")),
+            Self::Reorder(faces) => {
+                f.write_fmt(format_args!("Reordered faces to {}", faces.join(", ")))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SchemaError {
+    Deck(Box<DeckError>),
+    FaceExists {
+        deck: String,
+        face: String,
+    },
+    UnknownFace {
+        deck: String,
+        face: String,
+        suggestion: Option<String>,
+    },
+    InvalidOrder {
+        deck: String,
+        faces: Vec<String>,
+    },
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Deck(err) => f.write_fmt(format_args!("{err}")),
+            Self::FaceExists { deck, face } => f.write_fmt(format_args!(
+                "FaceExists: Deck \"{deck}\" already has a \"{face}\" face"
+            )),
+            Self::UnknownFace {
+                deck,
+                face,
+                suggestion,
+            } => {
+                f.write_fmt(format_args!(
+                    "UnknownFace: Deck \"{deck}\" has no \"{face}\" face"
+                ))?;
+                match suggestion {
+                    Some(suggestion) => {
+                        f.write_fmt(format_args!(", did you mean \"{suggestion}\"?"))
+                    }
+                    None => Ok(()),
+                }
+            }
+            Self::InvalidOrder { deck, faces } => f.write_fmt(format_args!(
+                "InvalidOrder: Faces must list every face of deck \"{deck}\" exactly once: {}",
+                faces.join(", ")
+            )),
+        }
+    }
+}
+
+impl From<DeckError> for SchemaError {
+    fn from(err: DeckError) -> Self {
+        Self::Deck(Box::new(err))
+    }
+}
+
+///Deck file rewritten for a face change, along with the cards whose ids changed
+///because their front did
+#[derive(Debug)]
+pub struct FaceMigration {
+    pub rewrite: DeckRewrite,
+    pub renamed: Vec<(CardId, CardId)>,
+}
+
+///Fails without rewriting anything if the change can't be made to every deck. Decks
+///other than JSON are skipped, and returned alongside the migrations.
+pub fn migrate_paths<P: Into<PathBuf>>(
+    paths: impl IntoIterator<Item = P>,
+    change: &FaceChange,
+) -> Result<(Vec<FaceMigration>, Vec<PathBuf>), SchemaError> {
+    let (paths, skipped): (Vec<_>, Vec<_>) =
+        find_deck_files(paths)?.into_iter().partition(|path| {
+            DeckFormat::from_path(path) == Some(DeckFormat::Json) && !is_card_file(path)
+        });
+
+    let migrations = paths
+        .into_iter()
+        .map(|path| {
+            let source = std::fs::read_to_string(&path)
+                .map_err(|err| DeckError::IoError(path.clone(), err))?;
+            let deck = parse_deck(&path, &source)?;
+            let order = face_order(&deck, change)?;
+            let edits = face_edits(&deck, &source, change, &order);
+
+            //NOTE: The change may still leave cards invalid, eg. with too few faces
+            parse_deck(&path, &apply_edits(&source, &edits, 0..source.len()))?;
+
+            let renamed = deck
+                .iter()
+                .filter_map(|card| {
+                    let front = order
+                        .iter()
+                        .find_map(|face| face.and_then(|face| card[face].as_ref()))?;
+                    let from = CardId::get(&deck, card);
+                    let to = CardId::with_front(&deck.name, front);
+                    (from != to).then_some((from, to))
+                })
+                .collect();
+
+            Ok(FaceMigration {
+                rewrite: DeckRewrite {
+                    path,
+                    source,
+                    edits,
+                },
+                renamed,
+            })
+        })
+        .collect::<Result<_, SchemaError>>()?;
+
+    Ok((migrations, skipped))
+}
+
+pub fn remap_stats(stats: &mut Stats, migrations: &[FaceMigration]) {
+    for migration in migrations {
+        stats.rename_cards(&migration.renamed);
+    }
+}
+
+fn face_index(deck: &Deck, face: &str) -> Result<usize, SchemaError> {
+    deck.faces
+        .iter()
+        .position(|deck_face| deck_face == face)
+        .ok_or_else(|| SchemaError::UnknownFace {
+            deck: deck.name.clone(),
+            face: face.to_owned(),
+            suggestion: suggest(face, &deck.faces.iter().collect::<Vec<_>>()).cloned(),
+        })
+}
+
+///Index of each face after the change within the faces before it, or None for an added face
+fn face_order(deck: &Deck, change: &FaceChange) -> Result<Vec<Option<usize>>, SchemaError> {
+    let mut order = (0..deck.faces.len()).map(Some).collect::<Vec<_>>();
+
+    match change {
+        FaceChange::Add(face, position) => {
+            if deck.faces.contains(face) {
+                return Err(SchemaError::FaceExists {
+                    deck: deck.name.clone(),
+                    face: face.clone(),
+                });
+            }
+
+            let index = match position {
+                FacePosition::End => deck.faces.len(),
+                FacePosition::Before(face) => face_index(deck, face)?,
+                FacePosition::After(face) => face_index(deck, face)? + 1,
+            };
+            order.insert(index, None);
+        }
+        FaceChange::Remove(face) => {
+            order.remove(face_index(deck, face)?);
+        }
+        FaceChange::Reorder(faces) => {
+            order = faces
+                .iter()
+                .map(|face| face_index(deck, face).map(Some))
+                .collect::<Result<_, _>>()?;
+
+            let mut sorted = order.clone();
+            sorted.sort();
+            sorted.dedup();
+            if sorted.len() != deck.faces.len() || order.len() != deck.faces.len() {
+                return Err(SchemaError::InvalidOrder {
+                    deck: deck.name.clone(),
+                    faces: faces.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(order)
+}
+
+fn face_edits(
+    deck: &Deck,
+    source: &str,
+    change: &FaceChange,
+    order: &[Option<usize>],
+) -> Vec<Edit> {
+    let spans = json_deck_spans(source);
+    let Some(faces_span) = spans.key("faces") else {
+        return vec![];
+    };
+
+    let mut arrays = vec![(faces_span, spans.faces.clone(), None)];
+    arrays.extend(
+        spans
+            .cards
+            .iter()
+            .cloned()
+            .zip(spans.card_faces.iter().cloned())
+            .enumerate()
+            .map(|(i, (card, faces))| (card, faces, Some(i))),
+    );
+
+    let mut edits = vec![];
+    for (array, elements, card) in arrays {
+        let description = match card {
+            Some(i) => format!("{change} in cards[{i}]"),
+            None => change.to_string(),
+        };
+
+        //NOTE: Keyed cards don't depend on the order of faces, and leave out null faces
+        if let Some(i) = card.filter(|i| deck[*i].is_keyed()) {
+            if let FaceChange::Remove(face) = change {
+                let entries = &spans.card_keys[i];
+                if let Some(index) = entries.iter().position(|(key, _)| key == face) {
+                    let ranges = entries
+                        .iter()
+                        .map(|(_, range)| range.clone())
+                        .collect::<Vec<_>>();
+                    edits.push(remove_element(&ranges, index));
+                }
+            }
+        } else {
+            match change {
+                FaceChange::Add(face, _) => {
+                    let index = order
+                        .iter()
+                        .position(Option::is_none)
+                        .expect("Added face is always in the order");
+                    let text = match card {
+                        Some(_) => "null".to_owned(),
+                        None => serde_json::to_string(face).expect("Unable to format face"),
+                    };
+                    edits.push(insert_element(&array, &elements, index, &text));
+                }
+                FaceChange::Remove(_) => {
+                    let index = (0..deck.faces.len())
+                        .find(|i| !order.contains(&Some(*i)))
+                        .expect("Removed face is never in the order");
+                    edits.push(remove_element(&elements, index));
+                }
+                FaceChange::Reorder(_) => {
+                    for (new, old) in order.iter().flatten().enumerate() {
+                        if new != *old {
+                            edits.push(Edit::new(
+                                elements[new].clone(),
+                                &source[elements[*old].clone()],
+                                "",
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        for edit in edits.iter_mut().filter(|edit| edit.description.is_empty()) {
+            edit.description = description.clone();
+            edit.offset = array.start;
+        }
+    }
+
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        deck::{parse_deck, CardId, Face},
+        stats::Stats,
+    };
+
+    use super::{migrate_paths, remap_stats, FaceChange, FacePosition, SchemaError};
+
+    const TEST_DECK: &str = "./tests/schema/deck.json";
+
+    fn migrated(change: FaceChange) -> String {
+        let (migrations, _) =
+            migrate_paths(vec![TEST_DECK], &change).expect("Unable to migrate deck");
+        assert_eq!(migrations.len(), 1);
+        migrations[0].rewrite.fixed()
+    }
+
+    #[test]
+    fn adds_face() {
+        let json = migrated(FaceChange::Add(
+            "Example".to_owned(),
+            FacePosition::After("Kanji".to_owned()),
+        ));
+        let deck = parse_deck(TEST_DECK.as_ref(), &json).expect("Unable to parse migrated deck");
+
+        assert_eq!(deck.faces, vec!["Kanji", "Example", "Hiragana", "English"]);
+        assert!(deck.iter().all(|card| card.len() == 4 && card[1].is_none()));
+        assert!(json.contains(r#"["日本", null, "にほん", "Japan"]"#));
+    }
+
+    #[test]
+    fn removes_face() {
+        let json = migrated(FaceChange::Remove("Hiragana".to_owned()));
+        let deck = parse_deck(TEST_DECK.as_ref(), &json).expect("Unable to parse migrated deck");
+
+        assert_eq!(deck.faces, vec!["Kanji", "English"]);
+        assert_eq!(deck[0][1], Some(Face::Single("Japan".to_owned())));
+        assert!(json.contains(r#"{"Kanji": "火", "English": "Fire"}"#));
+    }
+
+    #[test]
+    fn reorders_faces() {
+        let json = migrated(FaceChange::Reorder(vec![
+            "English".to_owned(),
+            "Kanji".to_owned(),
+            "Hiragana".to_owned(),
+        ]));
+        let deck = parse_deck(TEST_DECK.as_ref(), &json).expect("Unable to parse migrated deck");
+
+        assert_eq!(deck.faces, vec!["English", "Kanji", "Hiragana"]);
+        assert_eq!(deck[0][0], Some(Face::Single("Japan".to_owned())));
+        assert_eq!(deck[1][2], None);
+        assert_eq!(deck[3][0], Some(Face::Single("Fire".to_owned())));
+    }
+
+    #[test]
+    fn fails_to_remove_needed_face() {
+        assert!(matches!(
+            migrate_paths(vec![TEST_DECK], &FaceChange::Remove("English".to_owned())),
+            Err(SchemaError::Deck(..))
+        ));
+    }
+
+    #[test]
+    fn fails_on_unknown_or_missing_faces() {
+        assert!(matches!(
+            migrate_paths(vec![TEST_DECK], &FaceChange::Remove("Hiragna".to_owned())),
+            Err(SchemaError::UnknownFace {
+                suggestion: Some(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            migrate_paths(
+                vec![TEST_DECK],
+                &FaceChange::Reorder(vec!["English".to_owned(), "Kanji".to_owned()])
+            ),
+            Err(SchemaError::InvalidOrder { .. })
+        ));
+    }
+
+    #[test]
+    fn skips_decks_not_in_json() {
+        let (migrations, skipped) = migrate_paths(
+            vec!["./tests/schema/formats"],
+            &FaceChange::Remove("Hiragana".to_owned()),
+        )
+        .expect("Unable to migrate decks");
+
+        assert_eq!(migrations.len(), 1);
+        assert!(migrations[0].rewrite.path.ends_with("deck.json"));
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].ends_with("deck.yaml"));
+    }
+
+    #[test]
+    fn remaps_stats_of_changed_fronts() {
+        let (migrations, _) = migrate_paths(
+            vec![TEST_DECK],
+            &FaceChange::Reorder(vec![
+                "English".to_owned(),
+                "Kanji".to_owned(),
+                "Hiragana".to_owned(),
+            ]),
+        )
+        .expect("Unable to migrate deck");
+
+        let mut stats = Stats::new("./tests/schema/stats.json");
+        stats
+            .for_card_mut(migrations[0].renamed[0].0.clone())
+            .correct += 2;
+        remap_stats(&mut stats, &migrations);
+
+        let from = CardId::with_front("Schema", &Face::Single("日本".to_owned()));
+        let to = CardId::with_front("Schema", &Face::Single("Japan".to_owned()));
+        assert_eq!(migrations[0].renamed[0], (from.clone(), to.clone()));
+        assert_eq!(stats.for_card(to).correct, 2);
+        assert_eq!(stats.for_card(from).correct, 0);
+    }
+}
@@ -127,6 +127,54 @@ impl Stats {
         Ok(())
     }
 
+    ///Moves the stats of each card, along with those of its clozes, to its new id, merging
+    ///them with any stats already there. Every card is removed before any are inserted, so
+    ///ids may be swapped.
+    pub fn rename_cards(&mut self, renames: &[(CardId, CardId)]) {
+        let clozes = self
+            .card_stats
+            .keys()
+            .flat_map(|id| {
+                renames.iter().filter_map(|(from, to)| {
+                    let number = id.cloze_of(from)?;
+                    Some((id.clone(), to.with_cloze(number)))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let moved = renames
+            .iter()
+            .chain(clozes.iter())
+            .filter_map(|(from, to)| self.card_stats.remove(from).map(|stats| (to, stats)))
+            .collect::<Vec<_>>();
+
+        for (to, stats) in moved {
+            let card_stats = self.for_card_mut(to.clone());
+            card_stats.correct += stats.correct;
+            card_stats.incorrect += stats.incorrect;
+            card_stats.hinted += stats.hinted;
+        }
+    }
+
+    ///Stats of the card, without adding any for cards that have never been shown
+    pub fn get(&self, id: &CardId) -> Option<&CardStats> {
+        self.card_stats.get(id)
+    }
+
+    ///Stats of the cards added together, such as a card along with its clozes, if any
+    ///have been shown
+    pub fn total(&self, ids: &[CardId]) -> Option<CardStats> {
+        ids.iter()
+            .filter_map(|id| self.get(id))
+            .fold(None, |total: Option<CardStats>, stats| {
+                let mut total = total.unwrap_or_default();
+                total.correct += stats.correct;
+                total.incorrect += stats.incorrect;
+                total.hinted += stats.hinted;
+                Some(total)
+            })
+    }
+
     pub fn for_card(&mut self, id: impl Into<CardId>) -> &CardStats {
         self.for_card_mut(id)
     }
@@ -161,23 +209,81 @@ fn get_home_config_file() -> Result<PathBuf, StatsError> {
 pub struct CardStats {
     pub correct: usize,
     pub incorrect: usize,
+    ///Correct answers which were given after revealing hints
+    #[serde(default)]
+    pub hinted: usize,
 }
 
+///How much a correct answer given after revealing hints counts towards knowing a card
+const HINTED_CORRECT: f64 = 0.5;
+
 impl CardStats {
     pub fn weight(&self) -> f64 {
-        (1.0 / (self.correct.saturating_sub(self.incorrect) + 1) as f64)
-            + self.incorrect.saturating_sub(self.correct) as f64
+        let (known, incorrect) = (self.known(), self.incorrect as f64);
+        (1.0 / ((known - incorrect).max(0.0) + 1.0)) + (incorrect - known).max(0.0)
+    }
+
+    ///Correct answers, where those given with hints count for less
+    fn known(&self) -> f64 {
+        self.correct as f64 - self.hinted as f64 * (1.0 - HINTED_CORRECT)
+    }
+}
+
+///Correct answers beyond incorrect ones for a card to be strong
+const STRONG_MARGIN: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Strength {
+    ///Never answered
+    New,
+    ///Answered incorrectly at least as often as correctly
+    Weak,
+    ///Answered correctly more often than incorrectly
+    Learning,
+    ///Answered correctly at least STRONG_MARGIN more times than incorrectly,
+    ///where correct answers given with hints count for less
+    Strong,
+}
+
+impl Strength {
+    pub const ALL: [Self; 4] = [Self::New, Self::Weak, Self::Learning, Self::Strong];
+
+    pub fn of(stats: Option<&CardStats>) -> Self {
+        match stats {
+            Some(stats) if stats.correct + stats.incorrect > 0 => {
+                let (known, incorrect) = (stats.known(), stats.incorrect as f64);
+                if incorrect >= known {
+                    Self::Weak
+                } else if known >= incorrect + STRONG_MARGIN as f64 {
+                    Self::Strong
+                } else {
+                    Self::Learning
+                }
+            }
+            _ => Self::New,
+        }
+    }
+}
+
+impl Display for Strength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::New => "new",
+            Self::Weak => "weak",
+            Self::Learning => "learning",
+            Self::Strong => "strong",
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        deck::{Card, Deck},
+        deck::{load_decks, Card, CardId, Deck},
         DeckCard,
     };
 
-    use super::Stats;
+    use super::{CardStats, Stats, Strength};
 
     const TEST_STATS_FILE_PATH: &str = "./tests/stats.json";
 
@@ -187,6 +293,7 @@ mod tests {
 
         let deck = Deck {
             name: "test".to_owned(),
+            metadata: Default::default(),
             faces: vec![],
             cards: vec![],
         };
@@ -216,6 +323,7 @@ mod tests {
 
         let deck = Deck {
             name: "test".to_owned(),
+            metadata: Default::default(),
             faces: vec![],
             cards: vec![],
         };
@@ -235,4 +343,51 @@ mod tests {
             assert!(stats.for_card(&deck_card).correct == 1);
         }
     }
+
+    #[test]
+    fn hinted_answers_count_for_less() {
+        let unhinted = CardStats {
+            correct: 3,
+            incorrect: 0,
+            hinted: 0,
+        };
+        let hinted = CardStats {
+            correct: 3,
+            incorrect: 0,
+            hinted: 2,
+        };
+        assert!(hinted.weight() > unhinted.weight());
+        assert_eq!(Strength::of(Some(&unhinted)), Strength::Strong);
+        assert_eq!(Strength::of(Some(&hinted)), Strength::Learning);
+
+        let stats = serde_json::from_str::<CardStats>(r#"{"correct": 1, "incorrect": 2}"#)
+            .expect("Unable to load card stats without hints");
+        assert_eq!(stats.hinted, 0);
+    }
+
+    #[test]
+    fn keeps_stats_per_cloze() {
+        let decks = load_decks(vec!["./tests/cloze.json"]).expect("Unable to load test deck");
+        let (deck, card) = (&decks[0], &decks[0][0]);
+        let ids = CardId::with_clozes(deck, card);
+        let mut stats = Stats::new("");
+
+        stats.for_card_mut(ids[1].clone()).correct += 2;
+        stats.for_card_mut(ids[2].clone()).incorrect += 1;
+        assert!(stats.get(&ids[0]).is_none());
+        assert!(stats
+            .total(&ids)
+            .is_some_and(|total| total.correct == 2 && total.incorrect == 1));
+
+        //NOTE: Clozes move along with their card when it's renamed
+        let renamed = CardId::get(deck, &deck[1]);
+        stats.rename_cards(&[(ids[0].clone(), renamed.clone())]);
+        assert!(stats.get(&ids[1]).is_none());
+        assert!(stats
+            .get(&renamed.with_cloze(1))
+            .is_some_and(|stats| stats.correct == 2));
+        assert!(stats
+            .get(&renamed.with_cloze(2))
+            .is_some_and(|stats| stats.incorrect == 1));
+    }
 }
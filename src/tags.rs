@@ -0,0 +1,321 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{fmt::Display, str::FromStr};
+
+///Boolean expression over card tags, given as eg. `"JLPT N5" & !(verb | adjective)`.
+///
+///Tags are matched case-insensitively, and may be quoted with either `"` or `'` when
+///they contain spaces or operators. Operators may also be written as words: `not`, `and`
+///and `or`. Tags written next to each other without an operator must all match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TagExpr {
+    Tag(String),
+    Not(Box<TagExpr>),
+    And(Box<TagExpr>, Box<TagExpr>),
+    Or(Box<TagExpr>, Box<TagExpr>),
+}
+
+impl TagExpr {
+    pub fn matches<S: AsRef<str>>(&self, tags: &[S]) -> bool {
+        match self {
+            Self::Tag(tag) => tags
+                .iter()
+                .any(|other| other.as_ref().to_lowercase() == tag.to_lowercase()),
+            Self::Not(expr) => !expr.matches(tags),
+            Self::And(left, right) => left.matches(tags) && right.matches(tags),
+            Self::Or(left, right) => left.matches(tags) || right.matches(tags),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&String> {
+        let mut names = vec![];
+        self.collect_names(&mut names);
+        names
+    }
+
+    fn collect_names<'a>(&'a self, names: &mut Vec<&'a String>) {
+        match self {
+            Self::Tag(tag) if !names.contains(&tag) => names.push(tag),
+            Self::Tag(_) => {}
+            Self::Not(expr) => expr.collect_names(names),
+            Self::And(left, right) | Self::Or(left, right) => {
+                left.collect_names(names);
+                right.collect_names(names);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Tag(String),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+const OPERATOR_CHARS: &str = "()!&|";
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            _ if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '!' => tokens.push(Token::Not),
+            '&' => tokens.push(Token::And),
+            '|' => tokens.push(Token::Or),
+            '"' | '\'' => {
+                let mut tag = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => tag.push(next),
+                        None => return Err(format!("Tag expression has an unclosed quote: {s}")),
+                    }
+                }
+                if tag.trim().is_empty() {
+                    return Err(format!("Tag expression has an empty tag: {s}"));
+                }
+                tokens.push(Token::Tag(tag));
+            }
+            _ => {
+                let mut word = String::from(c);
+                while let Some(next) = chars.next_if(|next| {
+                    !next.is_whitespace()
+                        && !OPERATOR_CHARS.contains(*next)
+                        && !"\"'".contains(*next)
+                }) {
+                    word.push(next);
+                }
+                tokens.push(match word.to_lowercase().as_str() {
+                    "not" => Token::Not,
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    _ => Token::Tag(word),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+///Recursive descent parser, where `|` binds loosest, then `&`, then `!`
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    source: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<TagExpr, String> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = TagExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<TagExpr, String> {
+        let mut expr = self.not()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.position += 1,
+                //NOTE: Anything that can start an operand implies an and
+                Some(Token::Tag(_) | Token::Not | Token::Open) => {}
+                _ => return Ok(expr),
+            }
+            expr = TagExpr::And(Box::new(expr), Box::new(self.not()?));
+        }
+    }
+
+    fn not(&mut self) -> Result<TagExpr, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(TagExpr::Not(Box::new(self.not()?))),
+            Some(Token::Tag(tag)) => Ok(TagExpr::Tag(tag)),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err(format!(
+                        "Tag expression has an unclosed parenthesis: {}",
+                        self.source
+                    )),
+                }
+            }
+            Some(_) => Err(format!(
+                "Tag expression has an operator where a tag was expected: {}",
+                self.source
+            )),
+            None => Err(format!(
+                "Tag expression ends where a tag was expected: {}",
+                self.source
+            )),
+        }
+    }
+}
+
+impl FromStr for TagExpr {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+            source: s,
+        };
+
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(Token::Close) => Err(format!("Tag expression has an unopened parenthesis: {s}")),
+            Some(_) => Err(format!("Tag expression has a dangling operator: {s}")),
+        }
+    }
+
+    type Err = String;
+}
+
+impl Display for TagExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operand = |expr: &TagExpr, parent: u8| match expr {
+            Self::And(..) if parent > 1 => format!("({expr})"),
+            Self::Or(..) if parent > 0 => format!("({expr})"),
+            _ => expr.to_string(),
+        };
+
+        match self {
+            Self::Tag(tag)
+                if tag.is_empty()
+                    || tag.chars().any(|c| {
+                        c.is_whitespace() || OPERATOR_CHARS.contains(c) || "\"'".contains(c)
+                    })
+                    || ["not", "and", "or"].contains(&tag.to_lowercase().as_str()) =>
+            {
+                match tag.contains('"') {
+                    true => f.write_fmt(format_args!("'{tag}'")),
+                    false => f.write_fmt(format_args!("\"{tag}\"")),
+                }
+            }
+            Self::Tag(tag) => f.write_str(tag),
+            Self::Not(expr) => f.write_fmt(format_args!("!{}", operand(expr, 2))),
+            Self::And(left, right) => {
+                f.write_fmt(format_args!("{} & {}", operand(left, 1), operand(right, 1)))
+            }
+            Self::Or(left, right) => {
+                f.write_fmt(format_args!("{} | {}", operand(left, 0), operand(right, 0)))
+            }
+        }
+    }
+}
+
+///Cards must match every included expression and none of the excluded ones
+#[derive(Clone, Debug, Default)]
+pub struct TagFilter {
+    include: Vec<TagExpr>,
+    exclude: Vec<TagExpr>,
+}
+
+impl TagFilter {
+    pub fn new(include: Vec<TagExpr>, exclude: Vec<TagExpr>) -> Self {
+        Self { include, exclude }
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        !self.include.is_empty() || !self.exclude.is_empty()
+    }
+
+    pub fn allows<S: AsRef<str>>(&self, tags: &[S]) -> bool {
+        self.include.iter().all(|expr| expr.matches(tags))
+            && !self.exclude.iter().any(|expr| expr.matches(tags))
+    }
+
+    pub fn names(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = vec![];
+        for name in self
+            .include
+            .iter()
+            .chain(self.exclude.iter())
+            .flat_map(TagExpr::names)
+        {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TagExpr, TagFilter};
+
+    fn parse(s: &str) -> TagExpr {
+        s.parse().expect("Unable to parse tag expression")
+    }
+
+    #[test]
+    fn parses_tag_expressions() {
+        assert_eq!(parse("n5").to_string(), "n5");
+        assert_eq!(
+            parse("\"JLPT N5\" & !(verb | adjective)").to_string(),
+            "\"JLPT N5\" & !(verb | adjective)"
+        );
+        assert_eq!(parse("a | b c").to_string(), "a | b & c");
+        assert_eq!(parse("(a or b) and not c").to_string(), "(a | b) & !c");
+        assert_eq!(parse("'say \"hi\"'").to_string(), "'say \"hi\"'");
+
+        for invalid in ["", "a &", "| a", "(a", "a)", "\"a", "!", "a & & b", "''"] {
+            assert!(invalid.parse::<TagExpr>().is_err(), "{invalid} parsed");
+        }
+    }
+
+    #[test]
+    fn matches_tags() {
+        let tags = ["JLPT N5", "verb"];
+
+        assert!(parse("'jlpt n5'").matches(&tags));
+        assert!(parse("verb | adjective").matches(&tags));
+        assert!(!parse("verb & adjective").matches(&tags));
+        assert!(parse("!adjective").matches(&tags));
+        assert!(!parse("\"JLPT N5\" & !(verb | adjective)").matches(&tags));
+
+        let filter = TagFilter::new(vec![parse("verb")], vec![parse("'JLPT N4'")]);
+        assert!(filter.allows(&tags));
+        assert!(!filter.allows(&["verb", "JLPT N4"]));
+        assert!(!filter.allows::<&str>(&[]));
+        assert!(TagFilter::default().allows::<&str>(&[]));
+    }
+}
@@ -56,7 +56,6 @@ impl TerminalWrapper {
         Ok(())
     }
 
-    #[allow(unused)]
     pub fn render_widget(&mut self, widget: impl Widget) -> Result<(), FlashrError> {
         self.draw(|frame| frame.render_widget(widget, frame.area()))
     }
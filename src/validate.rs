@@ -0,0 +1,489 @@
+/*
+ * Copyright (C) 2024 Clownvin <123clownvin@gmail.com>
+ *
+ * This file is part of Flashr.
+ *
+ * Flashr is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Flashr is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Flashr.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::{
+    fmt::Display,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    compose::compose_deck,
+    deck::{
+        deck_issues, find_deck_files, is_card_file, Card, Deck, DeckError, DeckFormat, DeckIssue,
+        Face,
+    },
+    language,
+    location::SourceLocation,
+    FlashrError,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub path: PathBuf,
+    pub location: Option<SourceLocation>,
+    pub card: Option<usize>,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "{}: {}",
+            self.severity,
+            self.path.to_str().unwrap_or("unknown")
+        ))?;
+        if let Some(location) = self.location {
+            f.write_fmt(format_args!(":{location}"))?;
+        }
+        if let Some(card) = self.card {
+            f.write_fmt(format_args!(": cards[{card}]"))?;
+        }
+        f.write_fmt(format_args!(": {}", self.message))
+    }
+}
+
+#[derive(Default)]
+pub struct ValidationReport {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    }
+
+    pub fn into_result(self, deny_warnings: bool) -> Result<(), FlashrError> {
+        let errors = self.count(Severity::Error);
+        let warnings = self.count(Severity::Warning);
+
+        if errors > 0 || (deny_warnings && warnings > 0) {
+            Err(FlashrError::Validation { errors, warnings })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        path: &Path,
+        (card, location): (Option<usize>, Option<SourceLocation>),
+        message: impl Into<String>,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            path: path.to_path_buf(),
+            location,
+            card,
+            message: message.into(),
+        });
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for diagnostic in self.diagnostics.iter() {
+            f.write_fmt(format_args!("{diagnostic}\n"))?;
+        }
+        f.write_fmt(format_args!(
+            "{} errors, {} warnings\n",
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        ))
+    }
+}
+
+///Validates every deck file in the given paths, collecting all errors and warnings
+///rather than stopping at the first
+pub fn validate_paths<P: Into<PathBuf>>(
+    paths: impl IntoIterator<Item = P>,
+) -> Result<ValidationReport, DeckError> {
+    let mut report = ValidationReport::default();
+    let mut deck_names: Vec<(String, PathBuf)> = vec![];
+    let mut seen_cards: Vec<(Card, PathBuf, usize)> = vec![];
+    let mut card_files = vec![];
+    let mut included = vec![];
+
+    for path in find_deck_files(paths)? {
+        //NOTE: Files of cards are checked by the decks including them
+        if is_card_file(&path) {
+            card_files.push(path);
+            continue;
+        }
+
+        let file_start = report.diagnostics.len();
+        let json = match std::fs::read_to_string(&path) {
+            Ok(json) => json,
+            Err(err) => {
+                report.push(Severity::Error, &path, (None, None), err.to_string());
+                continue;
+            }
+        };
+
+        let format = DeckFormat::from_path(&path).unwrap_or(DeckFormat::Json);
+        let deck: Deck = match format.parse(&path, &json) {
+            Ok(deck) => deck,
+            Err(err) => {
+                report.push(
+                    Severity::Error,
+                    &path,
+                    (None, err.location(&json)),
+                    err.to_string(),
+                );
+                continue;
+            }
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        included.extend(
+            deck.metadata
+                .include
+                .iter()
+                .map(|include| dir.join(include)),
+        );
+
+        //NOTE: Included cards come after the deck's own, and are checked within their own files
+        let own_cards = deck.len();
+        let deck = match compose_deck(deck, &path, &mut vec![]) {
+            Ok(deck) => deck,
+            Err(err) => {
+                report.push(Severity::Error, &path, (None, None), err.to_string());
+                continue;
+            }
+        };
+
+        let spans = format.spans(&json);
+        let location_of = |span: Option<&Range<usize>>| {
+            span.map(|span| SourceLocation::from_offset(&json, span.start))
+        };
+        let card_at = |card: usize| (Some(card), location_of(spans.cards.get(card)));
+        let faces_location = location_of(spans.key("faces").as_ref());
+
+        for issue in deck_issues(&deck) {
+            match issue {
+                DeckIssue::NotEnoughFaces => report.push(
+                    Severity::Error,
+                    &path,
+                    (None, faces_location),
+                    format!(
+                        "Deck \"{}\" does not have enough faces. Requires two, has {}",
+                        deck.name,
+                        deck.faces.len()
+                    ),
+                ),
+                DeckIssue::DuplicateFace(face) => report.push(
+                    Severity::Error,
+                    &path,
+                    (None, faces_location),
+                    format!("Deck \"{}\" has more than one \"{face}\" face", deck.name),
+                ),
+                DeckIssue::InvalidCard(card, err) => {
+                    report.push(Severity::Error, &path, card_at(card), err.to_string())
+                }
+            }
+        }
+
+        for (card, warning) in deck_warnings(&deck)
+            .into_iter()
+            .filter(|(card, _)| *card < own_cards)
+        {
+            report.push(Severity::Warning, &path, card_at(card), warning);
+        }
+
+        let languages_location = location_of(spans.key("languages").as_ref());
+        for warning in language_warnings(&deck) {
+            report.push(
+                Severity::Warning,
+                &path,
+                (None, languages_location),
+                warning,
+            );
+        }
+
+        let info_faces_location = location_of(spans.key("info_faces").as_ref());
+        for face in deck
+            .metadata
+            .info_faces
+            .iter()
+            .filter(|face| !deck.faces.contains(face))
+        {
+            report.push(
+                Severity::Warning,
+                &path,
+                (None, info_faces_location),
+                format!("Info face \"{face}\" is not a face of the deck"),
+            );
+        }
+
+        //NOTE: Identical cards within the same deck are already errors
+        let first_seen = seen_cards.len();
+        for (i, card) in deck.iter().enumerate().take(own_cards) {
+            match seen_cards[..first_seen]
+                .iter()
+                .find(|(seen, ..)| seen == card)
+            {
+                Some((_, other_path, j)) => report.push(
+                    Severity::Warning,
+                    &path,
+                    card_at(i),
+                    format!(
+                        "Card is identical to cards[{j}] in {}",
+                        other_path.to_str().unwrap_or("unknown")
+                    ),
+                ),
+                None => seen_cards.push((card.clone(), path.clone(), i)),
+            }
+        }
+
+        match deck_names.iter().find(|(name, _)| *name == deck.name) {
+            Some((name, other_path)) => report.push(
+                Severity::Error,
+                &path,
+                (None, None),
+                format!(
+                    "Deck name \"{name}\" is already used by {}",
+                    other_path.to_str().unwrap_or("unknown")
+                ),
+            ),
+            None => deck_names.push((deck.name, path)),
+        }
+
+        report.diagnostics[file_start..].sort_by_key(|diagnostic| diagnostic.card);
+    }
+
+    let included = included
+        .iter()
+        .filter_map(|path| std::fs::canonicalize(path).ok())
+        .collect::<Vec<_>>();
+    for path in card_files
+        .iter()
+        .filter(|path| std::fs::canonicalize(path).map_or(true, |path| !included.contains(&path)))
+    {
+        report.push(
+            Severity::Warning,
+            path,
+            (None, None),
+            "File of cards is not included by any deck, so its cards are never used or checked"
+                .to_owned(),
+        );
+    }
+
+    Ok(report)
+}
+
+fn language_warnings(deck: &Deck) -> Vec<String> {
+    let mut warnings = vec![];
+
+    for (face, language) in deck.metadata.languages.iter() {
+        if !deck.faces.contains(face) {
+            warnings.push(format!(
+                "Language \"{language}\" is given for \"{face}\", which is not a face of the deck"
+            ));
+        }
+        if !language::is_valid_tag(language) {
+            warnings.push(format!(
+                "Language \"{language}\" of \"{face}\" face is not a BCP-47 language tag, eg. \"ja\" or \"en-US\""
+            ));
+        }
+    }
+
+    warnings
+}
+
+fn deck_warnings(deck: &Deck) -> Vec<(usize, String)> {
+    let mut warnings = vec![];
+
+    for (i, card) in deck.iter().enumerate() {
+        for (deck_face, card_face) in deck.faces.iter().zip(card.iter()) {
+            let strings = match card_face {
+                Some(Face::Single(face)) => vec![face],
+                Some(Face::Multi(faces)) => faces.iter().collect(),
+                None => continue,
+            };
+
+            if card_face
+                .as_ref()
+                .is_some_and(|face| face.is_multi_and(|faces| faces.len() == 1))
+            {
+                warnings.push((
+                    i,
+                    format!(
+                        "\"{deck_face}\" face has only one subface, and could be a plain string"
+                    ),
+                ));
+            }
+
+            for string in strings {
+                if string.is_empty() {
+                    warnings.push((i, format!("\"{deck_face}\" face contains an empty string")));
+                } else if string.trim() != string {
+                    warnings.push((
+                        i,
+                        format!(
+                            "\"{deck_face}\" face \"{string}\" has leading or trailing whitespace"
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    let normalize = |face: &Face| {
+        face.join()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    let fronts = deck
+        .iter()
+        .map(|card| card.front().map(|front| (front, normalize(front))))
+        .collect::<Vec<_>>();
+
+    for (j, front_b) in fronts.iter().enumerate() {
+        let Some((front_b, normalized_b)) = front_b else {
+            continue;
+        };
+
+        //NOTE: Exact duplicates are already errors
+        if let Some((i, (front_a, _))) =
+            fronts.iter().take(j).enumerate().find_map(|(i, front_a)| {
+                front_a
+                    .as_ref()
+                    .filter(|(front_a, normalized_a)| {
+                        front_a != front_b && normalized_a == normalized_b
+                    })
+                    .map(|front_a| (i, front_a))
+            })
+        {
+            warnings.push((
+                j,
+                format!("Front \"{front_b}\" is nearly the same as the front of cards[{i}], \"{front_a}\""),
+            ));
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_paths, Severity};
+
+    #[test]
+    fn reports_all_errors() {
+        let report = validate_paths(vec!["./tests/validate/many_errors.json"])
+            .expect("Unable to validate test deck");
+
+        let errors = report
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 3);
+        assert!(errors.iter().all(|error| error.location.is_some()));
+        assert_eq!(errors[0].card, Some(1));
+        assert_eq!(errors[0].location.map(|location| location.line), Some(6));
+    }
+
+    #[test]
+    fn reports_warnings() {
+        let report = validate_paths(vec!["./tests/validate/warnings.json"])
+            .expect("Unable to validate test deck");
+
+        assert_eq!(report.count(Severity::Error), 0);
+        assert_eq!(report.count(Severity::Warning), 3);
+        assert!(report.into_result(false).is_ok());
+    }
+
+    #[test]
+    fn reports_language_warnings() {
+        let report = validate_paths(vec!["./tests/validate/bad_languages.json"])
+            .expect("Unable to validate test deck");
+
+        assert_eq!(report.count(Severity::Error), 0);
+        assert_eq!(report.count(Severity::Warning), 2);
+        assert!(report.diagnostics.iter().all(|diagnostic| diagnostic
+            .location
+            .is_some_and(|location| location.line == 3)));
+    }
+
+    #[test]
+    fn reports_duplicate_deck_names() {
+        let report = validate_paths(vec!["./tests/duplicate_deck_names"])
+            .expect("Unable to validate test decks");
+
+        assert_eq!(report.count(Severity::Error), 1);
+        assert!(report.into_result(false).is_err());
+    }
+
+    #[test]
+    fn reports_serde_errors() {
+        let report = validate_paths(vec!["./tests/validate/malformed.json"])
+            .expect("Unable to validate test deck");
+
+        assert_eq!(report.count(Severity::Error), 1);
+        assert!(report.diagnostics[0].location.is_some());
+    }
+
+    #[test]
+    fn reports_card_files_not_included() {
+        let report =
+            validate_paths(vec!["./tests/compose"]).expect("Unable to validate test decks");
+        assert_eq!(report.count(Severity::Warning), 0);
+
+        let report = validate_paths(vec!["./tests/compose/extra.cards.yaml"])
+            .expect("Unable to validate test cards");
+        assert_eq!(report.count(Severity::Error), 0);
+        assert_eq!(report.count(Severity::Warning), 1);
+
+        //NOTE: A list of cards not named as a file of cards is a malformed deck
+        let report = validate_paths(vec!["./tests/validate/card_list.json"])
+            .expect("Unable to validate test deck");
+        assert_eq!(report.count(Severity::Error), 1);
+    }
+
+    #[test]
+    fn reports_identical_cards_across_files() {
+        let report =
+            validate_paths(vec!["./tests/duplicate_cards"]).expect("Unable to validate test decks");
+
+        assert_eq!(report.count(Severity::Error), 0);
+        assert_eq!(report.count(Severity::Warning), 3);
+    }
+}
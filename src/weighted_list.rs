@@ -222,58 +222,58 @@ mod tests {
     #[repr(transparent)]
     struct W(usize);
 
-    #[test]
-    fn bench_weighted_list_change_weight() {
-        impl<T> WeightedList<T> {
-            fn get_mut(&mut self, rng: &mut ThreadRng) -> Option<(&mut T, usize)> {
-                match self.len() {
-                    0 => None,
-                    1 => self.items.first_mut().map(|(val, _)| (val, 0)),
-                    _ => {
-                        let needle = rng.gen_range(0.0..self.total_weight);
-                        let mut running_total = 0.0;
-
-                        for (i, (item, weight)) in self.items.iter_mut().enumerate() {
-                            running_total += *weight;
-                            if needle < running_total {
-                                return Some((item, i));
-                            }
+    impl<T> WeightedList<T> {
+        fn get_mut(&mut self, rng: &mut ThreadRng) -> Option<(&mut T, usize)> {
+            match self.len() {
+                0 => None,
+                1 => self.items.first_mut().map(|(val, _)| (val, 0)),
+                _ => {
+                    let needle = rng.gen_range(0.0..self.total_weight);
+                    let mut running_total = 0.0;
+
+                    for (i, (item, weight)) in self.items.iter_mut().enumerate() {
+                        running_total += *weight;
+                        if needle < running_total {
+                            return Some((item, i));
                         }
-
-                        panic!("Reached end without finding match");
                     }
+
+                    panic!("Reached end without finding match");
                 }
             }
+        }
 
-            fn change_weight_bench(&mut self, mut index: usize, weight: f64) {
-                assert!(
-                    weight >= 0.0,
-                    "item weight must be greater than or equal to zero, given: {weight}"
-                );
-
-                let item = &mut self.items[index];
-                let old_weight = item.1;
-                self.total_weight = (self.total_weight - old_weight) + weight;
-                item.1 = weight;
-
-                //NOTE: Benchmarking that this is slower than not sorting
-                if old_weight < weight {
-                    //Bubble up
-                    while index > 0 && self.items[index - 1].1 < weight {
-                        self.items.swap(index, index - 1);
-                        index -= 1;
-                    }
-                } else {
-                    //Bubble down
-                    let max = self.len() - 1;
-                    while index < max && self.items[index + 1].1 > weight {
-                        self.items.swap(index, index + 1);
-                        index += 1;
-                    }
+        fn change_weight_bench(&mut self, mut index: usize, weight: f64) {
+            assert!(
+                weight >= 0.0,
+                "item weight must be greater than or equal to zero, given: {weight}"
+            );
+
+            let item = &mut self.items[index];
+            let old_weight = item.1;
+            self.total_weight = (self.total_weight - old_weight) + weight;
+            item.1 = weight;
+
+            //NOTE: Benchmarking that this is slower than not sorting
+            if old_weight < weight {
+                //Bubble up
+                while index > 0 && self.items[index - 1].1 < weight {
+                    self.items.swap(index, index - 1);
+                    index -= 1;
+                }
+            } else {
+                //Bubble down
+                let max = self.len() - 1;
+                while index < max && self.items[index + 1].1 > weight {
+                    self.items.swap(index, index + 1);
+                    index += 1;
                 }
             }
         }
+    }
 
+    #[test]
+    fn bench_weighted_list_change_weight() {
         let list = (0..200)
             .map(|_| (W(20), 1.0 / (20 + 1) as f64))
             .collect::<WeightedList<_>>();
@@ -323,77 +323,77 @@ mod tests {
         );
     }
 
-    #[test]
-    fn bench_weighted_list_iterator() {
-        struct WeightedListIterator<'a, T> {
-            list: &'a WeightedList<T>,
-            seen: Vec<usize>,
-            remaining_weight: f64,
-            rng: &'a mut ThreadRng,
-        }
+    struct WeightedListIterator<'a, T> {
+        list: &'a WeightedList<T>,
+        seen: Vec<usize>,
+        remaining_weight: f64,
+        rng: &'a mut ThreadRng,
+    }
 
-        impl<'a, T> WeightedListIterator<'a, T> {
-            fn new(list: &'a WeightedList<T>, rng: &'a mut ThreadRng) -> Self {
-                Self {
-                    list,
-                    seen: Vec::with_capacity(10),
-                    remaining_weight: list.total_weight,
-                    rng,
-                }
+    impl<'a, T> WeightedListIterator<'a, T> {
+        fn new(list: &'a WeightedList<T>, rng: &'a mut ThreadRng) -> Self {
+            Self {
+                list,
+                seen: Vec::with_capacity(10),
+                remaining_weight: list.total_weight,
+                rng,
             }
         }
+    }
 
-        impl<'a, T> Iterator for WeightedListIterator<'a, T>
-        where
-            T: PartialEq,
-        {
-            type Item = (&'a T, usize);
-
-            fn next(&mut self) -> Option<Self::Item> {
-                match self.list.len() - self.seen.len() {
-                    0 => None,
-                    1 => {
-                        let (item, i) = self
-                            .list
-                            .items
-                            .iter()
-                            .enumerate()
-                            .find(|(i, _)| !self.seen.contains(i))
-                            .map(|(i, (ref item, _))| (item, i))
-                            .expect("Unable to find not-yet-seen index");
-
-                        self.seen.push(i);
-                        Some((item, i))
-                    }
-                    _ => {
-                        let needle = self.rng.gen_range(0.0..self.remaining_weight);
-                        let mut running_total = 0.0;
-
-                        for (i, (item, weight)) in self.list.items.iter().enumerate() {
-                            if self.seen.contains(&i) {
-                                continue;
-                            }
-
-                            running_total += weight;
-                            if needle < running_total {
-                                self.remaining_weight -= weight;
-                                self.seen.push(i);
-                                return Some((item, i));
-                            }
+    impl<'a, T> Iterator for WeightedListIterator<'a, T>
+    where
+        T: PartialEq,
+    {
+        type Item = (&'a T, usize);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            match self.list.len() - self.seen.len() {
+                0 => None,
+                1 => {
+                    let (item, i) = self
+                        .list
+                        .items
+                        .iter()
+                        .enumerate()
+                        .find(|(i, _)| !self.seen.contains(i))
+                        .map(|(i, (ref item, _))| (item, i))
+                        .expect("Unable to find not-yet-seen index");
+
+                    self.seen.push(i);
+                    Some((item, i))
+                }
+                _ => {
+                    let needle = self.rng.gen_range(0.0..self.remaining_weight);
+                    let mut running_total = 0.0;
+
+                    for (i, (item, weight)) in self.list.items.iter().enumerate() {
+                        if self.seen.contains(&i) {
+                            continue;
                         }
 
-                        panic!("Reached end without finding match");
+                        running_total += weight;
+                        if needle < running_total {
+                            self.remaining_weight -= weight;
+                            self.seen.push(i);
+                            return Some((item, i));
+                        }
                     }
+
+                    panic!("Reached end without finding match");
                 }
             }
         }
+    }
 
-        impl<T> WeightedList<T> {
-            fn iter<'a>(&'a self, rng: &'a mut ThreadRng) -> WeightedListIterator<'a, T> {
-                WeightedListIterator::new(self, rng)
-            }
+    impl<T> WeightedList<T> {
+        fn iter<'a>(&'a self, rng: &'a mut ThreadRng) -> WeightedListIterator<'a, T> {
+            WeightedListIterator::new(self, rng)
         }
+    }
 
+    #[test]
+    fn bench_weighted_list_iterator() {
         let list = (0..2000)
             .map(|i: usize| ((i, i, i, i), 1.0 / 20.0))
             .collect::<WeightedList<_>>();